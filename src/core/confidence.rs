@@ -1,4 +1,7 @@
+use crate::core::policy::ConfidencePolicy;
+
 pub fn score_confidence(
+    policy: &ConfidencePolicy,
     has_parser: bool,
     has_ocr: bool,
     similarity: Option<f32>,
@@ -6,26 +9,26 @@ pub fn score_confidence(
 ) -> f32 {
     let mut score: f32 = 0.0;
     if has_parser {
-        score += 0.4;
+        score += policy.parser_weight;
     }
     if has_ocr {
-        score += 0.3;
+        score += policy.ocr_weight;
     }
 
     if let Some(sim) = similarity {
-        if sim >= 0.9 {
-            score += 0.3;
-        } else if sim >= 0.7 {
-            score += 0.15;
+        if sim >= policy.high_similarity {
+            score += policy.high_similarity_bonus;
+        } else if sim >= policy.mid_similarity {
+            score += policy.mid_similarity_bonus;
         } else {
-            score -= 0.2;
+            score -= policy.low_similarity_penalty;
         }
     }
 
     if geometry_good {
-        score += 0.1;
+        score += policy.geometry_bonus;
     } else {
-        score -= 0.1;
+        score -= policy.geometry_penalty;
     }
 
     score.clamp(0.0, 1.0)
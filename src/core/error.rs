@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// Failures that callers are expected to tell apart from a generic error,
+/// e.g. batch reports separating encrypted files from corrupt ones. They
+/// travel inside `anyhow::Error`; find them with [`DocumentError::find`].
+#[derive(Debug, Error)]
+pub enum DocumentError {
+    #[error("document is encrypted: {} (supply --password or DOCSTRUCT_PDF_PASSWORD)", path.display())]
+    Encrypted { path: PathBuf },
+    #[error("incorrect password for encrypted document: {}", path.display())]
+    IncorrectPassword { path: PathBuf },
+}
+
+impl DocumentError {
+    /// The first `DocumentError` in an error's context chain.
+    pub fn find(err: &anyhow::Error) -> Option<&DocumentError> {
+        err.chain().find_map(|cause| cause.downcast_ref())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        matches!(
+            self,
+            DocumentError::Encrypted { .. } | DocumentError::IncorrectPassword { .. }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn found_through_context_layers() {
+        let err = Err::<(), _>(DocumentError::Encrypted {
+            path: PathBuf::from("secret.pdf"),
+        })
+        .context("failed to count pages")
+        .context("Failed to process document")
+        .unwrap_err();
+        assert!(DocumentError::find(&err).is_some_and(DocumentError::is_encrypted));
+        assert!(DocumentError::find(&anyhow::anyhow!("corrupt xref")).is_none());
+    }
+}
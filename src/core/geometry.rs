@@ -38,6 +38,15 @@ impl BBox {
         }
     }
 
+    pub fn scale(&self, sx: f32, sy: f32) -> Self {
+        Self {
+            x0: self.x0 * sx,
+            y0: self.y0 * sy,
+            x1: self.x1 * sx,
+            y1: self.y1 * sy,
+        }
+    }
+
     pub fn iou(&self, other: &Self) -> f32 {
         let x0 = self.x0.max(other.x0);
         let y0 = self.y0.max(other.y0);
@@ -59,6 +68,115 @@ impl BBox {
         let (cx2, cy2) = other.center();
         ((cx1 - cx2).powi(2) + (cy1 - cy2).powi(2)).sqrt()
     }
+
+    /// Share of this box covered by the union of `boxes`, counting
+    /// overlapping boxes once.
+    pub fn coverage_by(&self, boxes: &[BBox]) -> f32 {
+        let area = self.area();
+        if area <= 0.0 {
+            return 0.0;
+        }
+        // Split the box along every clipped edge; each cell is either
+        // wholly covered by some box or not at all.
+        let edges = |lo: f32, hi: f32, ends: &dyn Fn(&BBox) -> [f32; 2]| {
+            let mut edges: Vec<f32> = boxes
+                .iter()
+                .flat_map(ends)
+                .map(|edge| edge.clamp(lo, hi))
+                .chain([lo, hi])
+                .collect();
+            edges.sort_by(f32::total_cmp);
+            edges.dedup();
+            edges
+        };
+        let xs = edges(self.x0, self.x1, &|b| [b.x0, b.x1]);
+        let ys = edges(self.y0, self.y1, &|b| [b.y0, b.y1]);
+        let mut covered = 0.0;
+        for x in xs.windows(2) {
+            for y in ys.windows(2) {
+                let (cx, cy) = ((x[0] + x[1]) * 0.5, (y[0] + y[1]) * 0.5);
+                if boxes
+                    .iter()
+                    .any(|b| cx >= b.x0 && cx <= b.x1 && cy >= b.y0 && cy <= b.y1)
+                {
+                    covered += (x[1] - x[0]) * (y[1] - y[0]);
+                }
+            }
+        }
+        (covered / area).clamp(0.0, 1.0)
+    }
+}
+
+/// Affine map between two 2D coordinate spaces, in PDF matrix order:
+/// `x' = a*x + c*y + e`, `y' = b*x + d*y + f`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self::scale(1.0, 1.0)
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            b: 0.0,
+            c: 0.0,
+            d: sy,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Axis-aligned box enclosing the transformed corners of `bbox`.
+    pub fn apply_bbox(&self, bbox: &BBox) -> BBox {
+        let corners = [
+            self.apply(bbox.x0, bbox.y0),
+            self.apply(bbox.x1, bbox.y0),
+            self.apply(bbox.x0, bbox.y1),
+            self.apply(bbox.x1, bbox.y1),
+        ];
+        BBox::new(
+            corners.iter().map(|p| p.0).fold(f32::MAX, f32::min),
+            corners.iter().map(|p| p.1).fold(f32::MAX, f32::min),
+            corners.iter().map(|p| p.0).fold(f32::MIN, f32::max),
+            corners.iter().map(|p| p.1).fold(f32::MIN, f32::max),
+        )
+    }
+
+    /// The reverse mapping, or `None` when the transform is degenerate.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Self {
+            a,
+            b,
+            c,
+            d,
+            e: -(a * self.e + c * self.f),
+            f: -(b * self.e + d * self.f),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -73,4 +191,31 @@ mod tests {
         let iou = a.iou(&b);
         assert_eq!(iou, 25.0 / 175.0);
     }
+
+    #[test]
+    fn coverage_counts_overlaps_once() {
+        let page = BBox::new(0.0, 0.0, 100.0, 100.0);
+        let boxes = [
+            BBox::new(0.0, 0.0, 50.0, 50.0),
+            BBox::new(25.0, 25.0, 75.0, 75.0),
+            BBox::new(90.0, 90.0, 120.0, 120.0),
+        ];
+        assert_eq!(
+            page.coverage_by(&boxes),
+            (2500.0 + 2500.0 - 625.0 + 100.0) / 10000.0
+        );
+    }
+
+    #[test]
+    fn transform_round_trips_boxes() {
+        let t = Transform {
+            e: 10.0,
+            f: 20.0,
+            ..Transform::scale(2.0, 3.0)
+        };
+        let bbox = BBox::new(1.0, 1.0, 5.0, 4.0);
+        let mapped = t.apply_bbox(&bbox);
+        assert_eq!(mapped, BBox::new(12.0, 23.0, 20.0, 32.0));
+        assert_eq!(t.inverse().unwrap().apply_bbox(&mapped), bbox);
+    }
 }
@@ -1,4 +1,6 @@
 pub mod confidence;
+pub mod error;
 pub mod geometry;
 pub mod model;
 pub mod page_classifier;
+pub mod policy;
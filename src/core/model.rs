@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::core::geometry::BBox;
+use crate::core::geometry::{BBox, Transform};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -8,6 +8,10 @@ pub enum Provenance {
     Parser,
     Ocr,
     Fused,
+    /// Invisible text another OCR tool left in a scanned PDF (text render
+    /// mode 3). It reads like parser text but is only as good as that tool.
+    #[serde(rename = "text_layer")]
+    TextLayer,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -21,6 +25,107 @@ pub enum PageClass {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentFinal {
     pub pages: Vec<PageFinal>,
+    /// Bookmarks / headings tree, when the source document provides one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outline: Vec<OutlineEntry>,
+    #[serde(default, skip_serializing_if = "DocumentMetadata::is_empty")]
+    pub metadata: DocumentMetadata,
+}
+
+/// Document properties from the PDF info dictionary or OOXML
+/// `docProps/core.xml`. Dates are ISO 8601 strings when the source allows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DocumentMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+    /// Application that authored the content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    /// Library or tool that wrote the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub producer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl DocumentMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.fields().is_empty()
+    }
+
+    /// Present fields with display labels, in a fixed order.
+    pub fn fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("Title", &self.title),
+            ("Author", &self.author),
+            ("Subject", &self.subject),
+            ("Keywords", &self.keywords),
+            ("Creator", &self.creator),
+            ("Producer", &self.producer),
+            ("Created", &self.created),
+            ("Modified", &self.modified),
+            ("Language", &self.language),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((label, value.as_deref()?)))
+        .collect()
+    }
+}
+
+/// One node of the document outline: a PDF bookmark, a DOCX heading or a
+/// PPTX slide title.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutlineEntry {
+    pub title: String,
+    /// Nesting depth, starting at 1 for top-level entries.
+    pub level: usize,
+    /// Target page, when the entry points into the document.
+    pub page_idx: Option<usize>,
+    /// Index into the target page's final blocks, when the title was found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_idx: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<OutlineEntry>,
+}
+
+impl OutlineEntry {
+    pub fn new(title: impl Into<String>, level: usize, page_idx: Option<usize>) -> Self {
+        Self {
+            title: title.into(),
+            level,
+            page_idx,
+            block_idx: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Build a tree from entries listed in document order with their levels,
+    /// as headings are. An entry nests under the closest preceding entry
+    /// with a lower level.
+    pub fn nest(flat: Vec<OutlineEntry>) -> Vec<OutlineEntry> {
+        fn insert(siblings: &mut Vec<OutlineEntry>, entry: OutlineEntry) {
+            match siblings.last_mut() {
+                Some(last) if last.level < entry.level => insert(&mut last.children, entry),
+                _ => siblings.push(entry),
+            }
+        }
+
+        let mut roots = Vec::new();
+        for entry in flat {
+            insert(&mut roots, entry);
+        }
+        roots
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,12 +133,74 @@ pub struct PageFinal {
     pub page_idx: usize,
     pub class: PageClass,
     pub blocks: Vec<Block>,
+    /// Size of the block coordinate space: the rendered page image in pixels.
     pub width: u32,
     pub height: u32,
+    /// Physical page size and the mapping from page points to image pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<PageGeometry>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub debug: Option<PageDebug>,
 }
 
+/// Size of a page as displayed, in PDF points (1/72 inch), with `/Rotate`
+/// already applied. Parser tracks report block coordinates in this space,
+/// top-left origin.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PageSize {
+    pub width: f32,
+    pub height: f32,
+    /// Page `/Rotate` in degrees clockwise: 0, 90, 180 or 270.
+    pub rotation: u16,
+}
+
+/// Both coordinate spaces of a page and the transform between them.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PageGeometry {
+    /// Displayed page size in points (parser space).
+    pub width_pt: f32,
+    pub height_pt: f32,
+    /// `/Rotate` of the page, already reflected in the sizes above.
+    pub rotation: u16,
+    /// Rendered image size in pixels (the space of `PageFinal` blocks).
+    pub image_width: u32,
+    pub image_height: u32,
+    pub dpi: u32,
+    /// Maps page points to image pixels.
+    pub page_to_image: Transform,
+}
+
+impl PageGeometry {
+    /// Geometry for a page rendered to an image of the given pixel size.
+    /// The scale comes from the actual image size rather than the DPI so
+    /// renderer rounding does not accumulate across the page.
+    pub fn new(page: PageSize, image_width: u32, image_height: u32, dpi: u32) -> Self {
+        Self {
+            width_pt: page.width,
+            height_pt: page.height,
+            rotation: page.rotation,
+            image_width,
+            image_height,
+            dpi,
+            page_to_image: Transform::scale(
+                image_width as f32 / page.width.max(1.0),
+                image_height as f32 / page.height.max(1.0),
+            ),
+        }
+    }
+
+    /// Geometry for a page that is not rendered: a virtual image at `dpi`.
+    pub fn unrendered(page: PageSize, dpi: u32) -> Self {
+        let scale = dpi as f32 / 72.0;
+        Self::new(
+            page,
+            (page.width * scale).round().max(1.0) as u32,
+            (page.height * scale).round().max(1.0) as u32,
+            dpi,
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageHypothesis {
     pub page_idx: usize,
@@ -42,10 +209,109 @@ pub struct PageHypothesis {
     pub height: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl PageHypothesis {
+    /// The same hypothesis with every block and span box mapped through
+    /// `transform` into a `width` x `height` coordinate space.
+    pub fn transformed(&self, transform: &Transform, width: u32, height: u32) -> Self {
+        Self {
+            page_idx: self.page_idx,
+            blocks: self
+                .blocks
+                .iter()
+                .map(|block| block.transformed(transform))
+                .collect(),
+            width,
+            height,
+        }
+    }
+
+    /// Move the blocks from `source` out into a hypothesis of their own,
+    /// in the same space.
+    pub fn take_source(&mut self, source: Provenance) -> Self {
+        let (taken, kept) = std::mem::take(&mut self.blocks)
+            .into_iter()
+            .partition(|block| block.provenance() == source);
+        self.blocks = kept;
+        Self {
+            page_idx: self.page_idx,
+            blocks: taken,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PageDebug {
     pub parser_blocks: Vec<Block>,
     pub ocr_blocks: Vec<Block>,
+    /// Blocks from an existing invisible OCR text layer, when the page has
+    /// one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub text_layer_blocks: Vec<Block>,
+    /// How fusion resolved each cluster and which blocks it dropped, in the
+    /// order it decided.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decisions: Vec<FusionDecision>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DecisionAction {
+    /// A parser block and an OCR-side block were weighed against each other.
+    Resolved,
+    /// A block found by one side only was kept.
+    Promoted,
+    /// A block was left out of the final page.
+    Dropped,
+}
+
+/// One fusion decision: the rule that fired, what it saw and what it chose.
+/// For dropped blocks, `provenance` and the texts are the dropped block's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionDecision {
+    pub action: DecisionAction,
+    /// The resolution rule or the filter responsible.
+    pub rule: String,
+    pub bbox: BBox,
+    /// Provenance of the block kept, or of the block dropped.
+    pub provenance: Provenance,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parser_bbox: Option<BBox>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr_bbox: Option<BBox>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parser_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    /// Korean text quality of each reading, when either has Korean.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parser_quality: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr_quality: Option<i32>,
+}
+
+impl FusionDecision {
+    /// A decision about `block` alone, with its text on its own side.
+    pub fn about(action: DecisionAction, rule: &str, block: &Block) -> Self {
+        let text = block.text_content();
+        let from_parser = block.provenance() == Provenance::Parser;
+        Self {
+            action,
+            rule: rule.to_string(),
+            bbox: block.bbox(),
+            provenance: block.provenance(),
+            parser_bbox: from_parser.then(|| block.bbox()),
+            ocr_bbox: (!from_parser).then(|| block.bbox()),
+            parser_text: if from_parser { text.clone() } else { None },
+            ocr_text: if from_parser { None } else { text },
+            similarity: None,
+            parser_quality: None,
+            ocr_quality: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,18 +322,44 @@ pub enum Block {
         lines: Vec<Line>,
         confidence: f32,
         source: Provenance,
+        /// Semantic role, when the source tags it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        role: Option<TextRole>,
+        /// Position in the source's logical reading order, when it defines
+        /// one (a tagged PDF's structure tree).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        order: Option<usize>,
+        /// Fusion tracks whose hypotheses agreed on this block.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tracks: Vec<String>,
         debug: Option<BlockDebug>,
     },
     TableBlock {
         bbox: BBox,
         confidence: f32,
         source: Provenance,
+        /// Cell grid, when the table structure is known. Cells covered by a
+        /// span are not listed separately.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        cells: Vec<TableCell>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        order: Option<usize>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tracks: Vec<String>,
         debug: Option<BlockDebug>,
     },
     FigureBlock {
         bbox: BBox,
         confidence: f32,
         source: Provenance,
+        /// Original embedded image, relative to the output directory, when
+        /// the parser track extracted it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        image: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        order: Option<usize>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tracks: Vec<String>,
         debug: Option<BlockDebug>,
     },
     MathBlock {
@@ -76,27 +368,210 @@ pub enum Block {
         source: Provenance,
         #[serde(skip_serializing_if = "Option::is_none")]
         latex: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        order: Option<usize>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tracks: Vec<String>,
         debug: Option<BlockDebug>,
     },
 }
 
+/// What a text block is in the document, as tagged by the source.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextRole {
+    /// Heading with its level, 1 to 6.
+    Heading(u8),
+    Paragraph,
+    ListItem,
+    Caption,
+    /// Body of a footnote, endnote or comment. Its reference mark, linked
+    /// to the note, leads the first line.
+    Footnote,
+    Endnote,
+    Comment,
+    /// Running header or footer repeated on the page.
+    PageHeader,
+    PageFooter,
+}
+
+impl TextRole {
+    /// Footnotes, endnotes and comments.
+    pub fn is_note(self) -> bool {
+        matches!(
+            self,
+            TextRole::Footnote | TextRole::Endnote | TextRole::Comment
+        )
+    }
+
+    pub fn is_header_or_footer(self) -> bool {
+        matches!(self, TextRole::PageHeader | TextRole::PageFooter)
+    }
+}
+
+/// One cell of a table grid. `row` and `col` are the zero-based indices of
+/// its top-left grid position; merged cells span more than one of each.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableCell {
+    pub row: usize,
+    pub col: usize,
+    pub rowspan: usize,
+    pub colspan: usize,
+    pub bbox: BBox,
+    pub lines: Vec<Line>,
+    /// Part of the table's header rows.
+    #[serde(default)]
+    pub header: bool,
+}
+
+impl TableCell {
+    /// Text of the cell with lines joined by spaces.
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(Line::text)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn is_merged(&self) -> bool {
+        self.rowspan > 1 || self.colspan > 1
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Line {
     pub spans: Vec<Span>,
 }
 
+impl Line {
+    /// Text of the line. Spans carry their own spacing and are concatenated as-is.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Span {
     pub text: String,
     pub bbox: BBox,
     pub source: Provenance,
     pub style: Option<TextStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<LinkTarget>,
+    /// Tracked change the text belongs to, when revisions are annotated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<Revision>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How tracked changes (DOCX `w:ins`/`w:del`) are read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionMode {
+    /// The text with every change accepted.
+    #[default]
+    Accepted,
+    /// The text before any change.
+    Original,
+    /// Inserted and deleted text both, marked with their revision.
+    Annotated,
+}
+
+/// A tracked insertion or deletion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revision {
+    pub kind: RevisionKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// When the change was made, as the source records it (ISO 8601).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionKind {
+    Insertion,
+    Deletion,
+}
+
+/// Where a link points: an external URI, a page of this document, or one
+/// of its notes (from the note's reference mark).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkTarget {
+    Uri(String),
+    Page(usize),
+    Note(NoteRef),
+}
+
+/// A footnote, endnote or comment by its id in the source document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NoteRef {
+    pub kind: NoteKind,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteKind {
+    Footnote,
+    Endnote,
+    Comment,
+}
+
+impl NoteKind {
+    /// Role of the note's text blocks.
+    pub fn role(self) -> TextRole {
+        match self {
+            NoteKind::Footnote => TextRole::Footnote,
+            NoteKind::Endnote => TextRole::Endnote,
+            NoteKind::Comment => TextRole::Comment,
+        }
+    }
+}
+
+/// A link annotation's clickable area on a page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageLink {
+    pub bbox: BBox,
+    pub target: LinkTarget,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TextStyle {
     pub font: Option<String>,
+    /// Font size in points.
     pub size: Option<f32>,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+}
+
+impl TextStyle {
+    /// Style inferred from a font name alone, e.g. `Arial-BoldItalic`,
+    /// `NanumGothicBold` or TeX's `CMBX12` / `CMTI10`.
+    pub fn from_font_name(font: &str, size: Option<f32>) -> Self {
+        let lower = font.to_ascii_lowercase();
+        let bold = ["bold", "black", "heavy", "semibold", "demi"]
+            .iter()
+            .any(|hint| lower.contains(hint))
+            || lower.starts_with("cmbx")
+            || lower.starts_with("cmb10");
+        let italic = ["italic", "oblique"]
+            .iter()
+            .any(|hint| lower.contains(hint))
+            || lower.starts_with("cmti")
+            || lower.starts_with("cmsl")
+            || lower.starts_with("cmbxti");
+        Self {
+            font: Some(font.to_string()),
+            size,
+            bold,
+            italic,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +583,56 @@ pub struct BlockDebug {
 }
 
 impl Block {
+    /// Give spans that sit inside a link area that link. A span belongs to
+    /// a link when its center falls in the area, so partially covered words
+    /// at the edges are not pulled in. Spans that already carry a link keep it.
+    pub fn attach_links(&mut self, links: &[PageLink]) {
+        let Block::TextBlock { lines, .. } = self else {
+            return;
+        };
+        for span in lines.iter_mut().flat_map(|line| line.spans.iter_mut()) {
+            if span.link.is_some() {
+                continue;
+            }
+            let (cx, cy) = span.bbox.center();
+            span.link = links
+                .iter()
+                .find(|link| {
+                    cx >= link.bbox.x0
+                        && cx <= link.bbox.x1
+                        && cy >= link.bbox.y0
+                        && cy <= link.bbox.y1
+                })
+                .map(|link| link.target.clone());
+        }
+    }
+
+    /// The block with its bbox and all span bboxes mapped through `transform`.
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let mut block = self.clone();
+        match &mut block {
+            Block::TextBlock { bbox, lines, .. } => {
+                *bbox = transform.apply_bbox(bbox);
+                for span in lines.iter_mut().flat_map(|line| line.spans.iter_mut()) {
+                    span.bbox = transform.apply_bbox(&span.bbox);
+                }
+            }
+            Block::TableBlock { bbox, cells, .. } => {
+                *bbox = transform.apply_bbox(bbox);
+                for cell in cells.iter_mut() {
+                    cell.bbox = transform.apply_bbox(&cell.bbox);
+                    for span in cell.lines.iter_mut().flat_map(|line| line.spans.iter_mut()) {
+                        span.bbox = transform.apply_bbox(&span.bbox);
+                    }
+                }
+            }
+            Block::FigureBlock { bbox, .. } | Block::MathBlock { bbox, .. } => {
+                *bbox = transform.apply_bbox(bbox)
+            }
+        }
+        block
+    }
+
     pub fn bbox(&self) -> BBox {
         match self {
             Block::TextBlock { bbox, .. }
@@ -138,18 +663,36 @@ impl Block {
     pub fn text_content(&self) -> Option<String> {
         match self {
             Block::TextBlock { lines, .. } => {
-                let text = lines
-                    .iter()
-                    .flat_map(|line| line.spans.iter())
-                    .map(|span| span.text.clone())
-                    .collect::<Vec<_>>()
-                    .join(" ");
+                let text = lines.iter().map(Line::text).collect::<Vec<_>>().join(" ");
                 Some(text)
             }
+            Block::TableBlock { cells, .. } if !cells.is_empty() => {
+                let text = cells.iter().map(TableCell::text).collect::<Vec<_>>();
+                Some(text.join(" "))
+            }
             _ => None,
         }
     }
 
+    /// Reading-order position from the source, if any.
+    pub fn order(&self) -> Option<usize> {
+        match self {
+            Block::TextBlock { order, .. }
+            | Block::TableBlock { order, .. }
+            | Block::FigureBlock { order, .. }
+            | Block::MathBlock { order, .. } => *order,
+        }
+    }
+
+    pub fn tracks(&self) -> &[String] {
+        match self {
+            Block::TextBlock { tracks, .. }
+            | Block::TableBlock { tracks, .. }
+            | Block::FigureBlock { tracks, .. }
+            | Block::MathBlock { tracks, .. } => tracks,
+        }
+    }
+
     pub fn kind(&self) -> &'static str {
         match self {
             Block::TextBlock { .. } => "text",
@@ -159,3 +702,44 @@ impl Block {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_attach_to_spans_centered_in_their_area() {
+        let span = |text: &str, x0: f32, x1: f32| Span {
+            text: text.to_string(),
+            bbox: BBox::new(x0, 0.0, x1, 10.0),
+            source: Provenance::Parser,
+            style: None,
+            link: None,
+            revision: None,
+        };
+        let mut block = Block::TextBlock {
+            bbox: BBox::new(0.0, 0.0, 120.0, 10.0),
+            lines: vec![Line {
+                spans: vec![span("see ", 0.0, 30.0), span("https://x.org", 32.0, 120.0)],
+            }],
+            confidence: 0.9,
+            source: Provenance::Parser,
+            role: None,
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        let target = LinkTarget::Uri("https://x.org".to_string());
+
+        block.attach_links(&[PageLink {
+            bbox: BBox::new(25.0, -1.0, 121.0, 11.0),
+            target: target.clone(),
+        }]);
+
+        let Block::TextBlock { lines, .. } = block else {
+            unreachable!();
+        };
+        assert_eq!(lines[0].spans[0].link, None);
+        assert_eq!(lines[0].spans[1].link, Some(target));
+    }
+}
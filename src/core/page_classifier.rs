@@ -1,26 +1,74 @@
 use crate::core::model::PageClass;
+use crate::core::policy::ClassifyPolicy;
 
 #[derive(Debug, Clone, Copy)]
 pub struct PageSignals {
     pub parser_glyphs: usize,
     pub ocr_glyphs: usize,
+    /// Share of the page under images: embedded images when the page was
+    /// measured, otherwise the largest OCR block.
     pub image_coverage: f32,
     pub ocr_text_density: f32,
+    /// Measurements of the rendered page, when it was rendered.
+    pub pixels: Option<PixelSignals>,
 }
 
-pub fn classify_page(signals: PageSignals) -> PageClass {
-    let parser_score = signals.parser_glyphs as f32;
+/// Signals measured on the rendered page and the images embedded in it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PixelSignals {
+    /// Share of pixels that are ink (dark against the page).
+    pub ink_ratio: f32,
+    /// Share of the page covered by images embedded in the document.
+    pub embedded_image_coverage: f32,
+    /// Share of ink pixels that are isolated specks: scanner noise.
+    pub noise: f32,
+    /// Estimated skew of the text lines, in degrees.
+    pub skew_degrees: f32,
+    /// Share of parser text boxes with rendered ink under them, when the
+    /// parser found text.
+    pub parser_ink_overlap: Option<f32>,
+}
+
+pub fn classify_page(signals: PageSignals, policy: &ClassifyPolicy) -> PageClass {
+    let parser_glyphs = signals.parser_glyphs;
     let ocr_score = signals.ocr_text_density;
     let coverage = signals.image_coverage;
     let ocr_glyphs = signals.ocr_glyphs;
 
-    if signals.parser_glyphs >= 120 && signals.parser_glyphs >= ocr_glyphs.saturating_mul(2) {
+    if let Some(pixels) = signals.pixels {
+        // A page that is one big image is a scan whatever text sits on it;
+        // anywhere else, parser text that shows up as ink is the page's own
+        // text, however much OCR reads in figures around it.
+        let page_is_image = pixels.embedded_image_coverage >= policy.scanned_min_image_coverage;
+        let scan_artifacts = pixels.skew_degrees.abs() >= policy.scanned_min_skew_degrees
+            || pixels.noise >= policy.scanned_min_noise;
+        let parser_visible =
+            pixels.parser_ink_overlap.unwrap_or(0.0) >= policy.digital_min_ink_overlap;
+        if parser_glyphs >= policy.digital_min_parser_glyphs && parser_visible && !page_is_image {
+            return PageClass::Digital;
+        }
+        if (page_is_image || scan_artifacts)
+            && ocr_glyphs >= parser_glyphs.saturating_mul(policy.scanned_ocr_ratio)
+        {
+            return PageClass::Scanned;
+        }
+        if page_is_image && parser_glyphs > 0 {
+            return PageClass::Hybrid;
+        }
+    }
+
+    if parser_glyphs >= policy.digital_min_parser_glyphs
+        && parser_glyphs >= ocr_glyphs.saturating_mul(policy.digital_parser_ratio)
+    {
         PageClass::Digital
-    } else if ocr_glyphs >= signals.parser_glyphs.saturating_mul(2)
-        && (ocr_score > 0.35 || coverage > 0.3)
+    } else if ocr_glyphs >= parser_glyphs.saturating_mul(policy.scanned_ocr_ratio)
+        && (ocr_score > policy.scanned_min_ocr_density || coverage > policy.scanned_min_coverage)
     {
         PageClass::Scanned
-    } else if parser_score > 220.0 && ocr_score < 0.25 && coverage < 0.25 {
+    } else if parser_glyphs > policy.sparse_digital_parser_glyphs
+        && ocr_score < policy.sparse_digital_max_ocr_density
+        && coverage < policy.sparse_digital_max_coverage
+    {
         PageClass::Digital
     } else {
         PageClass::Hybrid
@@ -38,8 +86,12 @@ mod tests {
             ocr_glyphs: 100,
             image_coverage: 0.2,
             ocr_text_density: 0.18,
+            pixels: None,
         };
-        assert_eq!(classify_page(signals), PageClass::Digital);
+        assert_eq!(
+            classify_page(signals, &ClassifyPolicy::default()),
+            PageClass::Digital
+        );
     }
 
     #[test]
@@ -49,7 +101,49 @@ mod tests {
             ocr_glyphs: 300,
             image_coverage: 0.62,
             ocr_text_density: 0.56,
+            pixels: None,
+        };
+        assert_eq!(
+            classify_page(signals, &ClassifyPolicy::default()),
+            PageClass::Scanned
+        );
+    }
+
+    #[test]
+    fn large_figures_do_not_make_a_digital_page_scanned() {
+        // OCR reads plenty of text inside a half-page chart.
+        let signals = PageSignals {
+            parser_glyphs: 300,
+            ocr_glyphs: 900,
+            image_coverage: 0.5,
+            ocr_text_density: 0.9,
+            pixels: Some(PixelSignals {
+                ink_ratio: 0.2,
+                embedded_image_coverage: 0.5,
+                noise: 0.0,
+                skew_degrees: 0.0,
+                parser_ink_overlap: Some(1.0),
+            }),
+        };
+        assert_eq!(
+            classify_page(signals, &ClassifyPolicy::default()),
+            PageClass::Digital
+        );
+
+        let scan = PageSignals {
+            parser_glyphs: 0,
+            ocr_glyphs: 900,
+            image_coverage: 1.0,
+            ocr_text_density: 0.9,
+            pixels: Some(PixelSignals {
+                embedded_image_coverage: 1.0,
+                parser_ink_overlap: None,
+                ..PixelSignals::default()
+            }),
         };
-        assert_eq!(classify_page(signals), PageClass::Scanned);
+        assert_eq!(
+            classify_page(scan, &ClassifyPolicy::default()),
+            PageClass::Scanned
+        );
     }
 }
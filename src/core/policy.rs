@@ -0,0 +1,372 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Every threshold fusion decides by: page classification, confidence
+/// scoring, pair resolution and the page-level filters. The defaults are the
+/// built-in behavior; a policy file only needs the values it changes.
+/// Lengths are in points (1/72 inch) and areas in square points, so one
+/// policy holds at any rendering DPI; see [`FusionPolicy::at_dpi`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FusionPolicy {
+    pub classify: ClassifyPolicy,
+    pub confidence: ConfidencePolicy,
+    pub resolve: ResolvePolicy,
+    pub filter: FilterPolicy,
+}
+
+impl FusionPolicy {
+    /// Load a policy from a JSON file (`.json`) or a TOML file (anything
+    /// else). Missing values keep their defaults; unknown keys are errors,
+    /// so a misspelled threshold is not silently ignored.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read fusion policy {}", path.display()))?;
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let policy = if is_json {
+            serde_json::from_str(&text).map_err(anyhow::Error::from)
+        } else {
+            toml::from_str(&text).map_err(anyhow::Error::from)
+        };
+        policy.with_context(|| format!("invalid fusion policy {}", path.display()))
+    }
+
+    /// The policy with its lengths and areas converted to pixels of a page
+    /// rendered at `dpi`, the space fusion runs in.
+    pub fn at_dpi(&self, dpi: u32) -> Self {
+        let scale = dpi as f32 / 72.0;
+        let mut policy = self.clone();
+        let resolve = &mut policy.resolve;
+        for length in [
+            &mut resolve.good_geometry_distance,
+            &mut resolve.page_sized_edge_tolerance,
+            &mut resolve.column_min_page_width,
+            &mut resolve.column_top_margin,
+            &mut resolve.column_bottom_margin,
+        ] {
+            *length *= scale;
+        }
+        policy.filter.parser_dominant_min_area *= scale * scale;
+        policy
+    }
+}
+
+/// Thresholds for telling digital, scanned and hybrid pages apart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClassifyPolicy {
+    /// Parser glyphs from which a page is digital, if the parser also
+    /// outnumbers OCR by `digital_parser_ratio`.
+    pub digital_min_parser_glyphs: usize,
+    pub digital_parser_ratio: usize,
+    /// OCR glyphs must outnumber parser glyphs by this much for a scanned
+    /// page, which also needs OCR density or image coverage above these.
+    pub scanned_ocr_ratio: usize,
+    pub scanned_min_ocr_density: f32,
+    pub scanned_min_coverage: f32,
+    /// A page with more parser glyphs than this is digital while OCR density
+    /// and coverage stay below these.
+    pub sparse_digital_parser_glyphs: usize,
+    pub sparse_digital_max_ocr_density: f32,
+    pub sparse_digital_max_coverage: f32,
+    /// OCR glyphs that count as a fully dense page.
+    pub dense_page_glyphs: usize,
+    /// On a rendered page: embedded images covering this share of it make
+    /// it a scan, as do this much skew or speckle noise; parser text boxes
+    /// this often over ink are visible text.
+    pub scanned_min_image_coverage: f32,
+    pub scanned_min_skew_degrees: f32,
+    pub scanned_min_noise: f32,
+    pub digital_min_ink_overlap: f32,
+}
+
+impl Default for ClassifyPolicy {
+    fn default() -> Self {
+        Self {
+            digital_min_parser_glyphs: 120,
+            digital_parser_ratio: 2,
+            scanned_ocr_ratio: 2,
+            scanned_min_ocr_density: 0.35,
+            scanned_min_coverage: 0.3,
+            sparse_digital_parser_glyphs: 220,
+            sparse_digital_max_ocr_density: 0.25,
+            sparse_digital_max_coverage: 0.25,
+            dense_page_glyphs: 1000,
+            scanned_min_image_coverage: 0.85,
+            scanned_min_skew_degrees: 0.5,
+            scanned_min_noise: 0.02,
+            digital_min_ink_overlap: 0.8,
+        }
+    }
+}
+
+/// Weights of the base confidence score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfidencePolicy {
+    pub parser_weight: f32,
+    pub ocr_weight: f32,
+    /// Bonus when the two readings are at least `high_similarity` alike, a
+    /// smaller one from `mid_similarity`, a penalty below that.
+    pub high_similarity: f32,
+    pub high_similarity_bonus: f32,
+    pub mid_similarity: f32,
+    pub mid_similarity_bonus: f32,
+    pub low_similarity_penalty: f32,
+    pub geometry_bonus: f32,
+    pub geometry_penalty: f32,
+}
+
+impl Default for ConfidencePolicy {
+    fn default() -> Self {
+        Self {
+            parser_weight: 0.4,
+            ocr_weight: 0.3,
+            high_similarity: 0.9,
+            high_similarity_bonus: 0.3,
+            mid_similarity: 0.7,
+            mid_similarity_bonus: 0.15,
+            low_similarity_penalty: 0.2,
+            geometry_bonus: 0.1,
+            geometry_penalty: 0.1,
+        }
+    }
+}
+
+/// Thresholds for resolving a cluster into one block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResolvePolicy {
+    /// Similarity from which parser and OCR text count as the same reading.
+    pub fused_similarity: f32,
+    /// Similarity from which readings are fused token by token, and the
+    /// OCR block confidence its tokens need to replace parser tokens.
+    pub token_fusion_similarity: f32,
+    pub token_min_ocr_confidence: f32,
+    /// Similarity at which a track agrees with the result, and the
+    /// confidence added per agreeing track beyond a parser/OCR pair.
+    pub agreement_similarity: f32,
+    pub agreement_bonus: f32,
+    /// Paired blocks overlapping this much, or with centers this close,
+    /// have good geometry.
+    pub good_geometry_iou: f32,
+    pub good_geometry_distance: f32,
+    /// Share of the page from which a parser text block is oversized.
+    pub oversized_parser_ratio: f32,
+    /// Below this similarity an oversized parser block yields to OCR outright.
+    pub oversized_min_similarity: f32,
+    /// Below this similarity an oversized parser block yields to clean OCR
+    /// text that is at most `ocr_length_margin` characters shorter.
+    pub oversized_ocr_similarity: f32,
+    pub ocr_length_margin: usize,
+    /// Korean quality (syllables score up, loose jamo down) from which parser
+    /// Korean is reliable, and below which it is degraded.
+    pub reliable_korean_quality: i32,
+    pub degraded_korean_quality: i32,
+    /// Below this similarity, OCR Korean clearly better and longer than the
+    /// parser's replaces it; margins are per page class.
+    pub korean_override_similarity: f32,
+    pub digital_korean_quality_margin: i32,
+    pub digital_korean_length_margin: usize,
+    pub hybrid_korean_quality_margin: i32,
+    pub hybrid_korean_length_margin: usize,
+    /// On hybrid pages, OCR this many characters longer than the parser
+    /// wins below `hybrid_long_ocr_similarity`.
+    pub hybrid_long_ocr_similarity: f32,
+    pub hybrid_long_ocr_margin: usize,
+    /// Confidence adjustments by source and page class.
+    pub degraded_korean_penalty: f32,
+    pub korean_ocr_bonus: f32,
+    pub scanned_ocr_bonus: f32,
+    pub scanned_parser_penalty: f32,
+    /// An unpaired parser block covering the page (within the edge
+    /// tolerance, or over the area ratio) is dropped once OCR found this many
+    /// blocks of its own.
+    pub page_sized_min_ocr_blocks: usize,
+    pub page_sized_edge_tolerance: f32,
+    pub page_sized_area_ratio: f32,
+    /// Reading order splits a page into two columns when it has this many
+    /// blocks over at least this width, at least `column_min_per_side` on
+    /// each side of the middle and `column_min_side_blocks` in all, and at
+    /// most this share of them spanning it. A block crossing the middle
+    /// spans both columns when wider than `column_spanning_width` of the
+    /// page.
+    pub column_min_blocks: usize,
+    pub column_min_page_width: f32,
+    pub column_min_per_side: usize,
+    pub column_min_side_blocks: usize,
+    pub column_max_spanning_share: f32,
+    pub column_spanning_width: f32,
+    /// Spanning blocks ending within `column_top_margin` of the columns' top
+    /// come before them, those starting within `column_bottom_margin` of
+    /// their bottom after them.
+    pub column_top_margin: f32,
+    pub column_bottom_margin: f32,
+}
+
+impl Default for ResolvePolicy {
+    fn default() -> Self {
+        Self {
+            fused_similarity: 0.72,
+            token_fusion_similarity: 0.5,
+            token_min_ocr_confidence: 0.6,
+            agreement_similarity: 0.72,
+            agreement_bonus: 0.05,
+            good_geometry_iou: 0.3,
+            good_geometry_distance: 18.0,
+            oversized_parser_ratio: 0.58,
+            oversized_min_similarity: 0.15,
+            oversized_ocr_similarity: 0.55,
+            ocr_length_margin: 40,
+            reliable_korean_quality: 4,
+            degraded_korean_quality: -2,
+            korean_override_similarity: 0.30,
+            digital_korean_quality_margin: 5,
+            digital_korean_length_margin: 40,
+            hybrid_korean_quality_margin: 4,
+            hybrid_korean_length_margin: 50,
+            hybrid_long_ocr_similarity: 0.35,
+            hybrid_long_ocr_margin: 80,
+            degraded_korean_penalty: 0.2,
+            korean_ocr_bonus: 0.05,
+            scanned_ocr_bonus: 0.08,
+            scanned_parser_penalty: 0.1,
+            page_sized_min_ocr_blocks: 10,
+            page_sized_edge_tolerance: 3.6,
+            page_sized_area_ratio: 0.7,
+            column_min_blocks: 6,
+            column_min_page_width: 108.0,
+            column_min_per_side: 2,
+            column_min_side_blocks: 5,
+            column_max_spanning_share: 0.45,
+            column_spanning_width: 0.58,
+            column_top_margin: 18.0,
+            column_bottom_margin: 10.8,
+        }
+    }
+}
+
+/// Thresholds of the page-level filters run after resolution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterPolicy {
+    /// Parser Korean below this quality is dropped when OCR read better.
+    pub broken_korean_quality: i32,
+    /// Parser text this long over this much area dominates the page, and
+    /// OCR duplicates of it are dropped.
+    pub parser_dominant_min_chars: usize,
+    pub parser_dominant_min_area: f32,
+    /// OCR text this short (ignoring whitespace) is dropped on parser pages.
+    pub short_ocr_chars: usize,
+    /// Parser Korean with this many syllables at this quality makes OCR
+    /// Korean redundant.
+    pub reliable_korean_syllables: usize,
+    pub reliable_korean_min_quality: i32,
+    /// Parser text this long, making up this share of OCR's length, makes
+    /// all OCR text redundant, unless one parser block faces this many OCR
+    /// blocks in two columns.
+    pub reliable_parser_chars: usize,
+    pub reliable_parser_share: f32,
+    pub column_min_ocr_blocks: usize,
+    /// OCR Korean below this quality, or with this many Hanja mixed in, is
+    /// noise.
+    pub noisy_korean_quality: i32,
+    pub noisy_hanja: usize,
+    /// OCR text of at least this many characters using at most
+    /// `noisy_max_distinct_chars` distinct ones is noise.
+    pub noisy_repeat_min_chars: usize,
+    pub noisy_max_distinct_chars: usize,
+    /// OCR text shorter than this is always a duplicate; from
+    /// `duplicate_contained_chars` on, text contained in a parser block is.
+    pub duplicate_min_chars: usize,
+    pub duplicate_contained_chars: usize,
+    /// Similarity at which OCR text duplicates a parser block anywhere, and
+    /// the overlap and similarity at which it does nearby.
+    pub duplicate_similarity: f32,
+    pub duplicate_overlap_iou: f32,
+    pub duplicate_overlap_similarity: f32,
+}
+
+impl Default for FilterPolicy {
+    fn default() -> Self {
+        Self {
+            broken_korean_quality: -10,
+            parser_dominant_min_chars: 120,
+            parser_dominant_min_area: 38_880.0,
+            short_ocr_chars: 3,
+            reliable_korean_syllables: 18,
+            reliable_korean_min_quality: -1,
+            reliable_parser_chars: 220,
+            reliable_parser_share: 0.7,
+            column_min_ocr_blocks: 6,
+            noisy_korean_quality: -6,
+            noisy_hanja: 2,
+            noisy_repeat_min_chars: 6,
+            noisy_max_distinct_chars: 2,
+            duplicate_min_chars: 4,
+            duplicate_contained_chars: 8,
+            duplicate_similarity: 0.82,
+            duplicate_overlap_iou: 0.55,
+            duplicate_overlap_similarity: 0.55,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_policy_files_keep_defaults() {
+        let dir = std::env::temp_dir().join(format!("docstruct-policy-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let toml_path = dir.join("scans.toml");
+        std::fs::write(
+            &toml_path,
+            "[resolve]\nfused_similarity = 0.8\n\n[classify]\ndigital_min_parser_glyphs = 60\n",
+        )
+        .unwrap();
+        let json_path = dir.join("scans.json");
+        std::fs::write(&json_path, r#"{"filter": {"noisy_hanja": 3}}"#).unwrap();
+
+        let from_toml = FusionPolicy::load(&toml_path).unwrap();
+        let from_json = FusionPolicy::load(&json_path).unwrap();
+
+        assert_eq!(from_toml.resolve.fused_similarity, 0.8);
+        assert_eq!(from_toml.classify.digital_min_parser_glyphs, 60);
+        assert_eq!(from_toml.confidence, ConfidencePolicy::default());
+        assert_eq!(from_json.filter.noisy_hanja, 3);
+        assert_eq!(from_json.resolve, ResolvePolicy::default());
+
+        std::fs::write(&toml_path, "[resolve]\nfused_similarty = 0.8\n").unwrap();
+        assert!(FusionPolicy::load(&toml_path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scales_lengths_and_areas_to_the_rendering_dpi() {
+        let policy = FusionPolicy::default();
+        let at_144 = policy.at_dpi(144);
+        assert_eq!(at_144.resolve.good_geometry_distance, 36.0);
+        assert_eq!(at_144.resolve.column_min_page_width, 216.0);
+        assert_eq!(at_144.filter.parser_dominant_min_area, 155_520.0);
+        assert_eq!(
+            at_144.resolve.fused_similarity,
+            policy.resolve.fused_similarity
+        );
+        assert_eq!(policy.at_dpi(72), policy);
+    }
+
+    #[test]
+    fn default_policy_round_trips_through_toml() {
+        let text = toml::to_string(&FusionPolicy::default()).unwrap();
+        assert_eq!(
+            toml::from_str::<FusionPolicy>(&text).unwrap(),
+            FusionPolicy::default()
+        );
+    }
+}
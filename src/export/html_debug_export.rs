@@ -3,8 +3,10 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 
-use crate::core::model::{Block, BlockDebug, DocumentFinal, Provenance};
-use crate::export::Exporter;
+use crate::core::model::{
+    Block, BlockDebug, DecisionAction, DocumentFinal, FusionDecision, LinkTarget, Provenance,
+};
+use crate::export::{emphasis_runs, revision_html, Exporter};
 
 #[derive(Debug, Clone)]
 pub struct HtmlDebugExporter {
@@ -23,7 +25,7 @@ impl HtmlDebugExporter {
         let debug = extract_debug(block);
         let block_type = block_type_label(block);
         format!(
-            r#"<div class='bbox {layer} {block_type}' style='left:{x0}px; top:{y0}px; width:{w}px; height:{h}px;' data-text='{text}' data-type='{block_type}' data-provenance='{prov}' data-confidence='{conf}' data-parser-text='{parser_text}' data-ocr-text='{ocr_text}' data-final-text='{final_text}' data-similarity='{similarity}'></div>"#,
+            r#"<div class='bbox {layer} {block_type}' style='left:{x0}px; top:{y0}px; width:{w}px; height:{h}px;' data-text='{text}' data-html='{styled}' data-type='{block_type}' data-provenance='{prov}' data-confidence='{conf}' data-parser-text='{parser_text}' data-ocr-text='{ocr_text}' data-final-text='{final_text}' data-similarity='{similarity}'></div>"#,
             x0 = bbox.x0,
             y0 = bbox.y0,
             w = bbox.width(),
@@ -31,6 +33,7 @@ impl HtmlDebugExporter {
             layer = layer,
             block_type = block_type,
             text = html_escape::encode_text(&text),
+            styled = html_escape::encode_single_quoted_attribute(&emphasized_html(block)),
             prov = provenance_label(provenance),
             conf = block.confidence(),
             parser_text = html_escape::encode_text(debug.parser_text.as_deref().unwrap_or("")),
@@ -44,6 +47,106 @@ impl HtmlDebugExporter {
     }
 }
 
+/// A block fusion left out, drawn where it was so missing text can be
+/// traced back to the rule that dropped it.
+fn dropped_to_div(decision: &FusionDecision) -> String {
+    let bbox = decision.bbox;
+    let text = decision
+        .parser_text
+        .as_deref()
+        .or(decision.ocr_text.as_deref())
+        .unwrap_or("");
+    format!(
+        r#"<div class='bbox dropped' style='left:{x0}px; top:{y0}px; width:{w}px; height:{h}px;' data-type='dropped' data-provenance='{prov}' data-rule='{rule}' data-html='{text}'></div>"#,
+        x0 = bbox.x0,
+        y0 = bbox.y0,
+        w = bbox.width(),
+        h = bbox.height(),
+        prov = provenance_label(decision.provenance),
+        rule = html_escape::encode_single_quoted_attribute(&decision.rule),
+        text = html_escape::encode_single_quoted_attribute(&html_escape::encode_text(text)),
+    )
+}
+
+/// The page's fusion decisions as table rows, in the order they were made.
+fn decision_rows(decisions: &[FusionDecision]) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    decisions
+        .iter()
+        .map(|decision| {
+            let text = |value: &Option<String>| {
+                html_escape::encode_text(value.as_deref().unwrap_or("")).into_owned()
+            };
+            format!(
+                "<tr class='{action}'><td>{action}</td><td>{rule}</td><td>{prov}</td><td>{sim}</td><td>{quality}</td><td>{parser}</td><td>{ocr}</td></tr>",
+                action = action_label(decision.action),
+                rule = html_escape::encode_text(&decision.rule),
+                prov = provenance_label(decision.provenance),
+                sim = optional(decision.similarity.map(|value| format!("{value:.3}"))),
+                quality = match (decision.parser_quality, decision.ocr_quality) {
+                    (Some(parser), Some(ocr)) => format!("{parser} / {ocr}"),
+                    _ => String::new(),
+                },
+                parser = text(&decision.parser_text),
+                ocr = text(&decision.ocr_text),
+            )
+        })
+        .collect()
+}
+
+fn action_label(action: DecisionAction) -> &'static str {
+    match action {
+        DecisionAction::Resolved => "resolved",
+        DecisionAction::Promoted => "promoted",
+        DecisionAction::Dropped => "dropped",
+    }
+}
+
+/// Block text as HTML with `<strong>`/`<em>` for bold and italic spans,
+/// anchors for linked ones and `<ins>`/`<del>` for tracked changes. Page
+/// links point at that page's debug view; note marks are superscripts.
+fn emphasized_html(block: &Block) -> String {
+    let Block::TextBlock { lines, .. } = block else {
+        return String::new();
+    };
+    lines
+        .iter()
+        .map(|line| {
+            emphasis_runs(&line.spans)
+                .into_iter()
+                .map(|run| {
+                    let mut html = html_escape::encode_text(&run.text).into_owned();
+                    if run.italic {
+                        html = format!("<em>{html}</em>");
+                    }
+                    if run.bold {
+                        html = format!("<strong>{html}</strong>");
+                    }
+                    let html = match &run.link {
+                        Some(LinkTarget::Uri(uri)) => format!(
+                            "<a href=\"{}\">{html}</a>",
+                            html_escape::encode_double_quoted_attribute(uri)
+                        ),
+                        Some(LinkTarget::Page(page_idx)) => {
+                            format!("<a href=\"page_{:03}.html\">{html}</a>", page_idx + 1)
+                        }
+                        // Comment marks have no text of their own.
+                        Some(LinkTarget::Note(note)) => format!(
+                            "<sup class=\"note\" title=\"{:?} {}\">{}</sup>",
+                            note.kind,
+                            html_escape::encode_double_quoted_attribute(&note.id),
+                            if html.is_empty() { "*" } else { &html }
+                        ),
+                        None => html,
+                    };
+                    revision_html(run.revision.as_ref(), html)
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("<br/>")
+}
+
 fn extract_debug(block: &Block) -> BlockDebug {
     match block {
         Block::TextBlock { debug, .. }
@@ -63,6 +166,7 @@ fn provenance_label(prov: Provenance) -> &'static str {
         Provenance::Parser => "parser",
         Provenance::Ocr => "ocr",
         Provenance::Fused => "fused",
+        Provenance::TextLayer => "text_layer",
     }
 }
 
@@ -81,13 +185,29 @@ impl Exporter for HtmlDebugExporter {
         for page in &document.pages {
             let image_path = format!("page_{:03}.png", page.page_idx + 1);
             let mut blocks_html = String::new();
+            let mut decisions_html = String::new();
             if let Some(debug) = &page.debug {
+                for decision in &debug.decisions {
+                    if decision.action == DecisionAction::Dropped {
+                        blocks_html.push_str(&dropped_to_div(decision));
+                    }
+                }
+                if !debug.decisions.is_empty() {
+                    decisions_html = format!(
+                        "<details id='decisions'><summary>Fusion decisions ({count})</summary><table><tr><th>action</th><th>rule</th><th>provenance</th><th>similarity</th><th>ko quality (parser / ocr)</th><th>parser text</th><th>ocr text</th></tr>{rows}</table></details>",
+                        count = debug.decisions.len(),
+                        rows = decision_rows(&debug.decisions),
+                    );
+                }
                 for block in &debug.parser_blocks {
                     blocks_html.push_str(&HtmlDebugExporter::block_to_div(block, "parser"));
                 }
                 for block in &debug.ocr_blocks {
                     blocks_html.push_str(&HtmlDebugExporter::block_to_div(block, "ocr"));
                 }
+                for block in &debug.text_layer_blocks {
+                    blocks_html.push_str(&HtmlDebugExporter::block_to_div(block, "text-layer"));
+                }
             }
             for block in &page.blocks {
                 blocks_html.push_str(&HtmlDebugExporter::block_to_div(block, "fused"));
@@ -107,6 +227,8 @@ body {{ margin: 0; font-family: Arial, sans-serif; }}
 .bbox.parser {{ border-color: rgba(0,0,255,0.6); }}
 .bbox.ocr {{ border-color: rgba(255,0,0,0.6); }}
 .bbox.fused {{ border-color: rgba(0,128,0,0.6); }}
+.bbox.text-layer {{ border-color: rgba(255,140,0,0.6); border-style: dotted; }}
+.bbox.dropped {{ border-color: rgba(200,0,0,0.8); border-style: dashed; background: repeating-linear-gradient(45deg, rgba(200,0,0,0.08), rgba(200,0,0,0.08) 6px, transparent 6px, transparent 12px); }}
 .bbox.text {{ background: rgba(100,100,255,0.1); }}
 .bbox.table {{ background: rgba(255,165,0,0.15); border-style: dashed; }}
 .bbox.figure {{ background: rgba(128,0,128,0.1); }}
@@ -115,6 +237,10 @@ body {{ margin: 0; font-family: Arial, sans-serif; }}
 #legend {{ position: fixed; right: 10px; bottom: 10px; background: #fff; padding: 10px; border: 1px solid #ddd; font-size: 12px; }}
 .legend-item {{ margin: 5px 0; }}
 .legend-box {{ display: inline-block; width: 20px; height: 15px; border: 2px solid; vertical-align: middle; margin-right: 5px; }}
+#decisions {{ position: fixed; left: 10px; bottom: 10px; background: #fff; padding: 10px; border: 1px solid #ddd; font-size: 12px; max-width: 60vw; max-height: 40vh; overflow: auto; }}
+#decisions table {{ border-collapse: collapse; }}
+#decisions td, #decisions th {{ border-bottom: 1px solid #eee; padding: 2px 6px; text-align: left; vertical-align: top; }}
+#decisions tr.dropped {{ color: #b00; }}
 </style>
 </head>
 <body>
@@ -124,7 +250,9 @@ body {{ margin: 0; font-family: Arial, sans-serif; }}
 <div class='legend-item'><span class='legend-box' style='background: rgba(255,165,0,0.15); border: 2px dashed rgba(255,0,0,0.6);'></span>Table</div>
 <div class='legend-item'><span class='legend-box' style='background: rgba(128,0,128,0.1); border-color: rgba(255,0,0,0.6);'></span>Figure</div>
 <div class='legend-item'><span class='legend-box' style='background: rgba(0,200,200,0.15); border-color: rgba(255,0,0,0.6);'></span>Math</div>
+<div class='legend-item'><span class='legend-box' style='border: 2px dashed rgba(200,0,0,0.8);'></span>Dropped</div>
 </div>
+{decisions}
 <div id='canvas'>
 <img src='{image}' />
 {blocks}
@@ -133,7 +261,11 @@ body {{ margin: 0; font-family: Arial, sans-serif; }}
 const info = document.getElementById('info');
 for (const el of document.querySelectorAll('.bbox')) {{
   el.addEventListener('click', () => {{
-    info.innerHTML = `type: ${{el.dataset.type}}<br/>provenance: ${{el.dataset.provenance}}<br/>confidence: ${{el.dataset.confidence}}<br/>similarity: ${{el.dataset.similarity}}<br/>parser_text: ${{el.dataset.parserText}}<br/>ocr_text: ${{el.dataset.ocrText}}<br/>final_text: ${{el.dataset.finalText}}`;
+    if (el.dataset.rule) {{
+      info.innerHTML = `dropped by: ${{el.dataset.rule}}<br/>provenance: ${{el.dataset.provenance}}<br/>text: ${{el.dataset.html}}`;
+      return;
+    }}
+    info.innerHTML = `type: ${{el.dataset.type}}<br/>provenance: ${{el.dataset.provenance}}<br/>confidence: ${{el.dataset.confidence}}<br/>text: ${{el.dataset.html}}<br/>similarity: ${{el.dataset.similarity}}<br/>parser_text: ${{el.dataset.parserText}}<br/>ocr_text: ${{el.dataset.ocrText}}<br/>final_text: ${{el.dataset.finalText}}`;
   }});
 }}
 </script>
@@ -141,7 +273,8 @@ for (const el of document.querySelectorAll('.bbox')) {{
 </html>"#,
                 page_idx = page.page_idx + 1,
                 image = image_path,
-                blocks = blocks_html
+                blocks = blocks_html,
+                decisions = decisions_html
             );
             let path = self
                 .out_dir
@@ -151,3 +284,122 @@ for (const el of document.querySelectorAll('.bbox')) {{
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::geometry::BBox;
+    use crate::core::model::{
+        Line, NoteKind, NoteRef, PageClass, PageDebug, PageFinal, Revision, RevisionKind, Span,
+    };
+
+    fn text_block(source: Provenance, spans: Vec<Span>) -> Block {
+        Block::TextBlock {
+            bbox: BBox::new(10.0, 10.0, 200.0, 30.0),
+            lines: vec![Line { spans }],
+            confidence: 0.8,
+            source,
+            role: None,
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        }
+    }
+
+    fn span(text: &str, link: Option<LinkTarget>, revision: Option<Revision>) -> Span {
+        Span {
+            text: text.to_string(),
+            bbox: BBox::new(10.0, 10.0, 200.0, 30.0),
+            source: Provenance::Parser,
+            style: None,
+            link,
+            revision,
+        }
+    }
+
+    #[test]
+    fn exports_decisions_and_dropped_blocks() -> Result<()> {
+        let kept = text_block(Provenance::Fused, vec![span("Kept text", None, None)]);
+        let dropped = text_block(Provenance::Ocr, vec![span("a < b & 'c'", None, None)]);
+        let mut resolved = FusionDecision::about(DecisionAction::Resolved, "similar_text", &kept);
+        resolved.similarity = Some(0.9);
+        let page = PageFinal {
+            page_idx: 0,
+            class: PageClass::Digital,
+            blocks: vec![kept],
+            width: 400,
+            height: 600,
+            geometry: None,
+            debug: Some(PageDebug {
+                parser_blocks: Vec::new(),
+                ocr_blocks: Vec::new(),
+                text_layer_blocks: Vec::new(),
+                decisions: vec![
+                    resolved,
+                    FusionDecision::about(DecisionAction::Dropped, "noisy_ocr", &dropped),
+                ],
+            }),
+        };
+        let out_dir =
+            std::env::temp_dir().join(format!("docstruct-html-debug-test-{}", std::process::id()));
+        HtmlDebugExporter::new(out_dir.clone()).export(&DocumentFinal {
+            pages: vec![page],
+            outline: Vec::new(),
+            metadata: Default::default(),
+        })?;
+        let html = fs::read_to_string(out_dir.join("page_001.html"))?;
+        fs::remove_dir_all(&out_dir)?;
+
+        assert!(html.contains("<summary>Fusion decisions (2)</summary>"));
+        assert!(html.contains(
+            "<tr class='resolved'><td>resolved</td><td>similar_text</td><td>fused</td><td>0.900</td>"
+        ));
+        assert!(html.contains(
+            "<tr class='dropped'><td>dropped</td><td>noisy_ocr</td><td>ocr</td><td></td><td></td><td></td><td>a &lt; b &amp; 'c'</td></tr>"
+        ));
+        // The dropped text is escaped once for display and once more for
+        // the attribute the viewer reads it from.
+        assert!(html.contains(
+            "data-type='dropped' data-provenance='ocr' data-rule='noisy_ocr' data-html='a &amp;lt; b &amp;amp; &#x27;c&#x27;'"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn block_html_marks_links_notes_and_revisions() {
+        let inserted = Revision {
+            kind: RevisionKind::Insertion,
+            author: Some("Kim".to_string()),
+            date: None,
+        };
+        let block = text_block(
+            Provenance::Parser,
+            vec![
+                span(
+                    "site",
+                    Some(LinkTarget::Uri("https://example.com/?a=1&b=2".to_string())),
+                    None,
+                ),
+                span(" see ", None, None),
+                span("page", Some(LinkTarget::Page(2)), None),
+                span(
+                    "1",
+                    Some(LinkTarget::Note(NoteRef {
+                        kind: NoteKind::Footnote,
+                        id: "1".to_string(),
+                    })),
+                    None,
+                ),
+                span(" new", None, Some(inserted)),
+            ],
+        );
+
+        assert_eq!(
+            emphasized_html(&block),
+            "<a href=\"https://example.com/?a=1&amp;b=2\">site</a> see \
+             <a href=\"page_003.html\">page</a>\
+             <sup class=\"note\" title=\"Footnote 1\">1</sup>\
+             <ins title=\"Kim\"> new</ins>"
+        );
+    }
+}
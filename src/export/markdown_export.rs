@@ -4,8 +4,11 @@ use std::path::PathBuf;
 use anyhow::Result;
 use image::{GenericImageView, ImageReader};
 
-use crate::core::model::{Block, DocumentFinal, Provenance};
-use crate::export::Exporter;
+use crate::core::model::{
+    Block, DocumentFinal, Line, LinkTarget, NoteKind, NoteRef, OutlineEntry, Provenance, TableCell,
+    TextRole,
+};
+use crate::export::{emphasis_runs, revision_html, EmphasisRun, Exporter};
 
 #[derive(Debug, Clone)]
 pub struct MarkdownExporter {
@@ -68,29 +71,15 @@ impl MarkdownExporter {
         page_idx: usize,
         block_idx: usize,
         page_image_path: &PathBuf,
+        body_size: Option<f32>,
     ) -> Result<String> {
         match block {
-            Block::TextBlock { lines, source, .. } => {
-                // Extract text from all spans in all lines
-                let text = lines
-                    .iter()
-                    .map(|line| {
-                        line.spans
-                            .iter()
-                            .map(|span| span.text.as_str())
-                            .collect::<Vec<_>>()
-                            .join("")
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                if Self::should_skip_degraded_parser_text(*source, &text) {
-                    return Ok(String::new());
-                }
-                if Self::should_skip_noisy_ocr_text(*source, &text) {
-                    return Ok(String::new());
-                }
-                Ok(text)
-            }
+            Block::TextBlock {
+                lines,
+                source,
+                role,
+                ..
+            } => Ok(Self::format_text(lines, *source, *role, body_size)),
             Block::TableBlock { bbox, .. } => {
                 // Crop table image
                 let img_path =
@@ -150,6 +139,79 @@ impl MarkdownExporter {
         }
     }
 
+    /// Figure backed by an image the parser extracted, referenced as saved
+    /// instead of cropped from the rendered page.
+    fn embedded_figure(block: &Block, block_idx: usize) -> Option<String> {
+        match block {
+            Block::FigureBlock {
+                image: Some(image), ..
+            } => Some(format!(
+                "\n**Figure {}:**\n\n![Figure]({})\n",
+                block_idx + 1,
+                image
+            )),
+            _ => None,
+        }
+    }
+
+    /// Table with a known cell grid, written out as a table instead of a
+    /// cropped image.
+    fn structured_table(block: &Block, block_idx: usize) -> Option<String> {
+        match block {
+            Block::TableBlock { cells, .. } if !cells.is_empty() => Some(format!(
+                "\n**Table {}:**\n\n{}",
+                block_idx + 1,
+                table_markdown(cells)
+            )),
+            _ => None,
+        }
+    }
+
+    /// Markdown for a text block. A tagged role decides headings and list
+    /// items; untagged blocks are headings when their font is clearly larger
+    /// than the body text. Otherwise the lines keep bold/italic markers.
+    fn format_text(
+        lines: &[Line],
+        source: Provenance,
+        role: Option<TextRole>,
+        body_size: Option<f32>,
+    ) -> String {
+        let text = lines.iter().map(Line::text).collect::<Vec<_>>().join("\n");
+        if Self::should_skip_degraded_parser_text(source, &text)
+            || Self::should_skip_noisy_ocr_text(source, &text)
+        {
+            return String::new();
+        }
+
+        if role.is_some_and(TextRole::is_note) {
+            return note_definition(lines);
+        }
+        let level = match role {
+            Some(TextRole::Heading(level)) => Some(usize::from(level)),
+            Some(_) => None,
+            None => heading_level(lines, body_size),
+        };
+        if let Some(level) = level {
+            let title = lines
+                .iter()
+                .map(|line| line.text().trim().to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            // Levels 1 and 2 are taken by the document and page titles.
+            return format!("{} {}", "#".repeat((level + 2).min(6)), title);
+        }
+
+        let body = lines
+            .iter()
+            .map(emphasize_markdown)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if role == Some(TextRole::ListItem) {
+            return list_item(&body);
+        }
+        body
+    }
+
     fn should_skip_degraded_parser_text(source: Provenance, text: &str) -> bool {
         if source != Provenance::Parser {
             return false;
@@ -203,14 +265,298 @@ impl MarkdownExporter {
     }
 }
 
+/// Line text with `**bold**` / `*italic*` markers, `[text](target)` links
+/// and `<ins>`/`<del>` around tracked changes. Whitespace is kept outside
+/// the emphasis markers so they stay valid Markdown.
+fn emphasize_markdown(line: &Line) -> String {
+    emphasis_runs(&line.spans)
+        .iter()
+        .map(|run| revision_html(run.revision.as_ref(), markdown_run(run)))
+        .collect()
+}
+
+fn markdown_run(run: &EmphasisRun) -> String {
+    if let Some(LinkTarget::Note(note)) = &run.link {
+        return format!("[^{}]", note_label(note));
+    }
+    let marker = match (run.bold, run.italic) {
+        (true, true) => "***",
+        (true, false) => "**",
+        (false, true) => "*",
+        (false, false) => "",
+    };
+    let core = run.text.trim();
+    if (marker.is_empty() && run.link.is_none()) || core.is_empty() {
+        return run.text.clone();
+    }
+    let lead = &run.text[..run.text.len() - run.text.trim_start().len()];
+    let trail = &run.text[run.text.trim_end().len()..];
+    let emphasized = format!("{marker}{core}{marker}");
+    let linked = match &run.link {
+        Some(LinkTarget::Uri(uri)) => format!("[{emphasized}]({})", uri.replace(' ', "%20")),
+        Some(LinkTarget::Page(page_idx)) => format!("[{emphasized}](#page-{})", page_idx + 1),
+        Some(LinkTarget::Note(_)) | None => emphasized,
+    };
+    format!("{lead}{linked}{trail}")
+}
+
+/// Markdown footnote label of a note. Footnotes keep their id; endnotes
+/// and comments are prefixed so the three never collide.
+fn note_label(note: &NoteRef) -> String {
+    match note.kind {
+        NoteKind::Footnote => note.id.clone(),
+        NoteKind::Endnote => format!("endnote-{}", note.id),
+        NoteKind::Comment => format!("comment-{}", note.id),
+    }
+}
+
+/// A footnote definition, `[^label]: text`, for the note its leading mark
+/// links to. The part of a note continued from an earlier page has no mark
+/// and stays plain text.
+fn note_definition(lines: &[Line]) -> String {
+    let note = lines
+        .iter()
+        .flat_map(|line| &line.spans)
+        .find_map(|span| match &span.link {
+            Some(LinkTarget::Note(note)) => Some(note),
+            _ => None,
+        });
+    let body = lines
+        .iter()
+        .map(|line| {
+            let spans = line
+                .spans
+                .iter()
+                .filter(
+                    |span| !matches!(&span.link, Some(LinkTarget::Note(own)) if Some(own) == note),
+                )
+                .cloned()
+                .collect();
+            emphasize_markdown(&Line { spans }).trim().to_string()
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>();
+    match note {
+        // Continuation lines are indented to stay in the footnote.
+        Some(note) => format!("[^{}]: {}", note_label(note), body.join("\n    ")),
+        None => body.join("\n"),
+    }
+}
+
+/// A list item line: bullets become `- `, numbered labels (`1.`, `a)`)
+/// are kept, and items without a label get `- `.
+fn list_item(text: &str) -> String {
+    let text = text.trim_start();
+    if let Some(rest) = text.strip_prefix(['•', '·', '▪', '‣', '◦', '●', '○', '■', '–', '-', '*'])
+    {
+        return format!("- {}", rest.trim_start());
+    }
+    let label = text.split_whitespace().next().unwrap_or_default();
+    let numbered = label.len() > 1
+        && label.ends_with(['.', ')'])
+        && label[..label.len() - 1]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric());
+    if numbered {
+        text.to_string()
+    } else {
+        format!("- {text}")
+    }
+}
+
+/// A pipe table when every cell covers one grid position and only the
+/// first row can be a header; otherwise an HTML table, which can express
+/// row and column spans. Pipe tables always have a header row, left blank
+/// when the table has none.
+fn table_markdown(cells: &[TableCell]) -> String {
+    let rows = cells
+        .iter()
+        .map(|cell| cell.row + cell.rowspan)
+        .max()
+        .unwrap_or(0);
+    let cols = cells
+        .iter()
+        .map(|cell| cell.col + cell.colspan)
+        .max()
+        .unwrap_or(0);
+    let pipe = cells
+        .iter()
+        .all(|cell| !cell.is_merged() && (!cell.header || cell.row == 0));
+
+    if pipe {
+        let mut grid = vec![vec![String::new(); cols]; rows];
+        for cell in cells {
+            grid[cell.row][cell.col] = cell
+                .lines
+                .iter()
+                .map(|line| emphasize_markdown(line).trim().replace('|', "\\|"))
+                .collect::<Vec<_>>()
+                .join("<br>");
+        }
+        let has_header = cells.iter().any(|cell| cell.header);
+        let body = if has_header {
+            grid.remove(0)
+        } else {
+            vec![String::new(); cols]
+        };
+        let row_text = |row: &[String]| format!("| {} |\n", row.join(" | "));
+        let mut out = row_text(&body);
+        out.push_str(&row_text(&vec!["---".to_string(); cols]));
+        for row in &grid {
+            out.push_str(&row_text(row));
+        }
+        return out;
+    }
+
+    let mut out = String::from("<table>\n");
+    for row in 0..rows {
+        out.push_str("<tr>");
+        for cell in cells.iter().filter(|cell| cell.row == row) {
+            let tag = if cell.header { "th" } else { "td" };
+            out.push('<');
+            out.push_str(tag);
+            if cell.rowspan > 1 {
+                out.push_str(&format!(" rowspan=\"{}\"", cell.rowspan));
+            }
+            if cell.colspan > 1 {
+                out.push_str(&format!(" colspan=\"{}\"", cell.colspan));
+            }
+            out.push('>');
+            let text = cell
+                .lines
+                .iter()
+                .map(|line| {
+                    emphasis_runs(&line.spans)
+                        .iter()
+                        .map(|run| {
+                            let html = html_escape::encode_text(&run.text).into_owned();
+                            revision_html(run.revision.as_ref(), html)
+                        })
+                        .collect::<String>()
+                        .trim()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("<br>");
+            out.push_str(&text);
+            out.push_str(&format!("</{tag}>"));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// `text` with the inline Markdown metacharacters backslash-escaped.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Nested list of outline entries linking to the `## Page N` headings.
+/// Titles are escaped so brackets or emphasis markers in a bookmark cannot
+/// break the link.
+fn table_of_contents(outline: &[OutlineEntry]) -> String {
+    fn push_entries(out: &mut String, entries: &[OutlineEntry], depth: usize) {
+        for entry in entries {
+            let indent = "  ".repeat(depth);
+            let title = escape_markdown(&entry.title);
+            match entry.page_idx {
+                Some(page_idx) => {
+                    out.push_str(&format!("{indent}- [{title}](#page-{})\n", page_idx + 1))
+                }
+                None => out.push_str(&format!("{indent}- {title}\n")),
+            }
+            push_entries(out, &entry.children, depth + 1);
+        }
+    }
+
+    let mut out = String::new();
+    push_entries(&mut out, outline, 0);
+    out
+}
+
+/// Most common span font size in the document, weighted by characters.
+fn body_font_size(document: &DocumentFinal) -> Option<f32> {
+    let mut counts: Vec<(f32, usize)> = Vec::new();
+    for page in &document.pages {
+        for block in &page.blocks {
+            let Block::TextBlock { lines, .. } = block else {
+                continue;
+            };
+            for span in lines.iter().flat_map(|line| line.spans.iter()) {
+                let Some(size) = span.style.as_ref().and_then(|style| style.size) else {
+                    continue;
+                };
+                let size = (size * 2.0).round() / 2.0;
+                let chars = span.text.trim().chars().count();
+                match counts.iter_mut().find(|(known, _)| *known == size) {
+                    Some((_, total)) => *total += chars,
+                    None => counts.push((size, chars)),
+                }
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|(size, chars)| *size > 0.0 && *chars > 0)
+        .max_by_key(|(_, chars)| *chars)
+        .map(|(size, _)| size)
+}
+
+/// Heading level (1-3) hinted by a short block set in a font noticeably
+/// larger than the body text.
+fn heading_level(lines: &[Line], body_size: Option<f32>) -> Option<usize> {
+    let body_size = body_size?;
+    let spans: Vec<_> = lines
+        .iter()
+        .flat_map(|line| line.spans.iter())
+        .filter(|span| !span.text.trim().is_empty())
+        .collect();
+    let text_len: usize = spans.iter().map(|span| span.text.chars().count()).sum();
+    if spans.is_empty() || lines.len() > 3 || text_len > 200 {
+        return None;
+    }
+
+    let min_size = spans
+        .iter()
+        .map(|span| span.style.as_ref().and_then(|style| style.size))
+        .collect::<Option<Vec<f32>>>()?
+        .into_iter()
+        .fold(f32::MAX, f32::min);
+    let ratio = min_size / body_size;
+    if ratio >= 1.6 {
+        Some(1)
+    } else if ratio >= 1.3 {
+        Some(2)
+    } else if ratio >= 1.15 {
+        Some(3)
+    } else {
+        None
+    }
+}
+
 impl Exporter for MarkdownExporter {
     fn export(&self, document: &DocumentFinal) -> Result<()> {
         fs::create_dir_all(&self.out_dir)?;
         fs::create_dir_all(&self.image_dir)?;
 
+        let body_size = body_font_size(document);
+
         // Export full document as markdown
         let mut markdown = String::new();
         markdown.push_str("# Document\n\n");
+        if !document.outline.is_empty() {
+            markdown.push_str("## Contents\n\n");
+            markdown.push_str(&table_of_contents(&document.outline));
+            markdown.push('\n');
+        }
 
         for page in &document.pages {
             markdown.push_str(&format!("---\n\n## Page {}\n\n", page.page_idx + 1));
@@ -226,31 +572,21 @@ impl Exporter for MarkdownExporter {
             let has_debug_image = page_image_path.exists();
 
             for (block_idx, block) in page.blocks.iter().enumerate() {
-                let block_text = if has_debug_image {
-                    self.format_block(block, page.page_idx, block_idx, &page_image_path)?
+                let block_text = if let Some(text) = Self::embedded_figure(block, block_idx)
+                    .or_else(|| Self::structured_table(block, block_idx))
+                {
+                    text
+                } else if has_debug_image {
+                    self.format_block(block, page.page_idx, block_idx, &page_image_path, body_size)?
                 } else {
                     // Fallback to simple text representation
                     match block {
-                        Block::TextBlock { lines, source, .. } => {
-                            let text = lines
-                                .iter()
-                                .map(|line| {
-                                    line.spans
-                                        .iter()
-                                        .map(|span| span.text.as_str())
-                                        .collect::<Vec<_>>()
-                                        .join("")
-                                })
-                                .collect::<Vec<_>>()
-                                .join("\n");
-                            if Self::should_skip_degraded_parser_text(*source, &text) {
-                                String::new()
-                            } else if Self::should_skip_noisy_ocr_text(*source, &text) {
-                                String::new()
-                            } else {
-                                text
-                            }
-                        }
+                        Block::TextBlock {
+                            lines,
+                            source,
+                            role,
+                            ..
+                        } => Self::format_text(lines, *source, *role, body_size),
                         Block::TableBlock { bbox, .. } => {
                             format!(
                                 "\n[TABLE: {:.0}x{:.0} at ({:.0}, {:.0})]\n",
@@ -318,30 +654,20 @@ impl Exporter for MarkdownExporter {
             let has_debug_image = page_image_path.exists();
 
             for (block_idx, block) in page.blocks.iter().enumerate() {
-                let block_text = if has_debug_image {
-                    self.format_block(block, page.page_idx, block_idx, &page_image_path)?
+                let block_text = if let Some(text) = Self::embedded_figure(block, block_idx)
+                    .or_else(|| Self::structured_table(block, block_idx))
+                {
+                    text
+                } else if has_debug_image {
+                    self.format_block(block, page.page_idx, block_idx, &page_image_path, body_size)?
                 } else {
                     match block {
-                        Block::TextBlock { lines, source, .. } => {
-                            let text = lines
-                                .iter()
-                                .map(|line| {
-                                    line.spans
-                                        .iter()
-                                        .map(|span| span.text.as_str())
-                                        .collect::<Vec<_>>()
-                                        .join("")
-                                })
-                                .collect::<Vec<_>>()
-                                .join("\n");
-                            if Self::should_skip_degraded_parser_text(*source, &text) {
-                                String::new()
-                            } else if Self::should_skip_noisy_ocr_text(*source, &text) {
-                                String::new()
-                            } else {
-                                text
-                            }
-                        }
+                        Block::TextBlock {
+                            lines,
+                            source,
+                            role,
+                            ..
+                        } => Self::format_text(lines, *source, *role, body_size),
                         Block::TableBlock { bbox, .. } => {
                             format!("\n[TABLE: {:.0}x{:.0}]\n", bbox.width(), bbox.height())
                         }
@@ -377,3 +703,243 @@ impl Exporter for MarkdownExporter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::geometry::BBox;
+    use crate::core::model::{Revision, RevisionKind, Span, TextStyle};
+
+    fn cell(row: usize, col: usize, rowspan: usize, colspan: usize, text: &str) -> TableCell {
+        TableCell {
+            row,
+            col,
+            rowspan,
+            colspan,
+            bbox: BBox::new(0.0, 0.0, 10.0, 10.0),
+            lines: vec![Line {
+                spans: vec![span(text, 10.0, false, false)],
+            }],
+            header: row == 0,
+        }
+    }
+
+    fn span(text: &str, size: f32, bold: bool, italic: bool) -> Span {
+        Span {
+            text: text.to_string(),
+            bbox: BBox::new(0.0, 0.0, 10.0, 10.0),
+            source: Provenance::Parser,
+            style: Some(TextStyle {
+                font: None,
+                size: Some(size),
+                bold,
+                italic,
+            }),
+            link: None,
+            revision: None,
+        }
+    }
+
+    #[test]
+    fn emphasis_markers_wrap_trimmed_runs() {
+        let line = Line {
+            spans: vec![
+                span("Plain ", 10.0, false, false),
+                span("bold ", 10.0, true, false),
+                span("words ", 10.0, true, false),
+                span("and italic", 10.0, false, true),
+            ],
+        };
+        assert_eq!(
+            emphasize_markdown(&line),
+            "Plain **bold words** *and italic*"
+        );
+    }
+
+    #[test]
+    fn larger_font_hints_heading_level() {
+        let heading = vec![Line {
+            spans: vec![span("1 Introduction", 17.0, true, false)],
+        }];
+        let body = vec![Line {
+            spans: vec![span("Body text", 10.0, false, false)],
+        }];
+        assert_eq!(heading_level(&heading, Some(10.0)), Some(1));
+        assert_eq!(heading_level(&body, Some(10.0)), None);
+        assert_eq!(heading_level(&heading, None), None);
+        assert_eq!(
+            MarkdownExporter::format_text(&heading, Provenance::Parser, None, Some(12.0)),
+            "#### 1 Introduction"
+        );
+    }
+
+    #[test]
+    fn linked_spans_become_markdown_links() {
+        let mut cited = span("[12]", 10.0, false, false);
+        cited.link = Some(LinkTarget::Page(4));
+        let mut site = span("korea.kr ", 10.0, true, false);
+        site.link = Some(LinkTarget::Uri("https://www.korea.kr".to_string()));
+        let line = Line {
+            spans: vec![span("See ", 10.0, false, false), site, cited],
+        };
+
+        assert_eq!(
+            emphasize_markdown(&line),
+            "See [**korea.kr**](https://www.korea.kr) [[12]](#page-5)"
+        );
+    }
+
+    #[test]
+    fn notes_become_markdown_footnotes() {
+        let note = |kind, id: &str| {
+            Some(LinkTarget::Note(NoteRef {
+                kind,
+                id: id.to_string(),
+            }))
+        };
+        let mut mark = span("1", 8.0, false, false);
+        mark.link = note(NoteKind::Footnote, "1");
+        let mut comment = span("", 10.0, false, false);
+        comment.link = note(NoteKind::Comment, "0");
+        let body = Line {
+            spans: vec![
+                span("Governed by law", 10.0, false, false),
+                mark.clone(),
+                comment,
+            ],
+        };
+        assert_eq!(emphasize_markdown(&body), "Governed by law[^1][^comment-0]");
+
+        let footnote = vec![
+            Line {
+                spans: vec![
+                    mark,
+                    span(" See the ", 8.0, false, false),
+                    span("Act", 8.0, false, true),
+                ],
+            },
+            Line {
+                spans: vec![span("as amended.", 8.0, false, false)],
+            },
+        ];
+        assert_eq!(
+            MarkdownExporter::format_text(
+                &footnote,
+                Provenance::Parser,
+                Some(TextRole::Footnote),
+                Some(10.0)
+            ),
+            "[^1]: See the *Act*\n    as amended."
+        );
+    }
+
+    #[test]
+    fn tracked_changes_become_ins_and_del() {
+        let change = |kind, author: &str| {
+            Some(Revision {
+                kind,
+                author: Some(author.to_string()),
+                date: Some("2024-03-01".to_string()),
+            })
+        };
+        let mut deleted = span("30", 10.0, false, false);
+        deleted.revision = change(RevisionKind::Deletion, "Kim");
+        let mut inserted = span("45", 10.0, true, false);
+        inserted.revision = change(RevisionKind::Insertion, "O'Neil & Co");
+        let line = Line {
+            spans: vec![
+                span("Within ", 10.0, false, false),
+                deleted,
+                inserted,
+                span(" days", 10.0, false, false),
+            ],
+        };
+
+        assert_eq!(
+            emphasize_markdown(&line),
+            "Within <del datetime=\"2024-03-01\" title=\"Kim\">30</del>\
+             <ins datetime=\"2024-03-01\" title=\"O'Neil &amp; Co\">**45**</ins> days"
+        );
+    }
+
+    #[test]
+    fn table_of_contents_nests_and_links_pages() {
+        let mut chapter = OutlineEntry::new("Ⅰ. 개요", 1, Some(0));
+        chapter
+            .children
+            .push(OutlineEntry::new("1. 추진 배경", 2, Some(2)));
+        let outline = vec![chapter, OutlineEntry::new("Notes", 1, None)];
+
+        assert_eq!(
+            table_of_contents(&outline),
+            "- [Ⅰ. 개요](#page-1)\n  - [1. 추진 배경](#page-3)\n- Notes\n"
+        );
+
+        let odd = vec![OutlineEntry::new("[Draft] *v2* <final>", 1, Some(1))];
+        assert_eq!(
+            table_of_contents(&odd),
+            "- [\\[Draft\\] \\*v2\\* \\<final\\>](#page-2)\n"
+        );
+    }
+
+    #[test]
+    fn simple_tables_become_pipe_tables() {
+        let cells = vec![
+            cell(0, 0, 1, 1, "Item"),
+            cell(0, 1, 1, 1, "A|B"),
+            cell(1, 0, 1, 1, "x"),
+            cell(1, 1, 1, 1, "1"),
+        ];
+        assert_eq!(
+            table_markdown(&cells),
+            "| Item | A\\|B |\n| --- | --- |\n| x | 1 |\n"
+        );
+
+        let mut headless = cells.clone();
+        headless.iter_mut().for_each(|cell| cell.header = false);
+        assert!(table_markdown(&headless).starts_with("|  |  |\n| --- | --- |\n| Item |"));
+    }
+
+    #[test]
+    fn merged_cells_fall_back_to_html() {
+        let cells = vec![
+            cell(0, 0, 1, 2, "Q1 & Q2"),
+            cell(1, 0, 1, 1, "3"),
+            cell(1, 1, 1, 1, "4"),
+        ];
+        assert_eq!(
+            table_markdown(&cells),
+            "<table>\n<tr><th colspan=\"2\">Q1 &amp; Q2</th></tr>\n<tr><td>3</td><td>4</td></tr>\n</table>\n"
+        );
+    }
+
+    #[test]
+    fn tagged_roles_override_font_heuristics() {
+        let large = vec![Line {
+            spans: vec![span("Summary", 17.0, true, false)],
+        }];
+        let body = vec![Line {
+            spans: vec![span("Scope", 10.0, false, false)],
+        }];
+        let item = |text: &str| {
+            vec![Line {
+                spans: vec![span(text, 10.0, false, false)],
+            }]
+        };
+        let format = |lines: &[Line], role| {
+            MarkdownExporter::format_text(lines, Provenance::Parser, role, Some(10.0))
+        };
+
+        assert_eq!(format(&large, Some(TextRole::Paragraph)), "**Summary**");
+        assert_eq!(format(&body, Some(TextRole::Heading(2))), "#### Scope");
+        assert_eq!(
+            format(&item("• First"), Some(TextRole::ListItem)),
+            "- First"
+        );
+        assert_eq!(
+            format(&item("2. Second"), Some(TextRole::ListItem)),
+            "2. Second"
+        );
+        assert_eq!(format(&item("Third"), Some(TextRole::ListItem)), "- Third");
+    }
+}
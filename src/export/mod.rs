@@ -5,7 +5,7 @@ pub mod text_export;
 
 use anyhow::Result;
 
-use crate::core::model::DocumentFinal;
+use crate::core::model::{Block, DocumentFinal, Line, LinkTarget, Revision, RevisionKind, Span};
 
 pub use html_debug_export::HtmlDebugExporter;
 pub use json_export::JsonExporter;
@@ -15,3 +15,218 @@ pub use text_export::TextExporter;
 pub trait Exporter {
     fn export(&self, document: &DocumentFinal) -> Result<()>;
 }
+
+/// Which optional parts of a document every exporter writes.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Footnotes, endnotes and comments, and the marks referencing them.
+    pub notes: bool,
+    /// Running page headers and footers.
+    pub headers_footers: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            notes: true,
+            headers_footers: true,
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn with_notes(mut self, notes: bool) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    pub fn with_headers_footers(mut self, headers_footers: bool) -> Self {
+        self.headers_footers = headers_footers;
+        self
+    }
+
+    /// The document without the parts these options leave out.
+    pub fn apply(&self, document: &DocumentFinal) -> DocumentFinal {
+        let mut document = document.clone();
+        for page in &mut document.pages {
+            page.blocks.retain(|block| match block {
+                Block::TextBlock {
+                    role: Some(role), ..
+                } => {
+                    (self.notes || !role.is_note())
+                        && (self.headers_footers || !role.is_header_or_footer())
+                }
+                _ => true,
+            });
+            if self.notes {
+                continue;
+            }
+            for block in &mut page.blocks {
+                match block {
+                    Block::TextBlock { lines, .. } => drop_note_marks(lines),
+                    Block::TableBlock { cells, .. } => {
+                        for cell in cells {
+                            drop_note_marks(&mut cell.lines);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        document
+    }
+}
+
+fn drop_note_marks(lines: &mut [Line]) {
+    for line in lines {
+        line.spans
+            .retain(|span| !matches!(span.link, Some(LinkTarget::Note(_))));
+    }
+}
+
+/// A stretch of text with uniform emphasis, link and tracked change, merged
+/// from adjacent spans.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EmphasisRun {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub link: Option<LinkTarget>,
+    pub revision: Option<Revision>,
+}
+
+/// Merge adjacent spans that share bold/italic, link and tracked change so
+/// exporters emit one marker pair (and one link) per stretch instead of one
+/// per word.
+pub(crate) fn emphasis_runs(spans: &[Span]) -> Vec<EmphasisRun> {
+    let mut runs: Vec<EmphasisRun> = Vec::new();
+    for span in spans {
+        let (bold, italic) = span
+            .style
+            .as_ref()
+            .map(|style| (style.bold, style.italic))
+            .unwrap_or((false, false));
+        match runs.last_mut() {
+            Some(last)
+                if last.bold == bold
+                    && last.italic == italic
+                    && last.link == span.link
+                    && last.revision == span.revision =>
+            {
+                last.text.push_str(&span.text)
+            }
+            _ => runs.push(EmphasisRun {
+                text: span.text.clone(),
+                bold,
+                italic,
+                link: span.link.clone(),
+                revision: span.revision.clone(),
+            }),
+        }
+    }
+    runs
+}
+
+/// `html` wrapped in `<ins>` or `<del>` when it is part of a tracked change,
+/// with the change's date as `datetime` and its author as `title`.
+pub(crate) fn revision_html(revision: Option<&Revision>, html: String) -> String {
+    let Some(revision) = revision else {
+        return html;
+    };
+    let tag = match revision.kind {
+        RevisionKind::Insertion => "ins",
+        RevisionKind::Deletion => "del",
+    };
+    let mut open = format!("<{tag}");
+    if let Some(date) = &revision.date {
+        open.push_str(&format!(
+            " datetime=\"{}\"",
+            html_escape::encode_double_quoted_attribute(date)
+        ));
+    }
+    if let Some(author) = &revision.author {
+        open.push_str(&format!(
+            " title=\"{}\"",
+            html_escape::encode_double_quoted_attribute(author)
+        ));
+    }
+    format!("{open}>{html}</{tag}>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::geometry::BBox;
+    use crate::core::model::{NoteKind, NoteRef, PageClass, PageFinal, Provenance, TextRole};
+
+    fn text_block(spans: Vec<Span>, role: TextRole) -> Block {
+        Block::TextBlock {
+            bbox: BBox::new(0.0, 0.0, 100.0, 10.0),
+            lines: vec![Line { spans }],
+            confidence: 0.6,
+            source: Provenance::Parser,
+            role: Some(role),
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        }
+    }
+
+    #[test]
+    fn options_drop_notes_with_their_marks() {
+        let span = |text: &str, link: Option<LinkTarget>| Span {
+            text: text.to_string(),
+            bbox: BBox::new(0.0, 0.0, 10.0, 10.0),
+            source: Provenance::Parser,
+            style: None,
+            link,
+            revision: None,
+        };
+        let mark = Some(LinkTarget::Note(NoteRef {
+            kind: NoteKind::Footnote,
+            id: "1".to_string(),
+        }));
+        let document = DocumentFinal {
+            pages: vec![PageFinal {
+                page_idx: 0,
+                class: PageClass::Digital,
+                blocks: vec![
+                    text_block(vec![span("Header", None)], TextRole::PageHeader),
+                    text_block(
+                        vec![span("Clause", None), span("1", mark.clone())],
+                        TextRole::Paragraph,
+                    ),
+                    text_block(
+                        vec![span("1", mark), span(" Note", None)],
+                        TextRole::Footnote,
+                    ),
+                ],
+                width: 100,
+                height: 100,
+                geometry: None,
+                debug: None,
+            }],
+            outline: Vec::new(),
+            metadata: Default::default(),
+        };
+
+        let kept = ExportOptions::default().apply(&document);
+        assert_eq!(kept.pages[0].blocks.len(), 3);
+
+        let stripped = ExportOptions::default().with_notes(false).apply(&document);
+        let texts: Vec<Option<String>> = stripped.pages[0]
+            .blocks
+            .iter()
+            .map(Block::text_content)
+            .collect();
+        assert_eq!(
+            texts,
+            vec![Some("Header".to_string()), Some("Clause".to_string())]
+        );
+
+        let bare = ExportOptions::default()
+            .with_headers_footers(false)
+            .apply(&document);
+        assert_eq!(bare.pages[0].blocks.len(), 2);
+    }
+}
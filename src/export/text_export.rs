@@ -3,8 +3,21 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 
-use crate::core::model::{Block, DocumentFinal, Provenance};
-use crate::export::Exporter;
+use crate::core::model::{Block, DocumentFinal, Line, Provenance, RevisionKind, TableCell};
+use crate::export::{emphasis_runs, Exporter};
+
+/// Line text with tracked changes marked the way `wdiff` does:
+/// `{+inserted+}` and `[-deleted-]`.
+fn marked_text(line: &Line) -> String {
+    emphasis_runs(&line.spans)
+        .into_iter()
+        .map(|run| match run.revision.map(|revision| revision.kind) {
+            Some(RevisionKind::Insertion) => format!("{{+{}+}}", run.text),
+            Some(RevisionKind::Deletion) => format!("[-{}-]", run.text),
+            None => run.text,
+        })
+        .collect()
+}
 
 #[derive(Debug, Clone)]
 pub struct TextExporter {
@@ -19,18 +32,7 @@ impl TextExporter {
     fn format_block(block: &Block) -> String {
         match block {
             Block::TextBlock { lines, source, .. } => {
-                // Extract text from all spans in all lines
-                let text = lines
-                    .iter()
-                    .map(|line| {
-                        line.spans
-                            .iter()
-                            .map(|span| span.text.as_str())
-                            .collect::<Vec<_>>()
-                            .join("")
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
+                let text = lines.iter().map(marked_text).collect::<Vec<_>>().join("\n");
                 if Self::should_skip_degraded_parser_text(*source, &text) {
                     return String::new();
                 }
@@ -39,6 +41,7 @@ impl TextExporter {
                 }
                 text
             }
+            Block::TableBlock { cells, .. } if !cells.is_empty() => Self::format_table(cells),
             Block::TableBlock { bbox, .. } => {
                 format!(
                     "[TABLE at x:{:.0} y:{:.0} w:{:.0} h:{:.0}]",
@@ -69,6 +72,26 @@ impl TextExporter {
         }
     }
 
+    /// One line per table row with cells separated by tabs. A merged cell's
+    /// text sits at its top-left position; the positions it covers are empty.
+    fn format_table(cells: &[TableCell]) -> String {
+        let rows = cells.iter().map(|cell| cell.row + cell.rowspan).max();
+        let cols = cells.iter().map(|cell| cell.col + cell.colspan).max();
+        let mut grid = vec![vec![String::new(); cols.unwrap_or(0)]; rows.unwrap_or(0)];
+        for cell in cells {
+            grid[cell.row][cell.col] = cell
+                .lines
+                .iter()
+                .map(|line| marked_text(line).trim().to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        grid.iter()
+            .map(|row| row.join("\t"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     fn should_skip_degraded_parser_text(source: Provenance, text: &str) -> bool {
         if source != Provenance::Parser {
             return false;
@@ -137,7 +160,7 @@ impl Exporter for TextExporter {
                     full_text.push_str("\n\n");
                 }
             }
-            full_text.push_str("\n");
+            full_text.push('\n');
         }
 
         let full_path = self.out_dir.join("document.txt");
@@ -163,3 +186,49 @@ impl Exporter for TextExporter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::geometry::BBox;
+    use crate::core::model::{Line, Span};
+
+    fn cell(row: usize, col: usize, colspan: usize, text: &str) -> TableCell {
+        TableCell {
+            row,
+            col,
+            rowspan: 1,
+            colspan,
+            bbox: BBox::new(0.0, 0.0, 10.0, 10.0),
+            lines: vec![Line {
+                spans: vec![Span {
+                    text: text.to_string(),
+                    bbox: BBox::new(0.0, 0.0, 10.0, 10.0),
+                    source: Provenance::Parser,
+                    style: None,
+                    link: None,
+                    revision: None,
+                }],
+            }],
+            header: row == 0,
+        }
+    }
+
+    #[test]
+    fn tables_print_one_row_per_line() {
+        let table = Block::TableBlock {
+            bbox: BBox::new(0.0, 0.0, 100.0, 40.0),
+            confidence: 0.6,
+            source: Provenance::Parser,
+            cells: vec![
+                cell(0, 0, 2, "Region"),
+                cell(1, 0, 1, "Seoul"),
+                cell(1, 1, 1, "9.4"),
+            ],
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        assert_eq!(TextExporter::format_block(&table), "Region\t\nSeoul\t9.4");
+    }
+}
@@ -1,5 +1,16 @@
+use crate::core::geometry::BBox;
 use crate::core::model::Block;
 
+/// Share of the smaller text block's area that must lie inside the larger
+/// one for the two to be grouped (a line inside its paragraph).
+const GROUP_COVERAGE: f32 = 0.6;
+
+/// Blocks with centers closer than this many points may pair without
+/// overlapping; a pair's score drops by its center distance over
+/// [`DISTANCE_PENALTY`] points.
+const PAIR_MAX_DISTANCE: f32 = 54.0;
+const DISTANCE_PENALTY: f32 = 3600.0;
+
 #[derive(Debug, Clone)]
 pub struct MatchedPair {
     pub a: Block,
@@ -8,67 +19,381 @@ pub struct MatchedPair {
     pub center_distance: f32,
 }
 
+/// Text blocks that cover the same content at different granularity: one
+/// block on one side and several on the other, such as a parser paragraph
+/// and the OCR lines inside it.
+#[derive(Debug, Clone)]
+pub struct MatchedGroup {
+    pub a: Vec<Block>,
+    pub b: Vec<Block>,
+}
+
 #[derive(Debug, Clone)]
 pub struct AlignmentResult {
     pub matched: Vec<MatchedPair>,
+    pub groups: Vec<MatchedGroup>,
     pub unmatched_a: Vec<Block>,
     pub unmatched_b: Vec<Block>,
 }
 
-pub fn align_blocks(a_blocks: &[Block], b_blocks: &[Block]) -> AlignmentResult {
+/// Blocks that the tracks agree cover the same content. A track can have
+/// several blocks in a cluster when it splits that content more finely.
+#[derive(Debug, Clone, Default)]
+pub struct Cluster {
+    pub members: Vec<ClusterMember>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClusterMember {
+    /// Index of the track the block came from.
+    pub track: usize,
+    pub block: Block,
+}
+
+impl Cluster {
+    /// The blocks a track contributes to this cluster.
+    pub fn blocks_of(&self, track: usize) -> Vec<Block> {
+        self.members
+            .iter()
+            .filter(|member| member.track == track)
+            .map(|member| member.block.clone())
+            .collect()
+    }
+
+    /// What later tracks are aligned against: the earliest track's blocks,
+    /// merged.
+    pub fn anchor(&self) -> Block {
+        merge_group(&self.blocks_of(self.members[0].track))
+    }
+}
+
+/// Align two block lists whose boxes are pixels of a page rendered at `dpi`.
+pub fn align_blocks(a_blocks: &[Block], b_blocks: &[Block], dpi: u32) -> AlignmentResult {
     let mut matched = Vec::new();
-    let mut unmatched_a = Vec::new();
+    let mut groups = Vec::new();
+    let mut used_a = vec![false; a_blocks.len()];
     let mut used_b = vec![false; b_blocks.len()];
 
-    for a in a_blocks {
-        let mut best_idx = None;
-        let mut best_score = 0.0;
-        for (idx, b) in b_blocks.iter().enumerate() {
-            if used_b[idx] {
-                continue;
+    for (a_idxs, b_idxs) in group_blocks(a_blocks, b_blocks, dpi) {
+        for &idx in &a_idxs {
+            used_a[idx] = true;
+        }
+        for &idx in &b_idxs {
+            used_b[idx] = true;
+        }
+        if let ([a], [b]) = (a_idxs.as_slice(), b_idxs.as_slice()) {
+            let (a, b) = (&a_blocks[*a], &b_blocks[*b]);
+            matched.push(MatchedPair {
+                a: a.clone(),
+                b: b.clone(),
+                iou: a.bbox().iou(&b.bbox()),
+                center_distance: a.bbox().center_distance(&b.bbox()),
+            });
+        } else {
+            groups.push(MatchedGroup {
+                a: a_idxs.iter().map(|&idx| a_blocks[idx].clone()).collect(),
+                b: b_idxs.iter().map(|&idx| b_blocks[idx].clone()).collect(),
+            });
+        }
+    }
+
+    let unused = |blocks: &[Block], used: &[bool]| -> Vec<Block> {
+        blocks
+            .iter()
+            .zip(used)
+            .filter(|(_, used)| !**used)
+            .map(|(block, _)| block.clone())
+            .collect()
+    };
+    AlignmentResult {
+        matched,
+        groups,
+        unmatched_a: unused(a_blocks, &used_a),
+        unmatched_b: unused(b_blocks, &used_b),
+    }
+}
+
+/// Cluster the blocks of several tracks, given in alignment order. Each
+/// track's blocks are aligned against the clusters formed so far; blocks
+/// that find no partner open clusters of their own, and a block grouped
+/// with several clusters joins them into one. Boxes are pixels of a page
+/// rendered at `dpi`.
+pub fn align_tracks(tracks: &[&[Block]], dpi: u32) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for (track, blocks) in tracks.iter().enumerate() {
+        let anchors: Vec<Block> = clusters.iter().map(Cluster::anchor).collect();
+        let mut used = vec![false; blocks.len()];
+        for (cluster_idxs, block_idxs) in group_blocks(&anchors, blocks, dpi) {
+            let target = cluster_idxs[0];
+            for &other in &cluster_idxs[1..] {
+                let members = std::mem::take(&mut clusters[other].members);
+                clusters[target].members.extend(members);
             }
-            let iou = a.bbox().iou(&b.bbox());
-            let dist = a.bbox().center_distance(&b.bbox());
-            let kind_bonus = if a.kind() == b.kind() { 0.1 } else { 0.0 };
-            let score = iou + kind_bonus - dist / 10000.0;
-            if score > best_score {
-                best_score = score;
-                best_idx = Some(idx);
+            for idx in block_idxs {
+                used[idx] = true;
+                clusters[target].members.push(ClusterMember {
+                    track,
+                    block: blocks[idx].clone(),
+                });
             }
         }
+        clusters.retain(|cluster| !cluster.members.is_empty());
+        for (block, _) in blocks.iter().zip(used).filter(|(_, used)| !used) {
+            clusters.push(Cluster {
+                members: vec![ClusterMember {
+                    track,
+                    block: block.clone(),
+                }],
+            });
+        }
+    }
+    clusters
+}
 
-        if let Some(idx) = best_idx {
-            let b = b_blocks[idx].clone();
-            let iou = a.bbox().iou(&b.bbox());
-            let dist = a.bbox().center_distance(&b.bbox());
-            if iou > 0.1 || dist < 150.0 {
-                used_b[idx] = true;
-                matched.push(MatchedPair {
-                    a: a.clone(),
-                    b,
-                    iou,
-                    center_distance: dist,
-                });
-            } else {
-                unmatched_a.push(a.clone());
+/// One block standing for several from the same track. Text blocks merge
+/// into one with the union of their boxes and all their lines, top to
+/// bottom; otherwise the first block stands for the group.
+pub fn merge_group(blocks: &[Block]) -> Block {
+    let texts: Vec<&Block> = blocks
+        .iter()
+        .filter(|block| matches!(block, Block::TextBlock { .. }))
+        .collect();
+    if blocks.len() < 2 || texts.len() != blocks.len() {
+        return blocks[0].clone();
+    }
+
+    let mut texts = texts;
+    texts.sort_by(|a, b| {
+        let (a, b) = (a.bbox(), b.bbox());
+        a.y0.total_cmp(&b.y0).then(a.x0.total_cmp(&b.x0))
+    });
+    let mut merged = texts[0].clone();
+    if let Block::TextBlock {
+        bbox,
+        lines,
+        confidence,
+        ..
+    } = &mut merged
+    {
+        for block in &texts[1..] {
+            if let Block::TextBlock {
+                bbox: other,
+                lines: more,
+                ..
+            } = block
+            {
+                *bbox = bbox.union(other);
+                lines.extend(more.iter().cloned());
             }
-        } else {
-            unmatched_a.push(a.clone());
         }
+        *confidence =
+            texts.iter().map(|block| block.confidence()).sum::<f32>() / texts.len() as f32;
+    }
+    merged
+}
+
+/// Global alignment of two block lists. Pairs come from an optimal
+/// assignment (Hungarian method) maximising the summed pair scores, so one
+/// early block cannot take the partner that fits a later block better.
+/// Text blocks left over then join a paired or unpaired text block that
+/// mostly contains them, giving one-to-many and many-to-one groups. Each
+/// result lists `a_blocks` and `b_blocks` indices; one side always has a
+/// single block.
+fn group_blocks(a_blocks: &[Block], b_blocks: &[Block], dpi: u32) -> Vec<(Vec<usize>, Vec<usize>)> {
+    if a_blocks.is_empty() || b_blocks.is_empty() {
+        return Vec::new();
     }
 
-    let mut unmatched_b = Vec::new();
-    for (idx, b) in b_blocks.iter().enumerate() {
-        if !used_b[idx] {
-            unmatched_b.push(b.clone());
+    let scores: Vec<Vec<Option<f32>>> = a_blocks
+        .iter()
+        .map(|a| b_blocks.iter().map(|b| pair_score(a, b, dpi)).collect())
+        .collect();
+    let gain = |i: usize, j: usize| f64::from(scores[i][j].unwrap_or(0.0));
+    let pairs: Vec<(usize, usize)> = if a_blocks.len() <= b_blocks.len() {
+        let cost: Vec<Vec<f64>> = (0..a_blocks.len())
+            .map(|i| (0..b_blocks.len()).map(|j| -gain(i, j)).collect())
+            .collect();
+        hungarian(&cost).into_iter().enumerate().collect()
+    } else {
+        let cost: Vec<Vec<f64>> = (0..b_blocks.len())
+            .map(|j| (0..a_blocks.len()).map(|i| -gain(i, j)).collect())
+            .collect();
+        hungarian(&cost)
+            .into_iter()
+            .enumerate()
+            .map(|(j, i)| (i, j))
+            .collect()
+    };
+
+    let mut groups: Vec<(Vec<usize>, Vec<usize>)> = Vec::new();
+    let mut a_group: Vec<Option<usize>> = vec![None; a_blocks.len()];
+    let mut b_group: Vec<Option<usize>> = vec![None; b_blocks.len()];
+    for (i, j) in pairs {
+        if scores[i][j].is_some() {
+            a_group[i] = Some(groups.len());
+            b_group[j] = Some(groups.len());
+            groups.push((vec![i], vec![j]));
         }
     }
 
-    AlignmentResult {
-        matched,
-        unmatched_a,
-        unmatched_b,
+    for j in 0..b_blocks.len() {
+        if b_group[j].is_some() {
+            continue;
+        }
+        let Some(i) = container(&b_blocks[j], a_blocks) else {
+            continue;
+        };
+        match a_group[i] {
+            Some(g) if groups[g].0.len() == 1 => {
+                groups[g].1.push(j);
+                b_group[j] = Some(g);
+            }
+            Some(_) => {}
+            None => {
+                a_group[i] = Some(groups.len());
+                b_group[j] = Some(groups.len());
+                groups.push((vec![i], vec![j]));
+            }
+        }
+    }
+    for i in 0..a_blocks.len() {
+        if a_group[i].is_some() {
+            continue;
+        }
+        let Some(j) = container(&a_blocks[i], b_blocks) else {
+            continue;
+        };
+        match b_group[j] {
+            Some(g) if groups[g].1.len() == 1 => {
+                groups[g].0.push(i);
+                a_group[i] = Some(g);
+            }
+            Some(_) => {}
+            None => {
+                a_group[i] = Some(groups.len());
+                b_group[j] = Some(groups.len());
+                groups.push((vec![i], vec![j]));
+            }
+        }
+    }
+
+    for (a_idxs, b_idxs) in &mut groups {
+        a_idxs.sort_unstable();
+        b_idxs.sort_unstable();
+    }
+    groups
+}
+
+/// How well `a` and `b` fit as a pair, or `None` if they should not pair:
+/// overlap plus a bonus for the same kind, less a little for distance.
+fn pair_score(a: &Block, b: &Block, dpi: u32) -> Option<f32> {
+    // Text, tables and figures only pair with their own kind: a mixed pair
+    // resolves into one block and loses the other's content. Parser tables
+    // come from vector rulings, so only an OCR table can confirm one.
+    let mixed = a.kind() != b.kind();
+    let math = a.kind() == "math" || b.kind() == "math";
+    if mixed && (a.kind() == "table" || !math) {
+        return None;
+    }
+    let iou = a.bbox().iou(&b.bbox());
+    let dist = a.bbox().center_distance(&b.bbox()) * 72.0 / dpi as f32;
+    let kind_bonus = if a.kind() == b.kind() { 0.1 } else { 0.0 };
+    let score = iou + kind_bonus - dist / DISTANCE_PENALTY;
+    (score > 0.0 && (iou > 0.1 || dist < PAIR_MAX_DISTANCE)).then_some(score)
+}
+
+/// The text block among `blocks` holding most of text block `block`, if it
+/// holds at least [`GROUP_COVERAGE`] of it.
+fn container(block: &Block, blocks: &[Block]) -> Option<usize> {
+    if !matches!(block, Block::TextBlock { .. }) {
+        return None;
+    }
+    let inner = block.bbox();
+    let area = inner.area();
+    if area <= 0.0 {
+        return None;
     }
+    blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, other)| matches!(other, Block::TextBlock { .. }))
+        .map(|(idx, other)| {
+            let outer = other.bbox();
+            let inside = BBox::new(
+                inner.x0.max(outer.x0),
+                inner.y0.max(outer.y0),
+                inner.x1.min(outer.x1),
+                inner.y1.min(outer.y1),
+            );
+            (idx, inside.area() / area)
+        })
+        .filter(|(_, coverage)| *coverage >= GROUP_COVERAGE)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(idx, _)| idx)
+}
+
+/// Minimum-cost assignment of every row to a distinct column, for a cost
+/// matrix with no more rows than columns. Returns each row's column.
+fn hungarian(cost: &[Vec<f64>]) -> Vec<usize> {
+    let (rows, cols) = (cost.len(), cost[0].len());
+    // Potentials and the augmenting-path search run over 1-based indices,
+    // with column 0 as the virtual start.
+    let mut u = vec![0.0; rows + 1];
+    let mut v = vec![0.0; cols + 1];
+    let mut row_of = vec![0usize; cols + 1];
+    let mut way = vec![0usize; cols + 1];
+
+    for row in 1..=rows {
+        row_of[0] = row;
+        let mut col0 = 0;
+        let mut min_slack = vec![f64::INFINITY; cols + 1];
+        let mut visited = vec![false; cols + 1];
+        loop {
+            visited[col0] = true;
+            let row0 = row_of[col0];
+            let mut delta = f64::INFINITY;
+            let mut col1 = 0;
+            for col in 1..=cols {
+                if visited[col] {
+                    continue;
+                }
+                let slack = cost[row0 - 1][col - 1] - u[row0] - v[col];
+                if slack < min_slack[col] {
+                    min_slack[col] = slack;
+                    way[col] = col0;
+                }
+                if min_slack[col] < delta {
+                    delta = min_slack[col];
+                    col1 = col;
+                }
+            }
+            for col in 0..=cols {
+                if visited[col] {
+                    u[row_of[col]] += delta;
+                    v[col] -= delta;
+                } else {
+                    min_slack[col] -= delta;
+                }
+            }
+            col0 = col1;
+            if row_of[col0] == 0 {
+                break;
+            }
+        }
+        while col0 != 0 {
+            let prev = way[col0];
+            row_of[col0] = row_of[prev];
+            col0 = prev;
+        }
+    }
+
+    let mut assignment = vec![0; rows];
+    for (col, &row) in row_of.iter().enumerate().skip(1) {
+        if row != 0 {
+            assignment[row - 1] = col - 1;
+        }
+    }
+    assignment
 }
 
 #[cfg(test)]
@@ -76,6 +401,7 @@ mod tests {
     use super::*;
     use crate::core::geometry::BBox;
     use crate::core::model::{Block, Line, Provenance, Span};
+    use crate::fusion::DEFAULT_DPI;
 
     fn text_block(bbox: BBox) -> Block {
         Block::TextBlock {
@@ -86,10 +412,15 @@ mod tests {
                     bbox,
                     source: Provenance::Parser,
                     style: None,
+                    link: None,
+                    revision: None,
                 }],
             }],
             confidence: 0.5,
             source: Provenance::Parser,
+            role: None,
+            order: None,
+            tracks: Vec::new(),
             debug: None,
         }
     }
@@ -98,9 +429,178 @@ mod tests {
     fn aligns_overlapping_blocks() {
         let a = text_block(BBox::new(0.0, 0.0, 50.0, 50.0));
         let b = text_block(BBox::new(10.0, 10.0, 60.0, 60.0));
-        let result = align_blocks(&[a.clone()], &[b.clone()]);
+        let result = align_blocks(&[a], &[b], DEFAULT_DPI);
         assert_eq!(result.matched.len(), 1);
         assert!(result.unmatched_a.is_empty());
         assert!(result.unmatched_b.is_empty());
     }
+
+    #[test]
+    fn pairing_distance_follows_the_rendering_dpi() {
+        // Half an inch apart: 100 px at 200 DPI, 300 px at 600 DPI.
+        let pair_at = |dpi: u32| {
+            let px = dpi as f32 / 200.0;
+            let a = text_block(BBox::new(0.0, 0.0, 40.0 * px, 20.0 * px));
+            let b = text_block(BBox::new(100.0 * px, 0.0, 140.0 * px, 20.0 * px));
+            align_blocks(&[a], &[b], dpi).matched.len()
+        };
+        assert_eq!(pair_at(DEFAULT_DPI), 1);
+        assert_eq!(pair_at(600), 1);
+    }
+
+    #[test]
+    fn parser_tables_only_pair_with_tables() {
+        let table = |source| Block::TableBlock {
+            bbox: BBox::new(0.0, 0.0, 100.0, 60.0),
+            confidence: 0.6,
+            source,
+            cells: Vec::new(),
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        let ocr_text = text_block(BBox::new(5.0, 5.0, 95.0, 55.0));
+
+        let result = align_blocks(
+            &[table(Provenance::Parser)],
+            std::slice::from_ref(&ocr_text),
+            DEFAULT_DPI,
+        );
+        assert!(result.matched.is_empty());
+
+        let result = align_blocks(
+            &[table(Provenance::Parser)],
+            &[ocr_text, table(Provenance::Ocr)],
+            DEFAULT_DPI,
+        );
+        assert_eq!(result.matched.len(), 1);
+        assert_eq!(result.matched[0].b.kind(), "table");
+        assert_eq!(result.unmatched_b.len(), 1);
+    }
+
+    #[test]
+    fn text_never_pairs_with_tables_or_figures() {
+        let text = text_block(BBox::new(5.0, 5.0, 95.0, 55.0));
+        let figure = Block::FigureBlock {
+            bbox: BBox::new(0.0, 0.0, 100.0, 60.0),
+            confidence: 0.5,
+            source: Provenance::Ocr,
+            image: None,
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        let table = Block::TableBlock {
+            bbox: BBox::new(0.0, 0.0, 100.0, 60.0),
+            confidence: 0.5,
+            source: Provenance::Ocr,
+            cells: Vec::new(),
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        for other in [figure, table] {
+            let forward = align_blocks(
+                std::slice::from_ref(&text),
+                std::slice::from_ref(&other),
+                DEFAULT_DPI,
+            );
+            let backward = align_blocks(&[other], std::slice::from_ref(&text), DEFAULT_DPI);
+            assert!(forward.matched.is_empty() && backward.matched.is_empty());
+        }
+    }
+
+    #[test]
+    fn clusters_blocks_across_tracks() {
+        let parser = [text_block(BBox::new(0.0, 0.0, 100.0, 40.0))];
+        let ocr = [
+            text_block(BBox::new(2.0, 1.0, 98.0, 41.0)),
+            text_block(BBox::new(0.0, 500.0, 100.0, 540.0)),
+        ];
+        let layer = [
+            text_block(BBox::new(1.0, 501.0, 99.0, 539.0)),
+            text_block(BBox::new(1.0, 2.0, 99.0, 39.0)),
+        ];
+
+        let clusters = align_tracks(&[&parser, &ocr, &layer], DEFAULT_DPI);
+
+        let tracks: Vec<Vec<usize>> = clusters
+            .iter()
+            .map(|cluster| cluster.members.iter().map(|m| m.track).collect())
+            .collect();
+        assert_eq!(tracks, vec![vec![0, 1, 2], vec![1, 2]]);
+    }
+
+    fn figure(bbox: BBox) -> Block {
+        Block::FigureBlock {
+            bbox,
+            confidence: 0.5,
+            source: Provenance::Parser,
+            image: None,
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        }
+    }
+
+    #[test]
+    fn assignment_beats_first_come_pairing() {
+        // The first block fits `b[0]` best, but only `b[0]` fits the second
+        // block; greedy pairing would leave the second block alone.
+        let a = [
+            figure(BBox::new(0.0, 0.0, 100.0, 100.0)),
+            figure(BBox::new(60.0, 0.0, 160.0, 100.0)),
+        ];
+        let b = [
+            figure(BBox::new(25.0, 0.0, 125.0, 100.0)),
+            figure(BBox::new(-90.0, 0.0, 10.0, 100.0)),
+        ];
+
+        let result = align_blocks(&a, &b, DEFAULT_DPI);
+
+        assert_eq!(result.matched.len(), 2);
+        assert_eq!(result.matched[0].b.bbox(), b[1].bbox());
+        assert_eq!(result.matched[1].b.bbox(), b[0].bbox());
+    }
+
+    #[test]
+    fn groups_lines_with_their_paragraph() {
+        let paragraph = [text_block(BBox::new(0.0, 0.0, 500.0, 100.0))];
+        let lines = [
+            text_block(BBox::new(0.0, 0.0, 500.0, 30.0)),
+            text_block(BBox::new(0.0, 35.0, 500.0, 65.0)),
+            text_block(BBox::new(0.0, 70.0, 500.0, 100.0)),
+        ];
+
+        let one_to_many = align_blocks(&paragraph, &lines, DEFAULT_DPI);
+        assert!(one_to_many.matched.is_empty());
+        assert_eq!(one_to_many.groups.len(), 1);
+        assert_eq!(one_to_many.groups[0].a.len(), 1);
+        assert_eq!(one_to_many.groups[0].b.len(), 3);
+        assert!(one_to_many.unmatched_b.is_empty());
+
+        let many_to_one = align_blocks(&lines, &paragraph, DEFAULT_DPI);
+        assert_eq!(many_to_one.groups.len(), 1);
+        assert_eq!(many_to_one.groups[0].a.len(), 3);
+        assert!(many_to_one.unmatched_a.is_empty());
+
+        let merged = merge_group(&lines);
+        assert_eq!(merged.bbox(), paragraph[0].bbox());
+        assert_eq!(merged.text_content().as_deref(), Some("hello hello hello"));
+    }
+
+    #[test]
+    fn a_block_spanning_clusters_joins_them() {
+        let lines = [
+            text_block(BBox::new(0.0, 0.0, 500.0, 30.0)),
+            text_block(BBox::new(0.0, 300.0, 500.0, 330.0)),
+        ];
+        let paragraph = [text_block(BBox::new(0.0, 0.0, 500.0, 330.0))];
+
+        let clusters = align_tracks(&[&lines, &paragraph], DEFAULT_DPI);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].blocks_of(0).len(), 2);
+        assert_eq!(clusters[0].blocks_of(1).len(), 1);
+    }
 }
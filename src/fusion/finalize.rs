@@ -1,26 +1,53 @@
-use crate::core::model::{PageClass, PageHypothesis};
-use crate::core::page_classifier::{classify_page as classify_page_internal, PageSignals};
+use crate::core::model::{PageClass, Provenance};
+use crate::core::page_classifier::{
+    classify_page as classify_page_internal, PageSignals, PixelSignals,
+};
+use crate::core::policy::ClassifyPolicy;
+use crate::fusion::Track;
 
-pub fn classify_page(parser: &PageHypothesis, ocr: &PageHypothesis) -> PageClass {
-    let parser_glyphs = parser
-        .blocks
-        .iter()
-        .filter_map(|block| block.text_content())
-        .map(|text| text.len())
-        .sum::<usize>();
-    let ocr_glyphs = ocr
-        .blocks
+/// Classify a page from its tracks. Document-text tracks count as parser
+/// glyphs, OCR and text-layer tracks as OCR glyphs; where several tracks
+/// read the page the same way, the fullest one counts. `pixels`, measured on
+/// the rendered page, replace the estimate of image coverage from OCR blocks.
+pub fn classify_page(
+    tracks: &[Track],
+    pixels: Option<&PixelSignals>,
+    policy: &ClassifyPolicy,
+) -> PageClass {
+    let glyphs = |parser_side: bool| {
+        tracks
+            .iter()
+            .filter(|track| (track.kind == Provenance::Parser) == parser_side)
+            .map(|track| {
+                track
+                    .hypothesis
+                    .blocks
+                    .iter()
+                    .filter_map(|block| block.text_content())
+                    .map(|text| text.len())
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0)
+    };
+    let parser_glyphs = glyphs(true);
+    let ocr_glyphs = glyphs(false);
+    let width = tracks.iter().map(|t| t.hypothesis.width).max().unwrap_or(0);
+    let height = tracks
         .iter()
-        .filter_map(|block| block.text_content())
-        .map(|text| text.len())
-        .sum::<usize>();
-    let page_area = (parser.width.max(ocr.width) as f32) * (parser.height.max(ocr.height) as f32);
-    let ocr_coverage = if page_area <= 0.0 {
+        .map(|t| t.hypothesis.height)
+        .max()
+        .unwrap_or(0);
+    let page_area = width as f32 * height as f32;
+    let ocr_coverage = if let Some(pixels) = pixels {
+        pixels.embedded_image_coverage
+    } else if page_area <= 0.0 {
         0.0
     } else {
-        let max_block_area = ocr
-            .blocks
+        let max_block_area = tracks
             .iter()
+            .filter(|track| track.kind != Provenance::Parser)
+            .flat_map(|track| &track.hypothesis.blocks)
             .map(|block| block.bbox().area())
             .fold(0.0_f32, f32::max);
         (max_block_area / page_area).clamp(0.0, 1.0)
@@ -30,7 +57,8 @@ pub fn classify_page(parser: &PageHypothesis, ocr: &PageHypothesis) -> PageClass
         parser_glyphs,
         ocr_glyphs,
         image_coverage: ocr_coverage,
-        ocr_text_density: (ocr_glyphs as f32 / 1000.0).min(1.0),
+        ocr_text_density: (ocr_glyphs as f32 / policy.dense_page_glyphs.max(1) as f32).min(1.0),
+        pixels: pixels.copied(),
     };
-    classify_page_internal(signals)
+    classify_page_internal(signals, policy)
 }
@@ -2,36 +2,240 @@ pub mod align;
 pub mod compare;
 pub mod finalize;
 pub mod resolve;
+pub mod tokens;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use crate::core::model::{PageFinal, PageHypothesis};
+use crate::core::geometry::BBox;
+use crate::core::model::{PageDebug, PageFinal, PageHypothesis, Provenance};
+use crate::core::page_classifier::PixelSignals;
+use crate::core::policy::FusionPolicy;
+
+/// Track id of the document's own text (PDF content stream, DOCX, PPTX).
+pub const PARSER_TRACK: &str = "parser";
+/// Track id of our OCR of the rendered page.
+pub const OCR_TRACK: &str = "ocr";
+/// Track id of an invisible OCR text layer found in the PDF.
+pub const TEXT_LAYER_TRACK: &str = "text_layer";
+
+/// Rendering resolution fusion assumes unless told otherwise, the CLI's
+/// `--dpi` default. Hypotheses are in pixels of a page rendered at it.
+pub const DEFAULT_DPI: u32 = 200;
+
+/// Prior trust in an existing text layer, relative to our own OCR: it was
+/// read by a tool we know nothing about.
+pub const TEXT_LAYER_TRUST: f32 = 0.6;
+
+/// One source's hypothesis for a page. `kind` says how the track reads the
+/// page (document text, pixels, or a foreign text layer), which decides how
+/// its blocks are weighed against the others; `trust` scales the
+/// confidence of blocks the track wins.
+#[derive(Debug, Clone, Copy)]
+pub struct Track<'a> {
+    pub id: &'a str,
+    pub kind: Provenance,
+    pub trust: f32,
+    pub hypothesis: &'a PageHypothesis,
+}
+
+impl<'a> Track<'a> {
+    pub fn new(id: &'a str, kind: Provenance, hypothesis: &'a PageHypothesis) -> Self {
+        Self {
+            id,
+            kind,
+            trust: 1.0,
+            hypothesis,
+        }
+    }
+
+    pub fn parser(hypothesis: &'a PageHypothesis) -> Self {
+        Self::new(PARSER_TRACK, Provenance::Parser, hypothesis)
+    }
+
+    pub fn ocr(hypothesis: &'a PageHypothesis) -> Self {
+        Self::new(OCR_TRACK, Provenance::Ocr, hypothesis)
+    }
+
+    pub fn text_layer(hypothesis: &'a PageHypothesis) -> Self {
+        Self::new(TEXT_LAYER_TRACK, Provenance::TextLayer, hypothesis).with_trust(TEXT_LAYER_TRUST)
+    }
+
+    pub fn with_trust(mut self, trust: f32) -> Self {
+        self.trust = trust;
+        self
+    }
+}
 
 pub trait FusionEngine {
-    fn fuse(&self, parser: &PageHypothesis, ocr: &PageHypothesis) -> Result<PageFinal>;
+    /// Fuse any number of tracks for one page. Tracks are aligned in the
+    /// order given, so the most structured source should come first.
+    /// `pixels`, measured on the rendered page, sharpen page classification.
+    fn fuse_page(&self, tracks: &[Track], pixels: Option<&PixelSignals>) -> Result<PageFinal>;
+
+    /// Fuse tracks for a page that was not measured.
+    fn fuse_tracks(&self, tracks: &[Track]) -> Result<PageFinal> {
+        self.fuse_page(tracks, None)
+    }
+
+    /// Fuse the usual parser and OCR pair.
+    fn fuse(&self, parser: &PageHypothesis, ocr: &PageHypothesis) -> Result<PageFinal> {
+        self.fuse_tracks(&[Track::parser(parser), Track::ocr(ocr)])
+    }
 }
 
-#[derive(Debug, Default)]
-pub struct SimpleFusionEngine;
+#[derive(Debug)]
+pub struct SimpleFusionEngine {
+    policy: FusionPolicy,
+    /// Resolution of the page images the hypotheses' pixels belong to,
+    /// which the policy's point-based lengths are scaled to.
+    dpi: u32,
+}
+
+impl Default for SimpleFusionEngine {
+    fn default() -> Self {
+        Self {
+            policy: FusionPolicy::default(),
+            dpi: DEFAULT_DPI,
+        }
+    }
+}
 
 impl SimpleFusionEngine {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_policy(mut self, policy: FusionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_dpi(mut self, dpi: u32) -> Self {
+        self.dpi = dpi;
+        self
     }
 }
 
 impl FusionEngine for SimpleFusionEngine {
-    fn fuse(&self, parser: &PageHypothesis, ocr: &PageHypothesis) -> Result<PageFinal> {
-        let page_class = finalize::classify_page(parser, ocr);
-        let aligned = align::align_blocks(&parser.blocks, &ocr.blocks);
-        let resolved = resolve::resolve_blocks(&aligned, page_class);
+    fn fuse_page(&self, tracks: &[Track], pixels: Option<&PixelSignals>) -> Result<PageFinal> {
+        let Some(first) = tracks.first() else {
+            bail!("no hypotheses to fuse");
+        };
+        let policy = self.policy.at_dpi(self.dpi);
+        let page_class = finalize::classify_page(tracks, pixels, &policy.classify);
+        let blocks: Vec<&[_]> = tracks
+            .iter()
+            .map(|track| track.hypothesis.blocks.as_slice())
+            .collect();
+        let clusters = align::align_tracks(&blocks, self.dpi);
+        let width = tracks.iter().map(|t| t.hypothesis.width).max().unwrap_or(0);
+        let height = tracks
+            .iter()
+            .map(|t| t.hypothesis.height)
+            .max()
+            .unwrap_or(0);
+        let page = BBox::new(0.0, 0.0, width as f32, height as f32);
+        let mut decisions = Vec::new();
+        let resolved =
+            resolve::resolve_clusters(&clusters, tracks, page_class, page, &policy, &mut decisions);
         Ok(PageFinal {
-            page_idx: parser.page_idx,
+            page_idx: first.hypothesis.page_idx,
             class: page_class,
             blocks: resolved,
-            width: parser.width.max(ocr.width),
-            height: parser.height.max(ocr.height),
-            debug: None,
+            width,
+            height,
+            geometry: None,
+            debug: Some(PageDebug {
+                decisions,
+                ..Default::default()
+            }),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::model::{Block, Line, Span};
+
+    fn hypothesis(blocks: Vec<(&str, Provenance, BBox)>) -> PageHypothesis {
+        PageHypothesis {
+            page_idx: 0,
+            blocks: blocks
+                .into_iter()
+                .map(|(text, source, bbox)| Block::TextBlock {
+                    bbox,
+                    lines: vec![Line {
+                        spans: vec![Span {
+                            text: text.to_string(),
+                            bbox,
+                            source,
+                            style: None,
+                            link: None,
+                            revision: None,
+                        }],
+                    }],
+                    confidence: 0.6,
+                    source,
+                    role: None,
+                    order: None,
+                    tracks: Vec::new(),
+                    debug: None,
+                })
+                .collect(),
+            width: 1000,
+            height: 1000,
+        }
+    }
+
+    #[test]
+    fn text_layer_only_fills_areas_ocr_missed() {
+        let title = BBox::new(100.0, 100.0, 600.0, 140.0);
+        let parser = hypothesis(vec![]);
+        let ocr = hypothesis(vec![("Quarterly report", Provenance::Ocr, title)]);
+        let layer = hypothesis(vec![
+            ("Quarterly report", Provenance::TextLayer, title),
+            (
+                "Footnote",
+                Provenance::TextLayer,
+                BBox::new(100.0, 900.0, 300.0, 930.0),
+            ),
+        ]);
+
+        let fused = SimpleFusionEngine::new()
+            .fuse_tracks(&[
+                Track::parser(&parser),
+                Track::ocr(&ocr),
+                Track::text_layer(&layer),
+            ])
+            .unwrap();
+
+        let blocks: Vec<(String, Provenance, Vec<String>)> = fused
+            .blocks
+            .iter()
+            .map(|block| {
+                (
+                    block.text_content().unwrap(),
+                    block.provenance(),
+                    block.tracks().to_vec(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                (
+                    "Quarterly report".to_string(),
+                    Provenance::Ocr,
+                    vec!["ocr".to_string(), "text_layer".to_string()]
+                ),
+                (
+                    "Footnote".to_string(),
+                    Provenance::TextLayer,
+                    vec!["text_layer".to_string()]
+                ),
+            ]
+        );
+        assert!(fused.blocks[1].confidence() < fused.blocks[0].confidence());
+    }
+}
@@ -1,107 +1,276 @@
 use crate::core::confidence::score_confidence;
-use crate::core::model::{Block, BlockDebug, Line, PageClass, Provenance};
-use crate::fusion::align::{AlignmentResult, MatchedPair};
+use crate::core::geometry::BBox;
+use crate::core::model::{
+    Block, BlockDebug, DecisionAction, FusionDecision, Line, PageClass, Provenance,
+};
+use crate::core::policy::{FilterPolicy, FusionPolicy, ResolvePolicy};
+use crate::fusion::align::{merge_group, Cluster, MatchedPair};
 use crate::fusion::compare::text_similarity;
+use crate::fusion::tokens::fuse_tokens;
+use crate::fusion::Track;
+
+/// Resolve clustered blocks into final blocks. In each cluster the most
+/// trusted document-text track is weighed against the most trusted OCR-side
+/// track (our OCR or a text layer), each with its blocks there merged into
+/// one; the result records which tracks agreed with it. `page` is the page
+/// extent in the hypotheses' coordinate space, used to spot page-sized
+/// parser blocks. Every threshold comes from `policy`.
+/// Each decision, including every block left out, is appended to `trace`.
+pub fn resolve_clusters(
+    clusters: &[Cluster],
+    tracks: &[Track],
+    page_class: PageClass,
+    page: BBox,
+    policy: &FusionPolicy,
+    trace: &mut Vec<FusionDecision>,
+) -> Vec<Block> {
+    let strongest = |cluster: &'_ Cluster, parser_side: bool| -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for member in &cluster.members {
+            let track = &tracks[member.track];
+            if (track.kind == Provenance::Parser) != parser_side {
+                continue;
+            }
+            if best.is_none_or(|best| track.trust > tracks[best].trust) {
+                best = Some(member.track);
+            }
+        }
+        best
+    };
 
-pub fn resolve_blocks(alignment: &AlignmentResult, page_class: PageClass) -> Vec<Block> {
-    let mut blocks = Vec::new();
+    // Count OCR-only clusters to detect multi-column layouts
+    let ocr_block_count = clusters
+        .iter()
+        .filter(|cluster| strongest(cluster, true).is_none())
+        .count();
 
-    for pair in &alignment.matched {
-        blocks.push(resolve_pair(pair, page_class));
+    let mut blocks = Vec::new();
+    for cluster in clusters {
+        let parser = strongest(cluster, true);
+        let ocr = strongest(cluster, false);
+        let merged = |track: usize| merge_group(&cluster.blocks_of(track));
+        let resolved = match (parser, ocr) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (merged(a), merged(b));
+                let pair = MatchedPair {
+                    iou: a.bbox().iou(&b.bbox()),
+                    center_distance: a.bbox().center_distance(&b.bbox()),
+                    a,
+                    b,
+                };
+                let (block, rule) = resolve_pair(&pair, page_class, page, policy);
+                trace.push(pair_decision(&pair, rule, &block));
+                block
+            }
+            (Some(a), None) => {
+                let block = merged(a);
+                let skip =
+                    skip_page_sized_parser_block(&block, page, ocr_block_count, &policy.resolve);
+                if let Some(rule) = skip {
+                    trace.push(FusionDecision::about(DecisionAction::Dropped, rule, &block));
+                    continue;
+                }
+                let block = promote_single(block, Provenance::Parser, page_class, policy);
+                trace.push(FusionDecision::about(
+                    DecisionAction::Promoted,
+                    "parser_only",
+                    &block,
+                ));
+                block
+            }
+            (None, Some(b)) => {
+                // The OCR side also carries blocks from an existing text layer.
+                let (provenance, rule) = match tracks[b].kind {
+                    Provenance::TextLayer => (Provenance::TextLayer, "text_layer_only"),
+                    _ => (Provenance::Ocr, "ocr_only"),
+                };
+                let block = promote_single(merged(b), provenance, page_class, policy);
+                trace.push(FusionDecision::about(
+                    DecisionAction::Promoted,
+                    rule,
+                    &block,
+                ));
+                block
+            }
+            (None, None) => continue,
+        };
+        blocks.push(weigh_tracks(
+            resolved,
+            cluster,
+            tracks,
+            parser,
+            ocr,
+            &policy.resolve,
+        ));
     }
 
-    // Count OCR blocks to detect multi-column layouts
-    let ocr_block_count = alignment.unmatched_b.len();
-
-    for block in &alignment.unmatched_a {
-        // Skip oversized parser blocks when there are many OCR blocks (likely multi-column)
-        // Check if this is a full-page parser block (common in PDF text extraction)
-        let bbox = block.bbox();
-        let parser_text = block.text_content().unwrap_or_default();
-        let parser_korean_reliable =
-            has_korean_chars(&parser_text) && korean_text_quality(&parser_text) >= 4;
-        let is_full_page = bbox.x0.abs() < 10.0
-            && bbox.y0.abs() < 10.0
-            && (bbox.x1 - 1000.0).abs() < 10.0
-            && (bbox.y1 - 1400.0).abs() < 10.0;
-
-        if is_full_page && ocr_block_count >= 10 && !parser_korean_reliable {
-            // Skip this full-page parser block in favor of OCR blocks
-            eprintln!(
-                "[DEBUG] Skipping full-page parser block ({} OCR blocks)",
-                ocr_block_count
-            );
-            continue;
+    let filter = &policy.filter;
+    let blocks = filter_degraded_parser_blocks(blocks, filter, trace);
+    let resolved = match page_class {
+        PageClass::Digital => {
+            let blocks = filter_redundant_ocr_text_blocks(blocks, policy, trace);
+            let blocks = filter_low_quality_ocr_text_blocks(blocks, true, filter, trace);
+            let blocks = filter_korean_ocr_when_parser_reliable(blocks, true, filter, trace);
+            filter_ocr_text_when_parser_reliable(blocks, page_class, policy, trace)
         }
-
-        // Also check area-based criterion
-        let block_area = bbox.width() * bbox.height();
-        let page_area = 1000.0 * 1400.0;
-        let is_oversized = block_area / page_area > 0.7;
-
-        if is_oversized && ocr_block_count >= 10 && !parser_korean_reliable {
-            eprintln!(
-                "[DEBUG] Skipping oversized parser block (area={:.1}%, {} OCR blocks)",
-                block_area / page_area * 100.0,
-                ocr_block_count
-            );
-            continue;
+        PageClass::Hybrid => {
+            let blocks = filter_redundant_ocr_text_blocks(blocks, policy, trace);
+            let blocks = filter_low_quality_ocr_text_blocks(blocks, true, filter, trace);
+            let blocks = filter_korean_ocr_when_parser_reliable(blocks, false, filter, trace);
+            filter_ocr_text_when_parser_reliable(blocks, page_class, policy, trace)
         }
+        PageClass::Scanned => filter_low_quality_ocr_text_blocks(blocks, false, filter, trace),
+    };
 
-        blocks.push(promote_single(
-            block.clone(),
-            Provenance::Parser,
-            page_class,
-        ));
+    sort_blocks_in_reading_order(resolved, &policy.resolve)
+}
+
+/// The rule dropping an unpaired parser block that spans (nearly) the whole
+/// page while OCR found many blocks of its own, if it applies; such a block is
+/// usually a multi-column page extracted as one run, and OCR's blocks
+/// describe the layout better.
+fn skip_page_sized_parser_block(
+    block: &Block,
+    page: BBox,
+    ocr_block_count: usize,
+    policy: &ResolvePolicy,
+) -> Option<&'static str> {
+    // Check if this is a full-page parser block (common in PDF text extraction)
+    let bbox = block.bbox();
+    let parser_text = block.text_content().unwrap_or_default();
+    let parser_korean_reliable = has_korean_chars(&parser_text)
+        && korean_text_quality(&parser_text) >= policy.reliable_korean_quality;
+    // Embedded-image figures are placed exactly, whatever their size.
+    let is_text = matches!(block, Block::TextBlock { .. });
+    let tolerance = policy.page_sized_edge_tolerance;
+    let is_full_page = is_text
+        && (bbox.x0 - page.x0).abs() < tolerance
+        && (bbox.y0 - page.y0).abs() < tolerance
+        && (bbox.x1 - page.x1).abs() < tolerance
+        && (bbox.y1 - page.y1).abs() < tolerance;
+    let many_ocr_blocks = ocr_block_count >= policy.page_sized_min_ocr_blocks;
+
+    if is_full_page && many_ocr_blocks && !parser_korean_reliable {
+        // Skip this full-page parser block in favor of OCR blocks
+        return Some("full_page_parser_block");
     }
 
-    for block in &alignment.unmatched_b {
-        blocks.push(promote_single(block.clone(), Provenance::Ocr, page_class));
+    // Also check area-based criterion
+    let block_area = bbox.width() * bbox.height();
+    let page_area = page.area().max(1.0);
+    let is_oversized = is_text && block_area / page_area > policy.page_sized_area_ratio;
+
+    if is_oversized && many_ocr_blocks && !parser_korean_reliable {
+        return Some("oversized_parser_block");
     }
+    None
+}
 
-    let blocks = filter_degraded_parser_blocks(blocks);
-    let resolved = match page_class {
-        PageClass::Digital => {
-            let blocks = filter_redundant_ocr_text_blocks(blocks);
-            let blocks = filter_low_quality_ocr_text_blocks(blocks, true);
-            let blocks = filter_korean_ocr_when_parser_reliable(blocks, true);
-            filter_ocr_text_when_parser_reliable(blocks, page_class)
+/// Scale a resolved block's confidence by the trust of the track(s) it came
+/// from, add a bonus for extra agreeing tracks, and record which tracks
+/// agreed: those whose text reads like the result, or for blocks without
+/// text, those that found a block of the same kind.
+fn weigh_tracks(
+    mut block: Block,
+    cluster: &Cluster,
+    tracks: &[Track],
+    parser: Option<usize>,
+    ocr: Option<usize>,
+    policy: &ResolvePolicy,
+) -> Block {
+    let trust_of = |track: Option<usize>| track.map(|track| tracks[track].trust);
+    let trust = match block.provenance() {
+        Provenance::Parser => trust_of(parser),
+        Provenance::Ocr | Provenance::TextLayer => trust_of(ocr),
+        Provenance::Fused => match (trust_of(parser), trust_of(ocr)) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        },
+    }
+    .unwrap_or(1.0);
+
+    let text = block.text_content();
+    let mut agreed: Vec<String> = Vec::new();
+    let mut seen: Vec<usize> = Vec::new();
+    for member in &cluster.members {
+        if seen.contains(&member.track) {
+            continue;
         }
-        PageClass::Hybrid => {
-            let blocks = filter_redundant_ocr_text_blocks(blocks);
-            let blocks = filter_low_quality_ocr_text_blocks(blocks, true);
-            let blocks = filter_korean_ocr_when_parser_reliable(blocks, false);
-            filter_ocr_text_when_parser_reliable(blocks, page_class)
+        seen.push(member.track);
+        let reading = merge_group(&cluster.blocks_of(member.track));
+        let agrees = match (&text, reading.text_content()) {
+            (Some(text), Some(other)) => {
+                text_similarity(text, &other) >= policy.agreement_similarity
+            }
+            (None, None) => reading.kind() == block.kind(),
+            _ => false,
+        };
+        if agrees {
+            agreed.push(tracks[member.track].id.to_string());
         }
-        PageClass::Scanned => filter_low_quality_ocr_text_blocks(blocks, false),
-    };
+    }
 
-    sort_blocks_in_reading_order(resolved)
+    let extra = agreed.len().saturating_sub(2) as f32;
+    match &mut block {
+        Block::TextBlock {
+            confidence, tracks, ..
+        }
+        | Block::TableBlock {
+            confidence, tracks, ..
+        }
+        | Block::FigureBlock {
+            confidence, tracks, ..
+        }
+        | Block::MathBlock {
+            confidence, tracks, ..
+        } => {
+            *confidence = (*confidence * trust + policy.agreement_bonus * extra).clamp(0.0, 1.0);
+            *tracks = agreed;
+        }
+    }
+    block
 }
 
-fn promote_single(block: Block, provenance: Provenance, page_class: PageClass) -> Block {
+fn promote_single(
+    block: Block,
+    provenance: Provenance,
+    page_class: PageClass,
+    policy: &FusionPolicy,
+) -> Block {
+    let resolve = &policy.resolve;
+    let base = |has_ocr: bool| {
+        score_confidence(
+            &policy.confidence,
+            provenance == Provenance::Parser,
+            has_ocr,
+            None,
+            true,
+        )
+    };
     match block {
-        Block::TextBlock { bbox, lines, .. } => {
-            let mut confidence = score_confidence(
-                provenance == Provenance::Parser,
-                provenance == Provenance::Ocr,
-                None,
-                true,
-            );
+        Block::TextBlock {
+            bbox,
+            lines,
+            role,
+            order,
+            ..
+        } => {
+            let from_ocr = matches!(provenance, Provenance::Ocr | Provenance::TextLayer);
+            let mut confidence = base(from_ocr);
             let final_text = text_from_lines(&lines);
             if provenance == Provenance::Parser
                 && final_text
                     .as_deref()
-                    .map(is_korean_parser_degraded)
+                    .map(|text| is_korean_parser_degraded(text, resolve))
                     .unwrap_or(false)
             {
-                confidence = (confidence - 0.2).clamp(0.0, 1.0);
+                confidence = (confidence - resolve.degraded_korean_penalty).clamp(0.0, 1.0);
             }
             if page_class == PageClass::Scanned {
                 if provenance == Provenance::Ocr {
-                    confidence = (confidence + 0.08).clamp(0.0, 1.0);
+                    confidence = (confidence + resolve.scanned_ocr_bonus).clamp(0.0, 1.0);
                 } else if provenance == Provenance::Parser {
-                    confidence = (confidence - 0.1).clamp(0.0, 1.0);
+                    confidence = (confidence - resolve.scanned_parser_penalty).clamp(0.0, 1.0);
                 }
             }
             Block::TextBlock {
@@ -109,61 +278,71 @@ fn promote_single(block: Block, provenance: Provenance, page_class: PageClass) -
                 lines,
                 confidence,
                 source: provenance,
+                role,
+                order,
+                tracks: Vec::new(),
                 debug: Some(BlockDebug {
                     parser_text: if provenance == Provenance::Parser {
                         final_text.clone()
                     } else {
                         None
                     },
-                    ocr_text: if provenance == Provenance::Ocr {
-                        final_text.clone()
-                    } else {
-                        None
-                    },
+                    ocr_text: if from_ocr { final_text.clone() } else { None },
                     final_text,
                     similarity: None,
                 }),
             }
         }
-        Block::TableBlock { bbox, .. } => Block::TableBlock {
+        Block::TableBlock {
+            bbox, cells, order, ..
+        } => Block::TableBlock {
             bbox,
-            confidence: score_confidence(
-                provenance == Provenance::Parser,
-                provenance == Provenance::Ocr,
-                None,
-                true,
-            ),
+            confidence: base(provenance == Provenance::Ocr),
             source: provenance,
+            cells,
+            order,
+            tracks: Vec::new(),
             debug: None,
         },
-        Block::FigureBlock { bbox, .. } => Block::FigureBlock {
+        Block::FigureBlock {
+            bbox, image, order, ..
+        } => Block::FigureBlock {
             bbox,
-            confidence: score_confidence(
-                provenance == Provenance::Parser,
-                provenance == Provenance::Ocr,
-                None,
-                true,
-            ),
+            confidence: base(provenance == Provenance::Ocr),
             source: provenance,
+            image,
+            order,
+            tracks: Vec::new(),
             debug: None,
         },
-        Block::MathBlock { bbox, latex, .. } => Block::MathBlock {
+        Block::MathBlock {
+            bbox, latex, order, ..
+        } => Block::MathBlock {
             bbox,
-            confidence: score_confidence(
-                provenance == Provenance::Parser,
-                provenance == Provenance::Ocr,
-                None,
-                true,
-            ),
+            confidence: base(provenance == Provenance::Ocr),
             source: provenance,
             latex: latex.clone(),
+            order,
+            tracks: Vec::new(),
             debug: None,
         },
     }
 }
 
-fn resolve_pair(pair: &MatchedPair, page_class: PageClass) -> Block {
-    let geometry_good = pair.iou > 0.3 || pair.center_distance < 50.0;
+fn resolve_pair(
+    pair: &MatchedPair,
+    page_class: PageClass,
+    page: BBox,
+    policy: &FusionPolicy,
+) -> (Block, &'static str) {
+    let resolve = &policy.resolve;
+    let geometry_good = pair.iou > resolve.good_geometry_iou
+        || pair.center_distance < resolve.good_geometry_distance;
+    // Our OCR, or an existing text layer where OCR found nothing.
+    let ocr_source = match pair.b.provenance() {
+        Provenance::TextLayer => Provenance::TextLayer,
+        _ => Provenance::Ocr,
+    };
     let a_text = pair.a.text_content();
     let b_text = pair.b.text_content();
 
@@ -171,8 +350,9 @@ fn resolve_pair(pair: &MatchedPair, page_class: PageClass) -> Block {
     // If so, prefer OCR blocks which have better layout information
     let parser_bbox = pair.a.bbox();
     let parser_area = parser_bbox.width() * parser_bbox.height();
-    let page_area = 1000.0 * 1400.0;
-    let parser_is_oversized = parser_area / page_area > 0.58;
+    let page_area = page.area().max(1.0);
+    let parser_is_oversized = matches!(pair.a, Block::TextBlock { .. })
+        && parser_area / page_area > resolve.oversized_parser_ratio;
 
     // If parser block is oversized and similarity is low, prefer OCR
     if parser_is_oversized {
@@ -186,13 +366,12 @@ fn resolve_pair(pair: &MatchedPair, page_class: PageClass) -> Block {
             (Some(a), Some(b)) => text_similarity(a, b),
             _ => 0.0,
         };
-        if sim < 0.15 && !(korean_present && parser_quality >= 4) {
+        if sim < resolve.oversized_min_similarity
+            && !(korean_present && parser_quality >= resolve.reliable_korean_quality)
+        {
             // Very low similarity + oversized parser = prefer OCR for better layout
-            eprintln!(
-                "[DEBUG] Oversized parser block with low similarity ({:.3}), preferring OCR",
-                sim
-            );
-            return promote_single(pair.b.clone(), Provenance::Ocr, page_class);
+            let block = promote_single(pair.b.clone(), ocr_source, page_class, policy);
+            return (block, "dissimilar_oversized_parser");
         }
     }
 
@@ -201,17 +380,22 @@ fn resolve_pair(pair: &MatchedPair, page_class: PageClass) -> Block {
         _ => None,
     };
 
-    let mut confidence = score_confidence(true, true, similarity, geometry_good);
+    let mut confidence =
+        score_confidence(&policy.confidence, true, true, similarity, geometry_good);
 
     match (&pair.a, &pair.b) {
         (
             Block::TextBlock {
                 bbox,
                 lines: parser_lines,
+                role,
+                order,
                 ..
             },
             Block::TextBlock {
-                lines: ocr_lines, ..
+                lines: ocr_lines,
+                confidence: ocr_confidence,
+                ..
             },
         ) => {
             let parser_text = a_text.as_deref().unwrap_or_default();
@@ -223,110 +407,223 @@ fn resolve_pair(pair: &MatchedPair, page_class: PageClass) -> Block {
             let parser_len = parser_text.chars().count();
             let ocr_len = ocr_text.chars().count();
 
-            let (final_lines, provenance) = if sim >= 0.72 {
+            let margin = resolve.ocr_length_margin;
+            let parser_reliable =
+                korean_present && parser_quality >= resolve.reliable_korean_quality;
+            let ocr_noisy = is_noisy_ocr_text(ocr_text, &policy.filter);
+            let korean_ocr_better = |quality_margin: i32, length_margin: usize| {
+                korean_present
+                    && sim < resolve.korean_override_similarity
+                    && ocr_quality > parser_quality + quality_margin
+                    && ocr_len > parser_len + length_margin
+            };
+            let oversized_parser = parser_is_oversized
+                && sim < resolve.oversized_ocr_similarity
+                && ocr_len + margin >= parser_len
+                && !parser_reliable
+                && !ocr_noisy;
+            let take_ocr = |rule| (ocr_lines.clone(), ocr_source, rule);
+            let take_parser = |rule| (parser_lines.clone(), Provenance::Parser, rule);
+            let (final_lines, provenance, rule) = if sim >= resolve.fused_similarity {
                 if page_class == PageClass::Scanned {
-                    (ocr_lines.clone(), Provenance::Fused)
+                    (ocr_lines.clone(), Provenance::Fused, "similar_text_scanned")
                 } else if page_class == PageClass::Hybrid
                     && parser_is_oversized
-                    && ocr_len + 40 >= parser_len
-                    && !(korean_present && parser_quality >= 4)
+                    && ocr_len + margin >= parser_len
+                    && !parser_reliable
                     && !ocr_text.trim().is_empty()
                 {
                     // Oversized parser blocks usually lose layout semantics
                     // (multi-column, tables, equations). Keep OCR ordering.
-                    (ocr_lines.clone(), Provenance::Ocr)
+                    take_ocr("similar_text_oversized_parser")
                 } else {
-                    (parser_lines.clone(), Provenance::Fused)
+                    (parser_lines.clone(), Provenance::Fused, "similar_text")
                 }
             } else {
                 match page_class {
                     PageClass::Digital => {
-                        if korean_present
-                            && sim < 0.30
-                            && ocr_quality > parser_quality + 5
-                            && ocr_len > parser_len + 40
-                        {
-                            (ocr_lines.clone(), Provenance::Ocr)
-                        } else if parser_is_oversized
-                            && sim < 0.55
-                            && ocr_len + 40 >= parser_len
-                            && !(korean_present && parser_quality >= 4)
-                            && !is_noisy_ocr_text(ocr_text)
-                        {
-                            (ocr_lines.clone(), Provenance::Ocr)
+                        if korean_ocr_better(
+                            resolve.digital_korean_quality_margin,
+                            resolve.digital_korean_length_margin,
+                        ) {
+                            take_ocr("better_korean_ocr")
+                        } else if oversized_parser {
+                            take_ocr("oversized_parser")
                         } else {
-                            (parser_lines.clone(), Provenance::Parser)
+                            take_parser("parser_preferred")
                         }
                     }
                     PageClass::Hybrid => {
-                        if (korean_present
-                            && sim < 0.30
-                            && ocr_quality > parser_quality + 4
-                            && ocr_len > parser_len + 50)
-                            || (parser_is_oversized
-                                && sim < 0.55
-                                && ocr_len + 40 >= parser_len
-                                && !(korean_present && parser_quality >= 4)
-                                && !is_noisy_ocr_text(ocr_text))
-                            || (sim < 0.35
-                                && ocr_len > parser_len + 80
-                                && !is_noisy_ocr_text(ocr_text))
+                        if korean_ocr_better(
+                            resolve.hybrid_korean_quality_margin,
+                            resolve.hybrid_korean_length_margin,
+                        ) {
+                            take_ocr("better_korean_ocr")
+                        } else if oversized_parser {
+                            take_ocr("oversized_parser")
+                        } else if sim < resolve.hybrid_long_ocr_similarity
+                            && ocr_len > parser_len + resolve.hybrid_long_ocr_margin
+                            && !ocr_noisy
                         {
-                            (ocr_lines.clone(), Provenance::Ocr)
+                            take_ocr("longer_ocr")
                         } else {
-                            (parser_lines.clone(), Provenance::Parser)
+                            take_parser("parser_preferred")
                         }
                     }
                     PageClass::Scanned => {
-                        if !ocr_text.trim().is_empty() && !is_noisy_ocr_text(ocr_text) {
-                            (ocr_lines.clone(), Provenance::Ocr)
+                        if !ocr_text.trim().is_empty() && !ocr_noisy {
+                            take_ocr("scanned_ocr")
                         } else {
-                            (parser_lines.clone(), Provenance::Parser)
+                            take_parser("noisy_scanned_ocr")
                         }
                     }
                 }
             };
 
-            if korean_present && provenance == Provenance::Parser && parser_quality < -2 {
-                confidence = (confidence - 0.2).clamp(0.0, 1.0);
+            // Close readings are fused word by word, so a broken Hangul
+            // word and a misread number in one block both get fixed.
+            let preferred = if provenance == Provenance::Parser
+                || (provenance == Provenance::Fused && page_class != PageClass::Scanned)
+            {
+                Provenance::Parser
+            } else {
+                ocr_source
+            };
+            let token_fused = (sim >= resolve.token_fusion_similarity && !parser_is_oversized)
+                .then(|| {
+                    fuse_tokens(
+                        parser_lines,
+                        ocr_lines,
+                        *ocr_confidence,
+                        resolve.token_min_ocr_confidence,
+                        ocr_source,
+                        preferred,
+                    )
+                })
+                .flatten();
+            let (final_lines, provenance, rule) = match token_fused {
+                Some(lines) => (lines, Provenance::Fused, "token_fusion"),
+                None => (final_lines, provenance, rule),
+            };
+
+            if korean_present
+                && provenance == Provenance::Parser
+                && parser_quality < resolve.degraded_korean_quality
+            {
+                confidence = (confidence - resolve.degraded_korean_penalty).clamp(0.0, 1.0);
             }
             if korean_present && provenance == Provenance::Ocr && ocr_quality > 0 {
-                confidence = (confidence + 0.05).clamp(0.0, 1.0);
+                confidence = (confidence + resolve.korean_ocr_bonus).clamp(0.0, 1.0);
             }
             if page_class == PageClass::Scanned && provenance == Provenance::Ocr {
-                confidence = (confidence + 0.08).clamp(0.0, 1.0);
+                confidence = (confidence + resolve.scanned_ocr_bonus).clamp(0.0, 1.0);
             }
 
             let final_text = text_from_lines(&final_lines);
-            Block::TextBlock {
+            let block = Block::TextBlock {
                 bbox: *bbox,
                 lines: final_lines,
                 confidence,
                 source: provenance,
+                role: *role,
+                order: *order,
+                tracks: Vec::new(),
                 debug: Some(BlockDebug {
                     parser_text: a_text.clone(),
                     ocr_text: b_text.clone(),
                     final_text,
                     similarity,
                 }),
-            }
+            };
+            (block, rule)
+        }
+        (
+            Block::TableBlock {
+                bbox, cells, order, ..
+            },
+            Block::TableBlock { .. },
+        ) => {
+            let block = Block::TableBlock {
+                bbox: *bbox,
+                confidence,
+                source: Provenance::Fused,
+                cells: cells.clone(),
+                order: *order,
+                tracks: Vec::new(),
+                debug: None,
+            };
+            (block, "table_pair")
+        }
+        // An embedded image places the figure exactly; OCR only confirms it.
+        (
+            Block::FigureBlock {
+                bbox, image, order, ..
+            },
+            _,
+        ) if image.is_some() => {
+            let block = Block::FigureBlock {
+                bbox: *bbox,
+                confidence,
+                source: Provenance::Fused,
+                image: image.clone(),
+                order: *order,
+                tracks: Vec::new(),
+                debug: None,
+            };
+            (block, "embedded_figure")
+        }
+        (Block::FigureBlock { order, .. }, Block::FigureBlock { .. }) => {
+            let block = Block::FigureBlock {
+                bbox: pair.a.bbox().union(&pair.b.bbox()),
+                confidence,
+                source: Provenance::Fused,
+                image: None,
+                order: *order,
+                tracks: Vec::new(),
+                debug: None,
+            };
+            (block, "figure_pair")
+        }
+        // Blocks of different kinds keep the parser's block whole.
+        _ => {
+            let block = promote_single(pair.a.clone(), Provenance::Parser, page_class, policy);
+            (block, "mixed_kind_parser")
         }
-        _ => Block::FigureBlock {
-            bbox: pair.a.bbox().union(&pair.b.bbox()),
-            confidence,
-            source: Provenance::Fused,
-            debug: None,
-        },
+    }
+}
+
+/// The trace entry for `pair` resolved into `block` by `rule`.
+fn pair_decision(pair: &MatchedPair, rule: &str, block: &Block) -> FusionDecision {
+    let parser_text = pair.a.text_content();
+    let ocr_text = pair.b.text_content();
+    let similarity = match (&parser_text, &ocr_text) {
+        (Some(a), Some(b)) => Some(text_similarity(a, b)),
+        _ => None,
+    };
+    let korean = [&parser_text, &ocr_text]
+        .into_iter()
+        .flatten()
+        .any(|text| has_korean_chars(text));
+    let quality = |text: &Option<String>| {
+        korean.then(|| korean_text_quality(text.as_deref().unwrap_or_default()))
+    };
+    FusionDecision {
+        action: DecisionAction::Resolved,
+        rule: rule.to_string(),
+        bbox: block.bbox(),
+        provenance: block.provenance(),
+        parser_bbox: Some(pair.a.bbox()),
+        ocr_bbox: Some(pair.b.bbox()),
+        parser_quality: quality(&parser_text),
+        ocr_quality: quality(&ocr_text),
+        parser_text,
+        ocr_text,
+        similarity,
     }
 }
 
 fn text_from_lines(lines: &[Line]) -> Option<String> {
-    let text = lines
-        .iter()
-        .flat_map(|line| line.spans.iter())
-        .map(|span| span.text.clone())
-        .collect::<Vec<_>>()
-        .join(" ");
+    let text = lines.iter().map(Line::text).collect::<Vec<_>>().join(" ");
     if text.is_empty() {
         None
     } else {
@@ -334,7 +631,7 @@ fn text_from_lines(lines: &[Line]) -> Option<String> {
     }
 }
 
-fn has_korean_chars(text: &str) -> bool {
+pub(crate) fn has_korean_chars(text: &str) -> bool {
     text.chars().any(|c| {
         let code = c as u32;
         (0xAC00..=0xD7A3).contains(&code)
@@ -356,7 +653,7 @@ fn hanja_count(text: &str) -> usize {
         .count()
 }
 
-fn korean_text_quality(text: &str) -> i32 {
+pub(crate) fn korean_text_quality(text: &str) -> i32 {
     let mut syllables = 0_i32;
     let mut jamos = 0_i32;
     for c in text.chars() {
@@ -374,11 +671,31 @@ fn korean_text_quality(text: &str) -> i32 {
     (syllables * 2) - (jamos * 3)
 }
 
-fn is_korean_parser_degraded(text: &str) -> bool {
-    has_korean_chars(text) && korean_text_quality(text) < -2
+fn is_korean_parser_degraded(text: &str, policy: &ResolvePolicy) -> bool {
+    has_korean_chars(text) && korean_text_quality(text) < policy.degraded_korean_quality
 }
 
-fn filter_degraded_parser_blocks(blocks: Vec<Block>) -> Vec<Block> {
+/// Keep the blocks `keep` accepts; the rest are traced as dropped by `rule`.
+fn retain_traced(
+    blocks: Vec<Block>,
+    rule: &str,
+    trace: &mut Vec<FusionDecision>,
+    keep: impl Fn(&Block) -> bool,
+) -> Vec<Block> {
+    let (kept, dropped): (Vec<Block>, Vec<Block>) = blocks.into_iter().partition(|b| keep(b));
+    trace.extend(
+        dropped
+            .iter()
+            .map(|block| FusionDecision::about(DecisionAction::Dropped, rule, block)),
+    );
+    kept
+}
+
+fn filter_degraded_parser_blocks(
+    blocks: Vec<Block>,
+    policy: &FilterPolicy,
+    trace: &mut Vec<FusionDecision>,
+) -> Vec<Block> {
     let ocr_texts: Vec<String> = blocks
         .iter()
         .filter_map(|block| match block {
@@ -400,9 +717,11 @@ fn filter_degraded_parser_blocks(blocks: Vec<Block>) -> Vec<Block> {
         .max()
         .unwrap_or(i32::MIN);
 
-    blocks
-        .into_iter()
-        .filter(|block| match block {
+    retain_traced(
+        blocks,
+        "filter_degraded_parser_blocks",
+        trace,
+        |block| match block {
             Block::TextBlock {
                 source: Provenance::Parser,
                 ..
@@ -413,15 +732,19 @@ fn filter_degraded_parser_blocks(blocks: Vec<Block>) -> Vec<Block> {
                 // If parser Korean is heavily decomposed while OCR text exists,
                 // suppress parser block to avoid duplicated, corrupted output.
                 !(has_korean_chars(&text)
-                    && parser_quality < -10
+                    && parser_quality < policy.broken_korean_quality
                     && best_ocr_quality > parser_quality)
             }
             _ => true,
-        })
-        .collect()
+        },
+    )
 }
 
-fn filter_redundant_ocr_text_blocks(blocks: Vec<Block>) -> Vec<Block> {
+fn filter_redundant_ocr_text_blocks(
+    blocks: Vec<Block>,
+    policy: &FusionPolicy,
+    trace: &mut Vec<FusionDecision>,
+) -> Vec<Block> {
     let parser_texts_with_area: Vec<(String, f32)> = blocks
         .iter()
         .filter_map(|block| match block {
@@ -447,16 +770,18 @@ fn filter_redundant_ocr_text_blocks(blocks: Vec<Block>) -> Vec<Block> {
         .map(|(t, _)| korean_text_quality(t))
         .max()
         .unwrap_or(i32::MIN);
-    let parser_max_area = parser_texts_with_area
+    let parser_total_area = parser_texts_with_area
         .iter()
         .map(|(_, area)| *area)
-        .fold(0.0_f32, f32::max);
+        .sum::<f32>();
 
     // Parser-dominant digital page: remove noisy duplicated OCR text fragments.
     // Thresholds are intentionally relaxed so pages like "mixed text + short sections"
-    // still drop OCR duplicates when parser provides a broad, readable text block.
-    let parser_dominant =
-        parser_total_len >= 120 && parser_quality >= -2 && parser_max_area >= 300_000.0;
+    // still drop OCR duplicates when parser text covers a broad, readable region.
+    // Area is summed because word-level layout splits the page into many blocks.
+    let parser_dominant = parser_total_len >= policy.filter.parser_dominant_min_chars
+        && parser_quality >= policy.resolve.degraded_korean_quality
+        && parser_total_area >= policy.filter.parser_dominant_min_area;
     if !parser_dominant {
         return blocks;
     }
@@ -473,29 +798,35 @@ fn filter_redundant_ocr_text_blocks(blocks: Vec<Block>) -> Vec<Block> {
         })
         .collect();
 
-    blocks
-        .into_iter()
-        .filter(|block| match block {
+    retain_traced(
+        blocks,
+        "filter_redundant_ocr_text_blocks",
+        trace,
+        |block| match block {
             Block::TextBlock {
                 source: Provenance::Ocr,
                 bbox,
                 ..
             } => {
                 let ocr_text = block.text_content().unwrap_or_default();
-                !is_duplicate_ocr_text_block(&ocr_text, bbox, &parser_text_blocks)
+                !is_duplicate_ocr_text_block(&ocr_text, bbox, &parser_text_blocks, &policy.filter)
             }
             _ => true,
-        })
-        .collect()
+        },
+    )
 }
 
 fn filter_low_quality_ocr_text_blocks(
     blocks: Vec<Block>,
     aggressive_short_filter: bool,
+    policy: &FilterPolicy,
+    trace: &mut Vec<FusionDecision>,
 ) -> Vec<Block> {
-    blocks
-        .into_iter()
-        .filter(|block| match block {
+    retain_traced(
+        blocks,
+        "filter_low_quality_ocr_text_blocks",
+        trace,
+        |block| match block {
             Block::TextBlock {
                 source: Provenance::Ocr,
                 ..
@@ -504,18 +835,23 @@ fn filter_low_quality_ocr_text_blocks(
                 if text.trim().is_empty() {
                     return false;
                 }
-                if is_noisy_ocr_text(&text) {
+                if is_noisy_ocr_text(&text, policy) {
                     return false;
                 }
                 let compact_len = normalize_text_for_compare(&text).chars().count();
-                !(aggressive_short_filter && compact_len <= 3)
+                !(aggressive_short_filter && compact_len <= policy.short_ocr_chars)
             }
             _ => true,
-        })
-        .collect()
+        },
+    )
 }
 
-fn filter_korean_ocr_when_parser_reliable(blocks: Vec<Block>, strict: bool) -> Vec<Block> {
+fn filter_korean_ocr_when_parser_reliable(
+    blocks: Vec<Block>,
+    strict: bool,
+    policy: &FilterPolicy,
+    trace: &mut Vec<FusionDecision>,
+) -> Vec<Block> {
     let parser_texts: Vec<(String, crate::core::geometry::BBox)> = blocks
         .iter()
         .filter_map(|block| match block {
@@ -542,14 +878,17 @@ fn filter_korean_ocr_when_parser_reliable(blocks: Vec<Block>, strict: bool) -> V
         .max()
         .unwrap_or(i32::MIN);
 
-    let parser_reliable = parser_korean_chars >= 18 && parser_best_quality >= -1;
+    let parser_reliable = parser_korean_chars >= policy.reliable_korean_syllables
+        && parser_best_quality >= policy.reliable_korean_min_quality;
     if !parser_reliable {
         return blocks;
     }
 
-    blocks
-        .into_iter()
-        .filter(|block| match block {
+    retain_traced(
+        blocks,
+        "filter_korean_ocr_when_parser_reliable",
+        trace,
+        |block| match block {
             Block::TextBlock {
                 source: Provenance::Ocr,
                 ..
@@ -563,11 +902,11 @@ fn filter_korean_ocr_when_parser_reliable(blocks: Vec<Block>, strict: bool) -> V
                 false
             }
             _ => true,
-        })
-        .collect()
+        },
+    )
 }
 
-fn parser_reliable_for_accuracy(blocks: &[Block]) -> bool {
+fn parser_reliable_for_accuracy(blocks: &[Block], policy: &FusionPolicy) -> bool {
     let parser_texts: Vec<String> = blocks
         .iter()
         .filter_map(|block| match block {
@@ -621,30 +960,40 @@ fn parser_reliable_for_accuracy(blocks: &[Block]) -> bool {
     // If parser has only 1 large block but OCR has many small blocks (10+),
     // it's likely a multi-column layout where parser's reading order is wrong.
     // Prefer OCR in this case.
+    // Tagged pages state their reading order; there is no column guess.
     if parser_text_blocks == 1
-        && ocr_text_blocks >= 6
-        && detect_two_column_divider(blocks).is_some()
+        && ocr_text_blocks >= policy.filter.column_min_ocr_blocks
+        && !blocks.iter().any(|block| block.order().is_some())
+        && detect_two_column_divider(blocks, &policy.resolve).is_some()
     {
         // Keep parser for Korean-heavy pages where parser text quality is strong.
-        if parser_korean_quality < 4 {
+        if parser_korean_quality < policy.resolve.reliable_korean_quality {
             return false;
         }
     }
 
-    parser_chars >= 220
+    parser_chars >= policy.filter.reliable_parser_chars
         && parser_text_blocks >= 1
-        && (ocr_chars == 0 || parser_chars.saturating_mul(10) >= ocr_chars.saturating_mul(7))
-        && parser_korean_quality >= -2
+        && (ocr_chars == 0
+            || parser_chars as f64 >= ocr_chars as f64 * policy.filter.reliable_parser_share as f64)
+        && parser_korean_quality >= policy.resolve.degraded_korean_quality
 }
 
-fn filter_ocr_text_when_parser_reliable(blocks: Vec<Block>, page_class: PageClass) -> Vec<Block> {
-    if page_class == PageClass::Scanned || !parser_reliable_for_accuracy(&blocks) {
+fn filter_ocr_text_when_parser_reliable(
+    blocks: Vec<Block>,
+    page_class: PageClass,
+    policy: &FusionPolicy,
+    trace: &mut Vec<FusionDecision>,
+) -> Vec<Block> {
+    if page_class == PageClass::Scanned || !parser_reliable_for_accuracy(&blocks, policy) {
         return blocks;
     }
 
-    blocks
-        .into_iter()
-        .filter(|block| {
+    retain_traced(
+        blocks,
+        "filter_ocr_text_when_parser_reliable",
+        trace,
+        |block| {
             !matches!(
                 block,
                 Block::TextBlock {
@@ -652,11 +1001,11 @@ fn filter_ocr_text_when_parser_reliable(blocks: Vec<Block>, page_class: PageClas
                     ..
                 }
             )
-        })
-        .collect()
+        },
+    )
 }
 
-fn is_noisy_ocr_text(text: &str) -> bool {
+fn is_noisy_ocr_text(text: &str, policy: &FilterPolicy) -> bool {
     let norm = normalize_text_for_compare(text);
     if norm.chars().count() <= 1 {
         return true;
@@ -671,26 +1020,29 @@ fn is_noisy_ocr_text(text: &str) -> bool {
     }
 
     let unique = norm.chars().collect::<std::collections::HashSet<_>>().len();
-    if norm.chars().count() >= 6 && unique <= 2 {
+    if norm.chars().count() >= policy.noisy_repeat_min_chars
+        && unique <= policy.noisy_max_distinct_chars
+    {
         return true;
     }
 
     let hangul_present = has_korean_chars(&norm);
     let hanja = hanja_count(&norm);
-    if hangul_present && hanja >= 2 {
+    if hangul_present && hanja >= policy.noisy_hanja {
         return true;
     }
 
-    has_korean_chars(&norm) && korean_text_quality(&norm) < -6
+    has_korean_chars(&norm) && korean_text_quality(&norm) < policy.noisy_korean_quality
 }
 
 fn is_duplicate_ocr_text_block(
     ocr_text: &str,
     ocr_bbox: &crate::core::geometry::BBox,
     parser_text_blocks: &[(String, crate::core::geometry::BBox)],
+    policy: &FilterPolicy,
 ) -> bool {
     let ocr_norm = normalize_text_for_compare(ocr_text);
-    if ocr_norm.len() < 4 {
+    if ocr_norm.len() < policy.duplicate_min_chars {
         return true;
     }
 
@@ -701,12 +1053,14 @@ fn is_duplicate_ocr_text_block(
         }
 
         // Strong textual containment means OCR is likely just a duplicate snippet.
-        if ocr_norm.len() >= 8 && parser_norm.contains(&ocr_norm) {
+        if ocr_norm.len() >= policy.duplicate_contained_chars && parser_norm.contains(&ocr_norm) {
             return true;
         }
 
         let similarity = text_similarity(&parser_norm, &ocr_norm);
-        similarity >= 0.82 || (ocr_bbox.iou(parser_bbox) >= 0.55 && similarity >= 0.55)
+        similarity >= policy.duplicate_similarity
+            || (ocr_bbox.iou(parser_bbox) >= policy.duplicate_overlap_iou
+                && similarity >= policy.duplicate_overlap_similarity)
     })
 }
 
@@ -717,12 +1071,23 @@ fn normalize_text_for_compare(text: &str) -> String {
         .to_lowercase()
 }
 
-fn sort_blocks_in_reading_order(mut blocks: Vec<Block>) -> Vec<Block> {
+fn sort_blocks_in_reading_order(mut blocks: Vec<Block>, policy: &ResolvePolicy) -> Vec<Block> {
+    // Blocks from a structure tree keep its order; anything without one
+    // (OCR-only regions) follows, top to bottom.
+    if blocks.iter().any(|block| block.order().is_some()) {
+        blocks.sort_by(|a, b| match (a.order(), b.order()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => block_yx_order(a, b),
+        });
+        return blocks;
+    }
     if blocks.len() <= 2 {
         return blocks;
     }
 
-    let maybe_divider = detect_two_column_divider(&blocks);
+    let maybe_divider = detect_two_column_divider(&blocks, policy);
     if let Some(divider_x) = maybe_divider {
         let mut left = Vec::new();
         let mut right = Vec::new();
@@ -742,9 +1107,9 @@ fn sort_blocks_in_reading_order(mut blocks: Vec<Block>) -> Vec<Block> {
             }
         }
 
-        left.sort_by(|a, b| block_yx_order(a, b));
-        right.sort_by(|a, b| block_yx_order(a, b));
-        spanning.sort_by(|a, b| block_yx_order(a, b));
+        left.sort_by(block_yx_order);
+        right.sort_by(block_yx_order);
+        spanning.sort_by(block_yx_order);
 
         let min_column_y = left
             .iter()
@@ -762,9 +1127,9 @@ fn sort_blocks_in_reading_order(mut blocks: Vec<Block>) -> Vec<Block> {
         let mut bottom_spanning = Vec::new();
         for block in spanning {
             let bbox = block.bbox();
-            if bbox.y1 <= min_column_y + 50.0 {
+            if bbox.y1 <= min_column_y + policy.column_top_margin {
                 top_spanning.push(block);
-            } else if bbox.y0 >= max_column_y - 30.0 {
+            } else if bbox.y0 >= max_column_y - policy.column_bottom_margin {
                 bottom_spanning.push(block);
             } else {
                 mid_spanning.push(block);
@@ -779,7 +1144,7 @@ fn sort_blocks_in_reading_order(mut blocks: Vec<Block>) -> Vec<Block> {
         out.extend(bottom_spanning);
         out
     } else {
-        blocks.sort_by(|a, b| block_yx_order(a, b));
+        blocks.sort_by(block_yx_order);
         blocks
     }
 }
@@ -797,15 +1162,15 @@ fn block_yx_order(a: &Block, b: &Block) -> std::cmp::Ordering {
         })
 }
 
-fn detect_two_column_divider(blocks: &[Block]) -> Option<f32> {
-    if blocks.len() < 6 {
+fn detect_two_column_divider(blocks: &[Block], policy: &ResolvePolicy) -> Option<f32> {
+    if blocks.len() < policy.column_min_blocks {
         return None;
     }
 
     let min_x = blocks.iter().map(|b| b.bbox().x0).fold(f32::MAX, f32::min);
     let max_x = blocks.iter().map(|b| b.bbox().x1).fold(0.0_f32, f32::max);
     let page_width = (max_x - min_x).max(1.0);
-    if page_width < 300.0 {
+    if page_width < policy.column_min_page_width {
         return None;
     }
 
@@ -819,7 +1184,7 @@ fn detect_two_column_divider(blocks: &[Block]) -> Option<f32> {
         let cx = (bbox.x0 + bbox.x1) * 0.5;
         let spans_divider = bbox.x0 < divider && bbox.x1 > divider;
         let width_ratio = bbox.width() / page_width;
-        if spans_divider && width_ratio > 0.58 {
+        if spans_divider && width_ratio > policy.column_spanning_width {
             spanning_count += 1;
             continue;
         }
@@ -830,9 +1195,12 @@ fn detect_two_column_divider(blocks: &[Block]) -> Option<f32> {
         }
     }
 
-    if left_count >= 2 && right_count >= 2 && left_count + right_count >= 5 {
+    if left_count >= policy.column_min_per_side
+        && right_count >= policy.column_min_per_side
+        && left_count + right_count >= policy.column_min_side_blocks
+    {
         let spanning_ratio = spanning_count as f32 / (blocks.len() as f32);
-        if spanning_ratio <= 0.45 {
+        if spanning_ratio <= policy.column_max_spanning_share {
             return Some(divider);
         }
     }
@@ -844,7 +1212,18 @@ fn detect_two_column_divider(blocks: &[Block]) -> Option<f32> {
 mod tests {
     use super::*;
     use crate::core::geometry::BBox;
-    use crate::core::model::{Line, Span};
+    use crate::core::model::{Line, PageHypothesis, Span};
+    use crate::fusion::align::align_tracks;
+    use crate::fusion::DEFAULT_DPI;
+
+    fn page_of(blocks: Vec<Block>) -> PageHypothesis {
+        PageHypothesis {
+            page_idx: 0,
+            blocks,
+            width: 1000,
+            height: 1000,
+        }
+    }
 
     fn text_block(text: &str, source: Provenance, bbox: BBox) -> Block {
         Block::TextBlock {
@@ -855,10 +1234,15 @@ mod tests {
                     bbox,
                     source,
                     style: None,
+                    link: None,
+                    revision: None,
                 }],
             }],
             confidence: 0.5,
             source,
+            role: None,
+            order: None,
+            tracks: Vec::new(),
             debug: None,
         }
     }
@@ -878,7 +1262,11 @@ mod tests {
             BBox::new(50.0, 60.0, 300.0, 120.0),
         );
 
-        let filtered = filter_redundant_ocr_text_blocks(vec![parser, ocr_dup]);
+        let filtered = filter_redundant_ocr_text_blocks(
+            vec![parser, ocr_dup],
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
 
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].provenance(), Provenance::Parser);
@@ -899,7 +1287,11 @@ mod tests {
             BBox::new(50.0, 720.0, 320.0, 760.0),
         );
 
-        let filtered = filter_redundant_ocr_text_blocks(vec![parser, ocr_unique]);
+        let filtered = filter_redundant_ocr_text_blocks(
+            vec![parser, ocr_unique],
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
 
         assert_eq!(filtered.len(), 2);
         assert!(filtered
@@ -920,7 +1312,12 @@ mod tests {
             BBox::new(10.0, 320.0, 180.0, 350.0),
         );
 
-        let filtered = filter_low_quality_ocr_text_blocks(vec![parser, ocr_noise], false);
+        let filtered = filter_low_quality_ocr_text_blocks(
+            vec![parser, ocr_noise],
+            false,
+            &FilterPolicy::default(),
+            &mut Vec::new(),
+        );
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].provenance(), Provenance::Parser);
     }
@@ -938,7 +1335,12 @@ mod tests {
             BBox::new(10.0, 320.0, 280.0, 360.0),
         );
 
-        let filtered = filter_low_quality_ocr_text_blocks(vec![parser, ocr_noise], false);
+        let filtered = filter_low_quality_ocr_text_blocks(
+            vec![parser, ocr_noise],
+            false,
+            &FilterPolicy::default(),
+            &mut Vec::new(),
+        );
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].provenance(), Provenance::Parser);
     }
@@ -961,8 +1363,12 @@ mod tests {
             BBox::new(20.0, 520.0, 260.0, 560.0),
         );
 
-        let filtered =
-            filter_korean_ocr_when_parser_reliable(vec![parser, ocr_korean, ocr_english], true);
+        let filtered = filter_korean_ocr_when_parser_reliable(
+            vec![parser, ocr_korean, ocr_english],
+            true,
+            &FilterPolicy::default(),
+            &mut Vec::new(),
+        );
         assert_eq!(filtered.len(), 2);
         assert!(filtered.iter().any(|b| matches!(
             b,
@@ -998,9 +1404,250 @@ mod tests {
             BBox::new(40.0, 710.0, 420.0, 760.0),
         );
 
-        let filtered =
-            filter_ocr_text_when_parser_reliable(vec![parser, ocr_noise], PageClass::Hybrid);
+        let filtered = filter_ocr_text_when_parser_reliable(
+            vec![parser, ocr_noise],
+            PageClass::Hybrid,
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].provenance(), Provenance::Parser);
     }
+    #[test]
+    fn embedded_image_figure_survives_ocr_match() {
+        let figure = Block::FigureBlock {
+            bbox: BBox::new(100.0, 100.0, 900.0, 1000.0),
+            confidence: 0.6,
+            source: Provenance::Parser,
+            image: Some("figures/page_001_image_01.jpg".to_string()),
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        let ocr = Block::FigureBlock {
+            bbox: BBox::new(90.0, 80.0, 910.0, 1020.0),
+            confidence: 0.5,
+            source: Provenance::Ocr,
+            image: None,
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        let (parser, ocr) = (page_of(vec![figure]), page_of(vec![ocr]));
+        let tracks = [Track::parser(&parser), Track::ocr(&ocr)];
+        let blocks = resolve_clusters(
+            &align_tracks(&[&parser.blocks, &ocr.blocks], DEFAULT_DPI),
+            &tracks,
+            PageClass::Digital,
+            BBox::new(0.0, 0.0, 1000.0, 1100.0),
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
+        assert_eq!(blocks.len(), 1);
+        match &blocks[0] {
+            Block::FigureBlock { bbox, image, .. } => {
+                assert_eq!(*bbox, BBox::new(100.0, 100.0, 900.0, 1000.0));
+                assert_eq!(image.as_deref(), Some("figures/page_001_image_01.jpg"));
+            }
+            other => panic!("expected a figure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parser_text_survives_an_overlapping_ocr_figure() {
+        let text = text_block(
+            "Figure 1 shows the layout",
+            Provenance::Parser,
+            BBox::new(100.0, 100.0, 900.0, 160.0),
+        );
+        let figure = Block::FigureBlock {
+            bbox: BBox::new(90.0, 80.0, 910.0, 400.0),
+            confidence: 0.5,
+            source: Provenance::Ocr,
+            image: None,
+            order: None,
+            tracks: Vec::new(),
+            debug: None,
+        };
+        let policy = FusionPolicy::default().at_dpi(DEFAULT_DPI);
+        let page = BBox::new(0.0, 0.0, 1000.0, 1000.0);
+
+        let (parser, ocr) = (page_of(vec![text.clone()]), page_of(vec![figure.clone()]));
+        let tracks = [Track::parser(&parser), Track::ocr(&ocr)];
+        let blocks = resolve_clusters(
+            &align_tracks(&[&parser.blocks, &ocr.blocks], DEFAULT_DPI),
+            &tracks,
+            PageClass::Digital,
+            page,
+            &policy,
+            &mut Vec::new(),
+        );
+        assert!(blocks
+            .iter()
+            .any(|block| block.text_content().as_deref() == Some("Figure 1 shows the layout")));
+
+        // Should such a pair reach resolution, the parser text is kept.
+        let pair = MatchedPair {
+            iou: text.bbox().iou(&figure.bbox()),
+            center_distance: text.bbox().center_distance(&figure.bbox()),
+            a: text,
+            b: figure,
+        };
+        let (block, rule) = resolve_pair(&pair, PageClass::Digital, page, &policy);
+        assert_eq!(rule, "mixed_kind_parser");
+        assert_eq!(
+            block.text_content().as_deref(),
+            Some("Figure 1 shows the layout")
+        );
+    }
+
+    #[test]
+    fn structure_order_overrides_column_guess() {
+        // Two columns of three blocks; the tags read across rows instead.
+        let mut blocks = Vec::new();
+        for row in 0..3 {
+            for col in 0..2 {
+                let x0 = 50.0 + col as f32 * 300.0;
+                let y0 = 100.0 + row as f32 * 100.0;
+                let mut block = text_block(
+                    &format!("block {row}{col}"),
+                    Provenance::Parser,
+                    BBox::new(x0, y0, x0 + 250.0, y0 + 60.0),
+                );
+                if let Block::TextBlock { order, .. } = &mut block {
+                    *order = Some(row * 2 + col);
+                }
+                blocks.push(block);
+            }
+        }
+        let policy = FusionPolicy::default().at_dpi(DEFAULT_DPI).resolve;
+        assert!(detect_two_column_divider(&blocks, &policy).is_some());
+
+        blocks.reverse();
+        let sorted = sort_blocks_in_reading_order(blocks, &policy);
+        let orders: Vec<Option<usize>> = sorted.iter().map(Block::order).collect();
+        assert_eq!(orders, (0..6).map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn records_agreeing_tracks_and_their_trust() {
+        let bbox = BBox::new(50.0, 100.0, 600.0, 140.0);
+        let parser = page_of(vec![text_block(
+            "Annual results for the fiscal year 2024",
+            Provenance::Parser,
+            bbox,
+        )]);
+        let ocr = page_of(vec![text_block(
+            "Annual resu1ts for the fiscal year 2024",
+            Provenance::Ocr,
+            bbox,
+        )]);
+        let second = page_of(vec![text_block(
+            "Annual resu1ts for the fiscal year 2024",
+            Provenance::Ocr,
+            bbox,
+        )]);
+        let stray = page_of(vec![text_block(
+            "Appendix",
+            Provenance::Ocr,
+            BBox::new(50.0, 800.0, 300.0, 840.0),
+        )]);
+        let page = BBox::new(0.0, 0.0, 1000.0, 1000.0);
+
+        let pair = [Track::parser(&parser), Track::ocr(&ocr)];
+        let both = resolve_clusters(
+            &align_tracks(&[&parser.blocks, &ocr.blocks], DEFAULT_DPI),
+            &pair,
+            PageClass::Digital,
+            page,
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
+        let three = [
+            Track::parser(&parser),
+            Track::ocr(&ocr),
+            Track::new("ocr_alt", Provenance::Ocr, &second).with_trust(0.8),
+        ];
+        let all = resolve_clusters(
+            &align_tracks(&[&parser.blocks, &ocr.blocks, &second.blocks], DEFAULT_DPI),
+            &three,
+            PageClass::Digital,
+            page,
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
+        assert_eq!(all[0].tracks(), ["parser", "ocr", "ocr_alt"]);
+        assert!(all[0].confidence() > both[0].confidence());
+
+        let alone = [Track::ocr(&stray).with_trust(0.5)];
+        let single = resolve_clusters(
+            &align_tracks(&[&stray.blocks], DEFAULT_DPI),
+            &alone,
+            PageClass::Scanned,
+            page,
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
+        let full = resolve_clusters(
+            &align_tracks(&[&stray.blocks], DEFAULT_DPI),
+            &[Track::ocr(&stray)],
+            PageClass::Scanned,
+            page,
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut Vec::new(),
+        );
+        assert!((single[0].confidence() * 2.0 - full[0].confidence()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn traces_resolution_and_dropped_blocks() {
+        let bbox = BBox::new(50.0, 100.0, 600.0, 140.0);
+        let parser = page_of(vec![text_block(
+            "Annual results for the fiscal year 2024",
+            Provenance::Parser,
+            bbox,
+        )]);
+        let ocr = page_of(vec![
+            text_block(
+                "Annual results for the fiscal year 2024",
+                Provenance::Ocr,
+                bbox,
+            ),
+            text_block(
+                "ㅁ ㅁ ㅁ ㅁ ㅁ",
+                Provenance::Ocr,
+                BBox::new(50.0, 800.0, 300.0, 840.0),
+            ),
+        ]);
+        let tracks = [Track::parser(&parser), Track::ocr(&ocr)];
+
+        let mut trace = Vec::new();
+        let blocks = resolve_clusters(
+            &align_tracks(&[&parser.blocks, &ocr.blocks], DEFAULT_DPI),
+            &tracks,
+            PageClass::Digital,
+            BBox::new(0.0, 0.0, 1000.0, 1000.0),
+            &FusionPolicy::default().at_dpi(DEFAULT_DPI),
+            &mut trace,
+        );
+
+        assert_eq!(blocks.len(), 1);
+        let steps: Vec<(DecisionAction, &str)> = trace
+            .iter()
+            .map(|decision| (decision.action, decision.rule.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (DecisionAction::Resolved, "similar_text"),
+                (DecisionAction::Promoted, "ocr_only"),
+                (
+                    DecisionAction::Dropped,
+                    "filter_low_quality_ocr_text_blocks"
+                ),
+            ]
+        );
+        assert_eq!(trace[0].similarity, Some(1.0));
+        assert_eq!(trace[2].ocr_text.as_deref(), Some("ㅁ ㅁ ㅁ ㅁ ㅁ"));
+    }
 }
@@ -0,0 +1,325 @@
+//! Token-level fusion of a parser reading and an OCR reading of one block.
+//!
+//! Both texts are split into whitespace tokens and aligned with an edit
+//! script (insertions, deletions and substitutions weighted by how alike two
+//! tokens are). Where the readings disagree on a token, the better one is
+//! picked on its own evidence: decomposed or garbled Hangul, replacement
+//! characters and mismatched digits. The result keeps the parser's lines and
+//! boxes, and every span says which track its token came from.
+
+use strsim::normalized_levenshtein;
+
+use crate::core::model::{Line, Provenance, Span};
+use crate::fusion::resolve::{has_korean_chars, korean_text_quality};
+
+/// Token pairs beyond this many are not aligned; the block is resolved
+/// wholesale instead.
+const MAX_ALIGNMENT_CELLS: usize = 250_000;
+
+/// Parser and OCR lines fused token by token, or `None` when the token
+/// alignment keeps every token from `preferred`, the side wholesale
+/// resolution chose. Tokens both readings agree on become
+/// [`Provenance::Fused`] spans; disagreements go to `preferred` unless the
+/// other token is clearly better. Only an OCR block at least
+/// `min_ocr_confidence` confident can override parser tokens or add tokens
+/// of its own; parser-only tokens are always kept.
+pub fn fuse_tokens(
+    parser_lines: &[Line],
+    ocr_lines: &[Line],
+    ocr_confidence: f32,
+    min_ocr_confidence: f32,
+    ocr_source: Provenance,
+    preferred: Provenance,
+) -> Option<Vec<Line>> {
+    let parser = parser_tokens(parser_lines);
+    let ocr: Vec<&str> = ocr_lines
+        .iter()
+        .flat_map(|line| &line.spans)
+        .flat_map(|span| span.text.split_whitespace())
+        .collect();
+    if parser.is_empty()
+        || ocr.is_empty()
+        || parser.len().saturating_mul(ocr.len()) > MAX_ALIGNMENT_CELLS
+    {
+        return None;
+    }
+
+    let confident = ocr_confidence >= min_ocr_confidence;
+    let mut lines: Vec<Vec<Span>> = vec![Vec::new(); parser_lines.len()];
+    let mut overridden = false;
+    let mut last: Option<(usize, &Span)> = None;
+    for step in edit_script(&parser, &ocr) {
+        match step {
+            Step::Same(p, _) => {
+                let token = &parser[p];
+                lines[token.line].push(token.span_with(token.text, Provenance::Fused));
+                last = Some((token.line, token.span));
+            }
+            Step::Replace(p, o) => {
+                let token = &parser[p];
+                let ocr_wins = match better_token(token.text, ocr[o], confident) {
+                    Some(winner) => winner != Provenance::Parser,
+                    None => preferred != Provenance::Parser,
+                };
+                overridden |= ocr_wins == (preferred == Provenance::Parser);
+                let span = if ocr_wins {
+                    token.span_with(ocr[o], ocr_source)
+                } else {
+                    token.span_with(token.text, Provenance::Parser)
+                };
+                lines[token.line].push(span);
+                last = Some((token.line, token.span));
+            }
+            Step::ParserOnly(p) => {
+                let token = &parser[p];
+                overridden |= preferred != Provenance::Parser;
+                lines[token.line].push(token.span_with(token.text, Provenance::Parser));
+                last = Some((token.line, token.span));
+            }
+            Step::OcrOnly(o) => {
+                if !confident || !ocr[o].chars().any(char::is_alphanumeric) {
+                    continue;
+                }
+                // Placed after the previous token, in its box for want of
+                // a better one.
+                let (line, span) = last.unwrap_or((parser[0].line, parser[0].span));
+                overridden |= preferred == Provenance::Parser;
+                let mut inserted = span.clone();
+                inserted.text = ocr[o].to_string();
+                inserted.source = ocr_source;
+                inserted.link = None;
+                lines[line].push(inserted);
+            }
+        }
+    }
+
+    if !overridden {
+        return None;
+    }
+    Some(
+        lines
+            .into_iter()
+            .filter(|spans| !spans.is_empty())
+            .map(|mut spans| {
+                let count = spans.len();
+                for span in &mut spans[..count - 1] {
+                    span.text.push(' ');
+                }
+                Line { spans }
+            })
+            .collect(),
+    )
+}
+
+/// The track whose token should stand when the readings differ, if the
+/// tokens themselves say so.
+fn better_token(parser: &str, ocr: &str, confident: bool) -> Option<Provenance> {
+    let garbled = |text: &str| text.contains('\u{FFFD}');
+    if garbled(parser) != garbled(ocr) {
+        return match garbled(parser) {
+            true if confident => Some(Provenance::Ocr),
+            true => None,
+            false => Some(Provenance::Parser),
+        };
+    }
+
+    // Broken Hangul: decomposed jamo score below whole syllables.
+    if has_korean_chars(parser) || has_korean_chars(ocr) {
+        let (parser_quality, ocr_quality) = (korean_text_quality(parser), korean_text_quality(ocr));
+        if ocr_quality > parser_quality && confident {
+            return Some(Provenance::Ocr);
+        }
+        if parser_quality > ocr_quality {
+            return Some(Provenance::Parser);
+        }
+    }
+
+    // The content stream holds the digits as typeset; OCR misreads them.
+    let digits = |text: &str| -> String { text.chars().filter(char::is_ascii_digit).collect() };
+    let (parser_digits, ocr_digits) = (digits(parser), digits(ocr));
+    if !parser_digits.is_empty() && parser_digits != ocr_digits {
+        return Some(Provenance::Parser);
+    }
+    None
+}
+
+struct ParserToken<'a> {
+    text: &'a str,
+    span: &'a Span,
+    line: usize,
+}
+
+impl ParserToken<'_> {
+    /// A span for `text` in this token's place.
+    fn span_with(&self, text: &str, source: Provenance) -> Span {
+        Span {
+            text: text.to_string(),
+            bbox: self.span.bbox,
+            source,
+            style: self.span.style.clone(),
+            link: self.span.link.clone(),
+            revision: self.span.revision.clone(),
+        }
+    }
+}
+
+fn parser_tokens(lines: &[Line]) -> Vec<ParserToken<'_>> {
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(line, l)| {
+            l.spans.iter().flat_map(move |span| {
+                span.text
+                    .split_whitespace()
+                    .map(move |text| ParserToken { text, span, line })
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Same(usize, usize),
+    Replace(usize, usize),
+    ParserOnly(usize),
+    OcrOnly(usize),
+}
+
+/// Minimum-cost edit script turning the parser tokens into the OCR tokens.
+/// Inserting or deleting a token costs 1; replacing one costs how unlike
+/// the two tokens are, so near-identical tokens pair up and unrelated ones
+/// are inserted and deleted instead.
+fn edit_script(parser: &[ParserToken], ocr: &[&str]) -> Vec<Step> {
+    let (n, m) = (parser.len(), ocr.len());
+    let replace_cost = |p: usize, o: usize| -> f64 {
+        if parser[p].text == ocr[o] {
+            0.0
+        } else {
+            // Never cheaper than deleting and inserting when unrelated.
+            2.0 * (1.0 - normalized_levenshtein(parser[p].text, ocr[o]))
+        }
+    };
+
+    let mut cost = vec![vec![0.0_f64; m + 1]; n + 1];
+    for (p, row) in cost.iter_mut().enumerate() {
+        row[0] = p as f64;
+    }
+    for (o, cell) in cost[0].iter_mut().enumerate() {
+        *cell = o as f64;
+    }
+    for p in 1..=n {
+        for o in 1..=m {
+            let replace = cost[p - 1][o - 1] + replace_cost(p - 1, o - 1);
+            let delete = cost[p - 1][o] + 1.0;
+            let insert = cost[p][o - 1] + 1.0;
+            cost[p][o] = replace.min(delete).min(insert);
+        }
+    }
+
+    let mut steps = Vec::with_capacity(n.max(m));
+    let (mut p, mut o) = (n, m);
+    while p > 0 || o > 0 {
+        if p > 0 && o > 0 {
+            let replace = replace_cost(p - 1, o - 1);
+            if (cost[p][o] - (cost[p - 1][o - 1] + replace)).abs() < 1e-9 {
+                steps.push(if replace == 0.0 {
+                    Step::Same(p - 1, o - 1)
+                } else {
+                    Step::Replace(p - 1, o - 1)
+                });
+                p -= 1;
+                o -= 1;
+                continue;
+            }
+        }
+        if p > 0 && (o == 0 || (cost[p][o] - (cost[p - 1][o] + 1.0)).abs() < 1e-9) {
+            steps.push(Step::ParserOnly(p - 1));
+            p -= 1;
+        } else {
+            steps.push(Step::OcrOnly(o - 1));
+            o -= 1;
+        }
+    }
+    steps.reverse();
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::geometry::BBox;
+
+    fn parser_line(words: &[&str], y: f32) -> Line {
+        let count = words.len();
+        Line {
+            spans: words
+                .iter()
+                .enumerate()
+                .map(|(idx, word)| Span {
+                    text: if idx + 1 < count {
+                        format!("{word} ")
+                    } else {
+                        word.to_string()
+                    },
+                    bbox: BBox::new(idx as f32 * 50.0, y, idx as f32 * 50.0 + 40.0, y + 10.0),
+                    source: Provenance::Parser,
+                    style: None,
+                    link: None,
+                    revision: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn ocr_line(text: &str) -> Line {
+        Line {
+            spans: vec![Span {
+                text: text.to_string(),
+                bbox: BBox::new(0.0, 0.0, 400.0, 30.0),
+                source: Provenance::Ocr,
+                style: None,
+                link: None,
+                revision: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn picks_the_better_reading_per_token() {
+        // The parser breaks one Hangul word into jamo; OCR misreads 2024.
+        let parser = vec![
+            parser_line(&["예산", "\u{1112}\u{1161}\u{11AB}"], 0.0),
+            parser_line(&["2024", "년"], 20.0),
+        ];
+        let ocr = vec![ocr_line("예산 한\n2O24 년")];
+
+        let lines = fuse_tokens(&parser, &ocr, 0.9, 0.6, Provenance::Ocr, Provenance::Parser)
+            .expect("tokens differ");
+
+        let spans: Vec<(&str, Provenance)> = lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .map(|span| (span.text.as_str(), span.source))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("예산 ", Provenance::Fused),
+                ("한", Provenance::Ocr),
+                ("2024 ", Provenance::Parser),
+                ("년", Provenance::Fused),
+            ]
+        );
+        assert_eq!(lines[0].spans[1].bbox, parser[0].spans[1].bbox);
+    }
+
+    #[test]
+    fn low_confidence_ocr_cannot_override() {
+        let parser = vec![parser_line(&["예산", "\u{1112}\u{1161}\u{11AB}"], 0.0)];
+        let ocr = vec![ocr_line("예산 한 추가")];
+
+        assert!(
+            fuse_tokens(&parser, &ocr, 0.3, 0.6, Provenance::Ocr, Provenance::Parser).is_none()
+        );
+    }
+}
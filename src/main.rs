@@ -3,7 +3,11 @@ use std::path::PathBuf;
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand, ValueEnum};
 
-use docstruct::pipeline::{build_document, export_document, PipelineConfig};
+use docstruct::core::error::DocumentError;
+use docstruct::core::model::{DocumentMetadata, RevisionMode};
+use docstruct::core::policy::FusionPolicy;
+use docstruct::export::ExportOptions;
+use docstruct::pipeline::{build_document, export_document_with, PipelineConfig};
 
 #[derive(Parser, Debug)]
 #[command(name = "docstruct")]
@@ -39,6 +43,26 @@ enum Commands {
         /// Disable progress bar
         #[arg(short, long)]
         quiet: bool,
+
+        /// Password for encrypted PDFs (owner or user password)
+        #[arg(long, env = "DOCSTRUCT_PDF_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+
+        /// Fusion policy file (TOML, or JSON by extension) overriding thresholds
+        #[arg(long, value_name = "FILE")]
+        policy: Option<PathBuf>,
+
+        /// Leave footnotes, endnotes and comments out of the outputs
+        #[arg(long)]
+        no_notes: bool,
+
+        /// Leave running page headers and footers out of the outputs
+        #[arg(long)]
+        no_headers_footers: bool,
+
+        /// How DOCX tracked changes are read
+        #[arg(long, value_enum, default_value_t = Revisions::Accepted)]
+        revisions: Revisions,
     },
 
     /// Convert multiple document files
@@ -61,15 +85,75 @@ enum Commands {
         /// Enable debug outputs
         #[arg(short, long)]
         debug: bool,
+
+        /// Password for encrypted PDFs (owner or user password)
+        #[arg(long, env = "DOCSTRUCT_PDF_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+
+        /// Fusion policy file (TOML, or JSON by extension) overriding thresholds
+        #[arg(long, value_name = "FILE")]
+        policy: Option<PathBuf>,
+
+        /// Leave footnotes, endnotes and comments out of the outputs
+        #[arg(long)]
+        no_notes: bool,
+
+        /// Leave running page headers and footers out of the outputs
+        #[arg(long)]
+        no_headers_footers: bool,
+
+        /// How DOCX tracked changes are read
+        #[arg(long, value_enum, default_value_t = Revisions::Accepted)]
+        revisions: Revisions,
     },
 
     /// Show information about a document file
     Info {
         /// Input document path (pdf/docx/ppt/pptx)
         input: PathBuf,
+
+        /// Print the information as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Password for encrypted PDFs (owner or user password)
+        #[arg(long, env = "DOCSTRUCT_PDF_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
     },
 }
 
+/// Pipeline settings shared by every document of a run.
+#[derive(Debug, Clone)]
+struct RunOptions {
+    dpi: u32,
+    password: Option<String>,
+    policy: FusionPolicy,
+    revisions: RevisionMode,
+    export: ExportOptions,
+}
+
+impl RunOptions {
+    fn new(
+        dpi: u32,
+        password: Option<String>,
+        policy: Option<PathBuf>,
+        revisions: RevisionMode,
+        export: ExportOptions,
+    ) -> Result<Self> {
+        let policy = match policy {
+            Some(path) => FusionPolicy::load(&path)?,
+            None => FusionPolicy::default(),
+        };
+        Ok(Self {
+            dpi,
+            password,
+            policy,
+            revisions,
+            export,
+        })
+    }
+}
+
 #[derive(ValueEnum, Clone, Debug, PartialEq)]
 enum Format {
     Json,
@@ -78,6 +162,26 @@ enum Format {
     Html,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Revisions {
+    /// Text with every tracked change accepted
+    Accepted,
+    /// Text as it was before the tracked changes
+    Original,
+    /// Both, with insertions and deletions marked
+    Annotated,
+}
+
+impl From<Revisions> for RevisionMode {
+    fn from(revisions: Revisions) -> Self {
+        match revisions {
+            Revisions::Accepted => RevisionMode::Accepted,
+            Revisions::Original => RevisionMode::Original,
+            Revisions::Annotated => RevisionMode::Annotated,
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -89,15 +193,41 @@ fn main() -> Result<()> {
             dpi,
             debug,
             quiet,
-        } => convert_single(input, output, format, dpi, debug, quiet),
+            password,
+            policy,
+            no_notes,
+            no_headers_footers,
+            revisions,
+        } => {
+            let export = ExportOptions::default()
+                .with_notes(!no_notes)
+                .with_headers_footers(!no_headers_footers);
+            let options = RunOptions::new(dpi, password, policy, revisions.into(), export)?;
+            convert_single(input, output, format, debug, quiet, &options)
+        }
         Commands::Batch {
             inputs,
             output,
             format,
             dpi,
             debug,
-        } => convert_batch(inputs, output, format, dpi, debug),
-        Commands::Info { input } => show_info(input),
+            password,
+            policy,
+            no_notes,
+            no_headers_footers,
+            revisions,
+        } => {
+            let export = ExportOptions::default()
+                .with_notes(!no_notes)
+                .with_headers_footers(!no_headers_footers);
+            let options = RunOptions::new(dpi, password, policy, revisions.into(), export)?;
+            convert_batch(inputs, output, format, debug, &options)
+        }
+        Commands::Info {
+            input,
+            json,
+            password,
+        } => show_info(input, json, password),
     }
 }
 
@@ -105,9 +235,9 @@ fn convert_single(
     input: PathBuf,
     output: Option<PathBuf>,
     _formats: Vec<Format>,
-    dpi: u32,
     _debug: bool,
     quiet: bool,
+    options: &RunOptions,
 ) -> Result<()> {
     // Validate input
     if !input.exists() {
@@ -126,10 +256,13 @@ fn convert_single(
     if !quiet {
         println!("[*] Processing: {}", input.display());
         println!("[*] Output: {}", output_dir.display());
-        println!("[*] DPI: {}", dpi);
+        println!("[*] DPI: {}", options.dpi);
     }
 
-    let config = PipelineConfig::new(input.clone(), output_dir.clone(), dpi);
+    let config = PipelineConfig::new(input.clone(), output_dir.clone(), options.dpi)
+        .with_password(options.password.clone())
+        .with_policy(options.policy.clone())
+        .with_revisions(options.revisions);
 
     if !quiet {
         println!("\n[+] Building document...");
@@ -142,7 +275,7 @@ fn convert_single(
         println!("[+] Exporting results...");
     }
 
-    export_document(&document, &config.output)
+    export_document_with(&document, &config.output, &options.export)
         .with_context(|| format!("Failed to export to: {}", output_dir.display()))?;
 
     if !quiet {
@@ -156,8 +289,8 @@ fn convert_batch(
     inputs: Vec<PathBuf>,
     output: Option<PathBuf>,
     formats: Vec<Format>,
-    dpi: u32,
     debug: bool,
+    options: &RunOptions,
 ) -> Result<()> {
     if inputs.is_empty() {
         anyhow::bail!("No input files specified");
@@ -170,6 +303,7 @@ fn convert_batch(
 
     let mut success = 0;
     let mut failed = 0;
+    let mut encrypted = 0;
 
     for (i, input) in inputs.iter().enumerate() {
         println!(
@@ -192,23 +326,35 @@ fn convert_batch(
             input.clone(),
             Some(output_dir),
             formats.clone(),
-            dpi,
             debug,
             true,
+            options,
         ) {
             Ok(_) => {
                 println!("  [✓] Success");
                 success += 1;
             }
             Err(e) => {
-                eprintln!("  [✗] Failed: {}", e);
+                if DocumentError::find(&e).is_some_and(DocumentError::is_encrypted) {
+                    eprintln!("  [✗] Encrypted: {:#}", e);
+                    encrypted += 1;
+                } else {
+                    eprintln!("  [✗] Failed: {}", e);
+                }
                 failed += 1;
             }
         }
         println!();
     }
 
-    println!("\n[*] Summary: {} succeeded, {} failed", success, failed);
+    if encrypted > 0 {
+        println!(
+            "\n[*] Summary: {} succeeded, {} failed ({} encrypted)",
+            success, failed, encrypted
+        );
+    } else {
+        println!("\n[*] Summary: {} succeeded, {} failed", success, failed);
+    }
 
     if failed > 0 {
         anyhow::bail!("{} file(s) failed to process", failed);
@@ -217,22 +363,40 @@ fn convert_batch(
     Ok(())
 }
 
-fn show_info(input: PathBuf) -> Result<()> {
+fn show_info(input: PathBuf, json: bool, password: Option<String>) -> Result<()> {
     use docstruct::parser::{layout_builder::ParserLayoutBuilder, ParserTrack};
 
     if !input.exists() {
         anyhow::bail!("Input file does not exist: {}", input.display());
     }
 
-    let parser = ParserLayoutBuilder::new(input.clone())
+    let parser = ParserLayoutBuilder::with_password(input.clone(), password)
         .with_context(|| format!("Failed to open document: {}", input.display()))?;
 
     let page_count = parser.page_count()?;
+    // Metadata is optional, as in the pipeline: report what can be read.
+    let metadata = parser.metadata().unwrap_or_else(|err| {
+        eprintln!("Warning: {err:#}");
+        DocumentMetadata::default()
+    });
+
+    if json {
+        let info = serde_json::json!({
+            "file": input.display().to_string(),
+            "pages": page_count,
+            "metadata": metadata,
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
 
     println!("Document Information");
     println!("===============");
     println!("File: {}", input.display());
     println!("Pages/Slides: {}", page_count);
+    for (label, value) in metadata.fields() {
+        println!("{label}: {value}");
+    }
 
     Ok(())
 }
@@ -26,12 +26,18 @@ impl OcrLayoutBuilder {
                 bbox,
                 confidence,
                 source,
+                cells: Vec::new(),
+                order: None,
+                tracks: Vec::new(),
                 debug: None,
             },
             "figure" => Block::FigureBlock {
                 bbox,
                 confidence,
                 source,
+                image: None,
+                order: None,
+                tracks: Vec::new(),
                 debug: None,
             },
             "math" => Block::MathBlock {
@@ -39,6 +45,8 @@ impl OcrLayoutBuilder {
                 confidence,
                 source,
                 latex: token.latex.filter(|s| !s.is_empty()),
+                order: None,
+                tracks: Vec::new(),
                 debug: None,
             },
             _ => {
@@ -48,6 +56,8 @@ impl OcrLayoutBuilder {
                     bbox,
                     source: Provenance::Ocr,
                     style: None,
+                    link: None,
+                    revision: None,
                 };
                 let line = Line { spans: vec![span] };
                 Block::TextBlock {
@@ -55,6 +65,9 @@ impl OcrLayoutBuilder {
                     lines: vec![line],
                     confidence,
                     source,
+                    role: None,
+                    order: None,
+                    tracks: Vec::new(),
                     debug: None,
                 }
             }
@@ -64,6 +77,7 @@ impl OcrLayoutBuilder {
 
 impl OcrTrack for OcrLayoutBuilder {
     fn analyze_page(&self, rendered_image: &Path, page_idx: usize) -> Result<PageHypothesis> {
+        let (width, height) = image::image_dimensions(rendered_image)?;
         let tokens = self.bridge.run(rendered_image)?;
         let blocks: Vec<Block> = tokens
             .into_iter()
@@ -73,8 +87,8 @@ impl OcrTrack for OcrLayoutBuilder {
         Ok(PageHypothesis {
             page_idx,
             blocks,
-            width: 1000,
-            height: 1400,
+            width,
+            height,
         })
     }
 }
@@ -1,5 +1,6 @@
 pub mod bridge;
 pub mod layout_builder;
+pub mod pixels;
 pub mod renderer;
 
 pub use renderer::PageRenderer;
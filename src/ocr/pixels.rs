@@ -0,0 +1,196 @@
+//! Page classification signals measured on the rendered page image.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use image::GrayImage;
+
+use crate::core::geometry::BBox;
+use crate::core::page_classifier::PixelSignals;
+
+/// Luma below which a pixel is ink.
+const INK_LUMA: u8 = 128;
+
+/// Share of ink under a parser text box from which the text counts as
+/// rendered.
+const MIN_TEXT_INK: f32 = 0.01;
+
+/// Skew is estimated on a copy scaled down to at most this width.
+const SKEW_SAMPLE_WIDTH: u32 = 800;
+
+/// Largest skew tried, in degrees either way, and the step between tries.
+const MAX_SKEW_DEGREES: f32 = 5.0;
+const SKEW_STEP_DEGREES: f32 = 0.25;
+
+/// Measure the rendered page at `path`. `parser_text` holds the parser's
+/// text boxes and `embedded_image_coverage` the share of the page under
+/// embedded images; both are passed through into the signals.
+pub fn measure_page(
+    path: &Path,
+    parser_text: &[BBox],
+    embedded_image_coverage: f32,
+) -> Result<PixelSignals> {
+    let image = image::open(path)
+        .with_context(|| format!("failed to read rendered page {}", path.display()))?
+        .to_luma8();
+    Ok(measure_image(&image, parser_text, embedded_image_coverage))
+}
+
+fn measure_image(
+    image: &GrayImage,
+    parser_text: &[BBox],
+    embedded_image_coverage: f32,
+) -> PixelSignals {
+    let (width, height) = image.dimensions();
+    let total = (width as usize * height as usize).max(1);
+    let is_ink = |x: u32, y: u32| image.get_pixel(x, y).0[0] < INK_LUMA;
+
+    let mut ink = 0usize;
+    let mut specks = 0usize;
+    for y in 0..height {
+        for x in 0..width {
+            if !is_ink(x, y) {
+                continue;
+            }
+            ink += 1;
+            let lonely = (y.saturating_sub(1)..=(y + 1).min(height - 1)).all(|ny| {
+                (x.saturating_sub(1)..=(x + 1).min(width - 1))
+                    .all(|nx| (nx, ny) == (x, y) || !is_ink(nx, ny))
+            });
+            if lonely {
+                specks += 1;
+            }
+        }
+    }
+
+    let parser_ink_overlap = (!parser_text.is_empty()).then(|| {
+        let inked = parser_text
+            .iter()
+            .filter(|bbox| ink_share(image, bbox) >= MIN_TEXT_INK)
+            .count();
+        inked as f32 / parser_text.len() as f32
+    });
+
+    PixelSignals {
+        ink_ratio: ink as f32 / total as f32,
+        embedded_image_coverage,
+        noise: if ink == 0 {
+            0.0
+        } else {
+            specks as f32 / ink as f32
+        },
+        skew_degrees: estimate_skew(image),
+        parser_ink_overlap,
+    }
+}
+
+/// Share of ink pixels inside `bbox`, clipped to the image.
+fn ink_share(image: &GrayImage, bbox: &BBox) -> f32 {
+    let (width, height) = image.dimensions();
+    let x0 = (bbox.x0.max(0.0) as u32).min(width);
+    let y0 = (bbox.y0.max(0.0) as u32).min(height);
+    let x1 = (bbox.x1.max(0.0).ceil() as u32).min(width);
+    let y1 = (bbox.y1.max(0.0).ceil() as u32).min(height);
+    let area = (x1.saturating_sub(x0) as usize) * (y1.saturating_sub(y0) as usize);
+    if area == 0 {
+        return 0.0;
+    }
+    let ink = (y0..y1)
+        .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+        .filter(|&(x, y)| image.get_pixel(x, y).0[0] < INK_LUMA)
+        .count();
+    ink as f32 / area as f32
+}
+
+/// Skew of the text lines, in degrees: the shear at which rows of ink
+/// line up best, i.e. where the row profile of ink varies most.
+fn estimate_skew(image: &GrayImage) -> f32 {
+    let sample = if image.width() > SKEW_SAMPLE_WIDTH {
+        let height =
+            (image.height() as u64 * SKEW_SAMPLE_WIDTH as u64 / image.width() as u64).max(1) as u32;
+        image::imageops::resize(
+            image,
+            SKEW_SAMPLE_WIDTH,
+            height,
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        image.clone()
+    };
+    let (width, height) = sample.dimensions();
+    let ink: Vec<(f32, f32)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| sample.get_pixel(x, y).0[0] < INK_LUMA)
+        .map(|(x, y)| (x as f32, y as f32))
+        .collect();
+    if ink.is_empty() {
+        return 0.0;
+    }
+
+    let steps = (MAX_SKEW_DEGREES / SKEW_STEP_DEGREES).round() as i32;
+    let mut best = (0.0_f32, f64::MIN);
+    // From zero outwards, so a tie keeps the smallest skew.
+    for step in (0..=steps).flat_map(|step| [step, -step]) {
+        let degrees = step as f32 * SKEW_STEP_DEGREES;
+        let slope = degrees.to_radians().tan();
+        let offset = (width as f32 * slope.abs()).ceil() as usize;
+        let mut rows = vec![0u32; height as usize + 2 * offset + 1];
+        for &(x, y) in &ink {
+            let row = (y - x * slope).round() as isize + offset as isize;
+            if let Some(count) = usize::try_from(row).ok().and_then(|row| rows.get_mut(row)) {
+                *count += 1;
+            }
+        }
+        let score: f64 = rows.iter().map(|&count| (count as f64).powi(2)).sum();
+        if score > best.1 {
+            best = (degrees, score);
+        }
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    /// A white page with three dark text lines, sheared by `slope`.
+    fn page_with_lines(slope: f32) -> GrayImage {
+        let mut image = GrayImage::from_pixel(400, 300, Luma([255]));
+        for line in 0..3 {
+            let y0 = 60.0 + line as f32 * 80.0;
+            for x in 20..380u32 {
+                for dy in 0..6 {
+                    let y = (y0 + dy as f32 + x as f32 * slope).round() as u32;
+                    image.put_pixel(x, y, Luma([0]));
+                }
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn measures_ink_noise_and_overlap() {
+        let mut image = page_with_lines(0.0);
+        for i in 0..40 {
+            image.put_pixel(5 + i * 9, 290, Luma([0]));
+        }
+        let on_text = BBox::new(20.0, 60.0, 380.0, 66.0);
+        let blank = BBox::new(20.0, 10.0, 380.0, 40.0);
+
+        let signals = measure_image(&image, &[on_text, blank], 0.25);
+
+        assert_eq!(signals.embedded_image_coverage, 0.25);
+        assert_eq!(signals.parser_ink_overlap, Some(0.5));
+        assert!(signals.ink_ratio > 0.05 && signals.ink_ratio < 0.06);
+        assert!(signals.noise > 0.0 && signals.noise < 0.01);
+        assert_eq!(signals.skew_degrees, 0.0);
+    }
+
+    #[test]
+    fn estimates_skew_of_tilted_lines() {
+        let image = page_with_lines(2.0_f32.to_radians().tan());
+        let skew = estimate_skew(&image);
+        assert!((skew - 2.0).abs() <= SKEW_STEP_DEGREES, "{skew}");
+    }
+}
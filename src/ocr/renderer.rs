@@ -3,6 +3,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::parser::poppler;
+
 #[derive(Debug, Clone)]
 pub struct RenderedPage {
     pub path: PathBuf,
@@ -14,11 +16,22 @@ pub struct RenderedPage {
 pub struct PageRenderer {
     out_dir: PathBuf,
     dpi: u32,
+    password: Option<String>,
 }
 
 impl PageRenderer {
     pub fn new(out_dir: PathBuf, dpi: u32) -> Self {
-        Self { out_dir, dpi }
+        Self {
+            out_dir,
+            dpi,
+            password: None,
+        }
+    }
+
+    /// Owner or user password for encrypted PDFs.
+    pub fn with_password(mut self, password: Option<String>) -> Self {
+        self.password = password;
+        self
     }
 
     pub fn render_page(&self, pdf_path: &Path, page_idx: usize) -> Result<RenderedPage> {
@@ -31,21 +44,27 @@ impl PageRenderer {
             .to_str()
             .ok_or_else(|| anyhow::anyhow!("non-UTF8 output path not supported"))?;
 
-        let status = Command::new("pdftoppm")
-            .arg("-png")
-            .arg("-r")
-            .arg(self.dpi.to_string())
-            .arg("-f")
-            .arg(page_number.to_string())
-            .arg("-l")
-            .arg(page_number.to_string())
-            .arg(pdf_path)
-            .arg(prefix_str)
-            .status()
-            .with_context(|| "failed to invoke pdftoppm; is poppler-utils installed?")?;
+        let output =
+            poppler::password_args(&mut Command::new("pdftoppm"), self.password.as_deref())
+                .arg("-png")
+                .arg("-r")
+                .arg(self.dpi.to_string())
+                .arg("-f")
+                .arg(page_number.to_string())
+                .arg("-l")
+                .arg(page_number.to_string())
+                .arg(pdf_path)
+                .arg(prefix_str)
+                .output()
+                .with_context(|| "failed to invoke pdftoppm; is poppler-utils installed?")?;
 
-        if !status.success() {
-            anyhow::bail!("pdftoppm failed with status: {status}");
+        if !output.status.success() {
+            return Err(poppler::failure(
+                "pdftoppm",
+                pdf_path,
+                &output,
+                self.password.as_deref(),
+            ));
         }
 
         // pdftoppm naming varies by version (`-1`, `-01`, etc.). Find the
@@ -69,10 +88,12 @@ impl PageRenderer {
                 )
             })?;
 
-        // We could inspect the image to get exact dimensions in the future.
-        // For now, approximate using the default layout used elsewhere.
-        let width = (1000.0 * self.dpi as f32 / 200.0) as u32;
-        let height = (1400.0 * self.dpi as f32 / 200.0) as u32;
+        let (width, height) = image::image_dimensions(&image_path).with_context(|| {
+            format!(
+                "failed to read rendered image size: {}",
+                image_path.display()
+            )
+        })?;
 
         Ok(RenderedPage {
             path: image_path,
@@ -0,0 +1,293 @@
+//! Placing DOCX paragraphs on the pages of their LibreOffice rendering.
+//!
+//! The rendered words are read in order and reduced to their letters and
+//! digits; each paragraph is matched against that stream from where the
+//! previous one ended, skipping what only the rendering has (list labels,
+//! running headers, page numbers). Every word of a paragraph then lands on
+//! the page, and in the box, of the rendered words it matched. Words that
+//! match nothing stay on the page of the word before them, without a box.
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::core::geometry::BBox;
+use crate::parser::docx_reader::DocxParagraph;
+
+/// Letters matched at once when looking for where a paragraph resumes.
+const ANCHOR_CHARS: usize = 8;
+
+/// How far ahead of the previous paragraph a paragraph may start, and how
+/// far a paragraph may jump over rendered-only text, in letters.
+const START_WINDOW: usize = 3000;
+const RESYNC_WINDOW: usize = 300;
+
+/// Stretches of letters a paragraph may try as its first match before it
+/// counts as not rendered.
+const MAX_ANCHOR_ATTEMPTS: usize = 8;
+
+/// A word of the rendering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedWord {
+    pub page_idx: usize,
+    pub text: String,
+    pub bbox: BBox,
+}
+
+/// Part of a paragraph run that sits on one page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedText {
+    /// Line and run of the paragraph the text comes from.
+    pub line: usize,
+    pub run: usize,
+    pub text: String,
+    pub page_idx: usize,
+    /// Union of the rendered words the text matched, if any.
+    pub bbox: Option<BBox>,
+}
+
+/// Place every paragraph's text on the rendering. `None` for paragraphs
+/// none of whose text was found.
+pub fn place_paragraphs(
+    paragraphs: &[&DocxParagraph],
+    words: &[RenderedWord],
+) -> Vec<Option<Vec<PlacedText>>> {
+    let rendered: Vec<(char, usize)> = words
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, word)| letters(&word.text).into_iter().map(move |c| (c, idx)))
+        .collect();
+    let rendered_chars: Vec<char> = rendered.iter().map(|(c, _)| *c).collect();
+
+    let mut cursor = 0;
+    let mut page_idx = 0;
+    paragraphs
+        .iter()
+        .map(|paragraph| {
+            let tokens = tokenize(paragraph);
+            let chars: Vec<char> = tokens
+                .iter()
+                .flat_map(|token| &token.letters)
+                .copied()
+                .collect();
+            let matches = match_letters(&chars, &rendered_chars, cursor);
+            let last = matches.iter().rev().find_map(|m| *m)?;
+            cursor = last + 1;
+
+            let mut placed: Vec<PlacedText> = Vec::new();
+            let mut offset = 0;
+            for token in tokens {
+                let matched: Vec<&RenderedWord> = matches[offset..offset + token.letters.len()]
+                    .iter()
+                    .filter_map(|m| m.map(|pos| &words[rendered[pos].1]))
+                    .collect();
+                offset += token.letters.len();
+                if let Some(first) = matched.first() {
+                    page_idx = first.page_idx;
+                }
+                let bbox = matched
+                    .iter()
+                    .filter(|word| word.page_idx == page_idx)
+                    .map(|word| word.bbox)
+                    .reduce(|a, b| a.union(&b));
+
+                match placed.last_mut() {
+                    Some(prev)
+                        if (prev.line, prev.run, prev.page_idx)
+                            == (token.line, token.run, page_idx) =>
+                    {
+                        prev.text.push_str(&token.text);
+                        prev.bbox = match (prev.bbox, bbox) {
+                            (Some(a), Some(b)) => Some(a.union(&b)),
+                            (a, b) => a.or(b),
+                        };
+                    }
+                    _ => placed.push(PlacedText {
+                        line: token.line,
+                        run: token.run,
+                        text: token.text,
+                        page_idx,
+                        bbox,
+                    }),
+                }
+            }
+            Some(placed)
+        })
+        .collect()
+}
+
+/// A word of a paragraph run with the whitespace after it.
+struct Token {
+    line: usize,
+    run: usize,
+    text: String,
+    letters: Vec<char>,
+}
+
+fn tokenize(paragraph: &DocxParagraph) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for (line, runs) in paragraph.lines.iter().enumerate() {
+        for (run, docx_run) in runs.iter().enumerate() {
+            // Marks without text (comment references) still take a place.
+            if docx_run.text.is_empty() {
+                tokens.push(Token {
+                    line,
+                    run,
+                    text: String::new(),
+                    letters: Vec::new(),
+                });
+            }
+            let mut rest = docx_run.text.as_str();
+            while !rest.is_empty() {
+                let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let end = rest[word_end..]
+                    .find(|c: char| !c.is_whitespace())
+                    .map_or(rest.len(), |gap| word_end + gap);
+                tokens.push(Token {
+                    line,
+                    run,
+                    text: rest[..end].to_string(),
+                    letters: letters(&rest[..word_end]),
+                });
+                rest = &rest[end..];
+            }
+        }
+    }
+    tokens
+}
+
+/// Letters and digits of `text`, composed and lowercased, as compared
+/// between the document and its rendering.
+fn letters(text: &str) -> Vec<char> {
+    text.nfc()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Position in `rendered` matched by each letter of a paragraph, reading
+/// from `cursor`. Letters agree in order; on disagreement the match jumps
+/// ahead to where the next few letters occur again.
+fn match_letters(letters: &[char], rendered: &[char], cursor: usize) -> Vec<Option<usize>> {
+    let mut matches = vec![None; letters.len()];
+    let mut r = cursor;
+    let mut i = 0;
+    let mut synced = false;
+    let mut attempts = 0;
+    while i < letters.len() {
+        if synced && r < rendered.len() && rendered[r] == letters[i] {
+            matches[i] = Some(r);
+            r += 1;
+            i += 1;
+            continue;
+        }
+
+        let len = ANCHOR_CHARS.min(letters.len() - i);
+        let window = if synced || len < 4 {
+            RESYNC_WINDOW
+        } else {
+            START_WINDOW
+        };
+        if let Some(found) = find(&letters[i..i + len], rendered, r, window) {
+            r = found;
+            synced = true;
+            continue;
+        }
+        if !synced {
+            attempts += 1;
+            if attempts >= MAX_ANCHOR_ATTEMPTS {
+                break;
+            }
+            // Try the paragraph from a few letters further on.
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    matches
+}
+
+/// Start of the first occurrence of `needle` in `haystack[from..from + window]`.
+fn find(needle: &[char], haystack: &[char], from: usize, window: usize) -> Option<usize> {
+    let last = haystack.len().checked_sub(needle.len())?;
+    (from..=last.min(from + window)).find(|&start| haystack[start..start + needle.len()] == *needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::docx_reader::DocxRun;
+
+    fn paragraph(text: &str) -> DocxParagraph {
+        DocxParagraph {
+            lines: vec![vec![DocxRun {
+                text: text.to_string(),
+                bold: false,
+                italic: false,
+                size: None,
+                font: None,
+                link: None,
+                revision: None,
+            }]],
+            outline_level: None,
+            title: false,
+            list_label: None,
+            bookmarks: Vec::new(),
+        }
+    }
+
+    fn rendered(page_idx: usize, y: f32, text: &str) -> Vec<RenderedWord> {
+        let mut x = 72.0;
+        text.split_whitespace()
+            .map(|word| {
+                let bbox = BBox::new(x, y, x + 6.0 * word.len() as f32, y + 12.0);
+                x = bbox.x1 + 4.0;
+                RenderedWord {
+                    page_idx,
+                    text: word.to_string(),
+                    bbox,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn places_paragraphs_across_page_breaks() {
+        let paragraphs = [
+            paragraph("First paragraph, on the first page."),
+            paragraph("Second one runs over the break."),
+        ];
+        let words: Vec<RenderedWord> = [
+            rendered(0, 100.0, "First paragraph, on the first page."),
+            rendered(0, 700.0, "• Second one runs"),
+            rendered(0, 760.0, "1"),
+            rendered(1, 40.0, "Running header"),
+            rendered(1, 100.0, "over the break."),
+        ]
+        .concat();
+
+        let placed = place_paragraphs(&paragraphs.each_ref(), &words);
+
+        let first = placed[0].as_ref().expect("first placed");
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].page_idx, 0);
+        assert_eq!(first[0].bbox.map(|b| b.y0), Some(100.0));
+
+        let second = placed[1].as_ref().expect("second placed");
+        let pages: Vec<(usize, &str)> = second
+            .iter()
+            .map(|piece| (piece.page_idx, piece.text.as_str()))
+            .collect();
+        assert_eq!(pages, vec![(0, "Second one runs "), (1, "over the break.")]);
+        assert_eq!(second[1].bbox.map(|b| b.y0), Some(100.0));
+    }
+
+    #[test]
+    fn paragraphs_missing_from_the_rendering_are_unplaced() {
+        let paragraphs = [paragraph("Hidden words nowhere"), paragraph("Shown text")];
+        let words = rendered(0, 100.0, "Shown text");
+
+        let placed = place_paragraphs(&paragraphs.each_ref(), &words);
+
+        assert!(placed[0].is_none());
+        assert!(placed[1].is_some());
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
@@ -6,50 +7,44 @@ use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::core::geometry::BBox;
-use crate::core::model::{Block, Line, PageHypothesis, Provenance, Span};
-use crate::parser::ParserTrack;
+use crate::core::model::{
+    Block, DocumentMetadata, Line, LinkTarget, NoteKind, NoteRef, OutlineEntry, PageHypothesis,
+    PageLink, PageSize, Provenance, RevisionMode, Span, TableCell, TextRole, TextStyle,
+};
+use crate::parser::docx_layout::{place_paragraphs, PlacedText, RenderedWord};
+use crate::parser::docx_reader::{
+    DocxBlock, DocxCell, DocxDocument, DocxHeaderFooter, DocxParagraph, DocxRun, DocxTable,
+};
+use crate::parser::ooxml;
+use crate::parser::pdf_parser::PdfParser;
+use crate::parser::{PageImage, ParserTrack};
 
 #[derive(Debug, Clone)]
 pub struct DocxParser {
-    text: String,
+    path: PathBuf,
+    /// Page sizes of the LibreOffice rendering, which defines page space.
+    pages: Vec<PageSize>,
+    /// Paragraph and table blocks of each page, placed where the rendering
+    /// shows them.
+    blocks: Vec<Vec<Block>>,
+    /// Headings in document order, on the page where they start.
+    headings: Vec<OutlineEntry>,
     render_pdf_path: PathBuf,
     _temp_dir: PathBuf,
 }
 
 impl DocxParser {
     pub fn new(path: PathBuf) -> Result<Self> {
+        Self::with_revisions(path, RevisionMode::default())
+    }
+
+    /// Open the document, reading tracked changes as `revisions` says.
+    pub fn with_revisions(path: PathBuf, revisions: RevisionMode) -> Result<Self> {
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
         let temp_dir = std::env::temp_dir().join(format!("docstruct-docx-{now}"));
         fs::create_dir_all(&temp_dir)?;
 
-        let script = r#"
-import sys, zipfile, xml.etree.ElementTree as ET
-path = sys.argv[1]
-with zipfile.ZipFile(path) as z:
-    data = z.read('word/document.xml')
-root = ET.fromstring(data)
-ns = {'w':'http://schemas.openxmlformats.org/wordprocessingml/2006/main'}
-paras = []
-for p in root.findall('.//w:p', ns):
-    chunks = [t.text for t in p.findall('.//w:t', ns) if t.text]
-    txt = ''.join(chunks).strip()
-    if txt:
-        paras.append(txt)
-print('\n'.join(paras))
-"#;
-        let output = Command::new("python3")
-            .arg("-c")
-            .arg(script)
-            .arg(&path)
-            .output()
-            .with_context(|| "failed to invoke python3 for DOCX parsing")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "DOCX parsing failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+        let document = DocxDocument::open(&path, revisions)?;
 
         let status = Command::new("soffice")
             .arg("--headless")
@@ -77,48 +72,524 @@ print('\n'.join(paras))
             );
         }
 
+        let render = PdfParser::new(render_pdf_path.clone());
+        let pages = (0..render.page_count()?.max(1))
+            .map(|page_idx| render.page_size(page_idx))
+            .collect::<Result<Vec<_>>>()?;
+        let words: Vec<RenderedWord> = (0..pages.len())
+            .flat_map(|page_idx| {
+                render
+                    .page_words(page_idx)
+                    .into_iter()
+                    .map(move |word| RenderedWord {
+                        page_idx,
+                        text: word.text,
+                        bbox: word.bbox,
+                    })
+            })
+            .collect();
+        let (blocks, headings) = layout_blocks(&document, &words, &pages);
+
         Ok(Self {
-            text: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            path,
+            pages,
+            blocks,
+            headings,
             render_pdf_path,
             _temp_dir: temp_dir,
         })
     }
 }
 
-impl ParserTrack for DocxParser {
-    fn page_count(&self) -> Result<usize> {
-        Ok(1)
+/// Blocks per page and the headings among them. Paragraphs and table rows
+/// go where their text shows in the rendering, split at page breaks; text
+/// the rendering does not show follows the block before it. When the
+/// rendering has no readable text at all, blocks share the first page in
+/// equal bands.
+///
+/// Running headers and footers are looked for on each page and kept where
+/// found. Footnotes follow on the page of their reference or the next one,
+/// endnotes anywhere after it; comments, which the rendering leaves out,
+/// go under the last block on their reference's page.
+fn layout_blocks(
+    document: &DocxDocument,
+    words: &[RenderedWord],
+    pages: &[PageSize],
+) -> (Vec<Vec<Block>>, Vec<OutlineEntry>) {
+    let body = &document.blocks;
+    let paragraphs: Vec<&DocxParagraph> = body.iter().flat_map(DocxBlock::paragraphs).collect();
+    let mut placements = if words.is_empty() {
+        vec![None; paragraphs.len()]
+    } else {
+        place_paragraphs(&paragraphs, words)
     }
+    .into_iter();
 
-    fn analyze_page(&self, page_idx: usize) -> Result<PageHypothesis> {
-        let blocks = if page_idx == 0 && !self.text.is_empty() {
-            let bbox = BBox::new(0.0, 0.0, 1000.0, 1400.0);
-            vec![Block::TextBlock {
+    let mut layout = PageLayout::new(pages, words.is_empty().then_some(body.len()));
+    layout.add_header_footer(&document.header, TextRole::PageHeader, words);
+    for item in body {
+        match item {
+            DocxBlock::Paragraph(paragraph) => {
+                let placement = placements.next().flatten();
+                layout.add_paragraph(paragraph, placement, paragraph.role());
+            }
+            DocxBlock::Table(table) => {
+                let cells = table
+                    .cells
+                    .iter()
+                    .map(|cell| {
+                        cell.paragraphs
+                            .iter()
+                            .map(|paragraph| (paragraph, placements.next().flatten()))
+                            .collect()
+                    })
+                    .collect();
+                layout.add_table(table, cells);
+            }
+        }
+    }
+
+    for docx_note in &document.notes {
+        let Some(&page_idx) = layout.note_pages.get(&docx_note.note) else {
+            continue;
+        };
+        let shown_on = |word: &&RenderedWord| match docx_note.note.kind {
+            NoteKind::Footnote => word.page_idx == page_idx || word.page_idx == page_idx + 1,
+            NoteKind::Endnote => word.page_idx >= page_idx,
+            NoteKind::Comment => false,
+        };
+        let note_words: Vec<RenderedWord> = words.iter().filter(shown_on).cloned().collect();
+        let paragraphs: Vec<&DocxParagraph> = docx_note.paragraphs.iter().collect();
+        let placements = if note_words.is_empty() {
+            vec![None; paragraphs.len()]
+        } else {
+            place_paragraphs(&paragraphs, &note_words)
+        };
+        layout.resume_at(page_idx);
+        for (paragraph, placement) in paragraphs.into_iter().zip(placements) {
+            layout.add_paragraph(paragraph, placement, docx_note.note.kind.role());
+        }
+    }
+    layout.add_header_footer(&document.footer, TextRole::PageFooter, words);
+    layout.resolve_bookmark_links();
+    (layout.blocks, layout.headings)
+}
+
+/// A cell's paragraphs with where their text was placed, if anywhere.
+type CellPlacement<'a> = Vec<(&'a DocxParagraph, Option<Vec<PlacedText>>)>;
+
+/// A cell's paragraphs with their text pieces.
+type CellPieces<'a> = Vec<(&'a DocxParagraph, Vec<PlacedText>)>;
+
+/// Blocks laid out so far, and where the last one ended.
+struct PageLayout<'a> {
+    pages: &'a [PageSize],
+    blocks: Vec<Vec<Block>>,
+    headings: Vec<OutlineEntry>,
+    /// Page and bottom edge of the last block.
+    last: (usize, f32),
+    /// Height of every block when nothing comes from the rendering.
+    band: Option<f32>,
+    /// Page of each note's reference mark.
+    note_pages: HashMap<NoteRef, usize>,
+    /// Page on which each bookmark's paragraph starts.
+    bookmark_pages: HashMap<String, usize>,
+}
+
+impl<'a> PageLayout<'a> {
+    const MARGIN_PT: f32 = 72.0;
+    const LINE_HEIGHT_PT: f32 = 14.0;
+
+    /// `unrendered_blocks`, when set, is the number of blocks sharing the
+    /// first page because the rendering placed nothing.
+    fn new(pages: &'a [PageSize], unrendered_blocks: Option<usize>) -> Self {
+        let top = Self::MARGIN_PT.min(pages[0].height / 4.0);
+        Self {
+            pages,
+            blocks: vec![Vec::new(); pages.len()],
+            headings: Vec::new(),
+            last: (0, top),
+            band: unrendered_blocks
+                .map(|count| (pages[0].height - 2.0 * top) / count.max(1) as f32),
+            note_pages: HashMap::new(),
+            bookmark_pages: HashMap::new(),
+        }
+    }
+
+    /// Continue under the lowest block of `page_idx`.
+    fn resume_at(&mut self, page_idx: usize) {
+        if self.last.0 == page_idx {
+            return;
+        }
+        let top = Self::MARGIN_PT.min(self.pages[page_idx].height / 4.0);
+        let bottom = self.blocks[page_idx]
+            .iter()
+            .map(|block| block.bbox().y1)
+            .fold(top, f32::max);
+        self.last = (page_idx, bottom);
+    }
+
+    /// A page header or footer on every page where the rendering shows it.
+    fn add_header_footer(
+        &mut self,
+        header_footer: &DocxHeaderFooter,
+        role: TextRole,
+        words: &[RenderedWord],
+    ) {
+        let last = self.last;
+        for page_idx in 0..self.pages.len() {
+            let paragraphs: Vec<&DocxParagraph> = header_footer.for_page(page_idx).iter().collect();
+            let page_words: Vec<RenderedWord> = words
+                .iter()
+                .filter(|word| word.page_idx == page_idx)
+                .cloned()
+                .collect();
+            if paragraphs.is_empty() || page_words.is_empty() {
+                continue;
+            }
+            let placements = place_paragraphs(&paragraphs, &page_words);
+            for (paragraph, placement) in paragraphs.into_iter().zip(placements) {
+                if placement.is_some() {
+                    self.add_paragraph(paragraph, placement, role);
+                }
+            }
+        }
+        // The body flows from where it was, not from the last footer.
+        self.last = last;
+    }
+
+    /// Point internal hyperlinks (`#bookmark`) at the page their bookmark
+    /// landed on; links to bookmarks the document lacks are dropped.
+    fn resolve_bookmark_links(&mut self) {
+        let spans = self
+            .blocks
+            .iter_mut()
+            .flatten()
+            .flat_map(|block| match block {
+                Block::TextBlock { lines, .. } => lines.iter_mut().collect::<Vec<_>>(),
+                Block::TableBlock { cells, .. } => cells
+                    .iter_mut()
+                    .flat_map(|cell| cell.lines.iter_mut())
+                    .collect(),
+                _ => Vec::new(),
+            });
+        for span in spans.flat_map(|line| line.spans.iter_mut()) {
+            if let Some(LinkTarget::Uri(uri)) = &span.link {
+                if let Some(bookmark) = uri.strip_prefix('#') {
+                    span.link = self
+                        .bookmark_pages
+                        .get(bookmark)
+                        .map(|&page_idx| LinkTarget::Page(page_idx));
+                }
+            }
+        }
+    }
+
+    /// Remember the page of every note reference among `pieces`.
+    fn note_marks(&mut self, paragraph: &DocxParagraph, pieces: &[PlacedText]) {
+        for piece in pieces {
+            if let Some(LinkTarget::Note(note)) = &paragraph.lines[piece.line][piece.run].link {
+                self.note_pages
+                    .entry(note.clone())
+                    .or_insert(piece.page_idx);
+            }
+        }
+    }
+
+    /// Room for `lines` lines the rendering did not place: under the last
+    /// block on its page, or at the top of a later page.
+    fn free_box(&self, page_idx: usize, lines: usize) -> BBox {
+        let page = self.pages[page_idx];
+        let margin = Self::MARGIN_PT.min(page.width / 4.0);
+        let top = if page_idx == self.last.0 {
+            self.last.1
+        } else {
+            Self::MARGIN_PT.min(page.height / 4.0)
+        };
+        let height = self
+            .band
+            .unwrap_or(Self::LINE_HEIGHT_PT * lines.max(1) as f32);
+        BBox::new(
+            margin,
+            top,
+            page.width - margin,
+            (top + height).min(page.height),
+        )
+    }
+
+    fn next_order(&self, page_idx: usize) -> Option<usize> {
+        Some(self.blocks[page_idx].len())
+    }
+
+    fn push(&mut self, page_idx: usize, bbox: BBox, block: Block) {
+        self.blocks[page_idx].push(block);
+        self.last = (page_idx, bbox.y1);
+    }
+
+    fn add_paragraph(
+        &mut self,
+        paragraph: &DocxParagraph,
+        placement: Option<Vec<PlacedText>>,
+        role: TextRole,
+    ) {
+        let pieces = placement.unwrap_or_else(|| unplaced_pieces(paragraph, self.last.0));
+        self.note_marks(paragraph, &pieces);
+        for (idx, on_page) in pieces.chunk_by(|a, b| a.page_idx == b.page_idx).enumerate() {
+            let page_idx = on_page[0].page_idx;
+            let bbox = pieces_bbox(on_page, page_idx)
+                .unwrap_or_else(|| self.free_box(page_idx, paragraph.lines.len()));
+            if idx == 0 {
+                for bookmark in &paragraph.bookmarks {
+                    self.bookmark_pages
+                        .entry(bookmark.clone())
+                        .or_insert(page_idx);
+                }
+            }
+            if idx == 0 && matches!(role, TextRole::Heading(_)) {
+                if let Some(level) = paragraph.outline_level {
+                    let title = paragraph.text().replace('\n', " ");
+                    self.headings
+                        .push(OutlineEntry::new(title.trim(), level, Some(page_idx)));
+                }
+            }
+            let block = Block::TextBlock {
                 bbox,
-                lines: vec![Line {
-                    spans: vec![Span {
-                        text: self.text.clone(),
-                        bbox,
-                        source: Provenance::Parser,
-                        style: None,
-                    }],
-                }],
+                lines: paragraph_lines(paragraph, on_page, bbox, idx == 0),
                 confidence: 0.6,
                 source: Provenance::Parser,
+                role: Some(role),
+                order: self.next_order(page_idx),
+                tracks: Vec::new(),
                 debug: None,
-            }]
-        } else {
-            vec![]
+            };
+            self.push(page_idx, bbox, block);
+        }
+    }
+
+    /// One table block per page the table's rows fall on. A row is on the
+    /// page of its first placed text; cells without placed text get the
+    /// extent of their row and column.
+    fn add_table(&mut self, table: &DocxTable, cells: Vec<CellPlacement>) {
+        let mut page_idx = self.last.0;
+        let mut cells_pieces: Vec<CellPieces> = Vec::new();
+        for paragraphs in cells {
+            let mut placed = Vec::new();
+            for (paragraph, placement) in paragraphs {
+                let pieces = placement.unwrap_or_else(|| unplaced_pieces(paragraph, page_idx));
+                self.note_marks(paragraph, &pieces);
+                if let Some(piece) = pieces.last() {
+                    page_idx = piece.page_idx;
+                }
+                placed.push((paragraph, pieces));
+            }
+            cells_pieces.push(placed);
+        }
+
+        let mut row_pages = Vec::with_capacity(table.rows);
+        let mut page_idx = self.last.0;
+        for row in 0..table.rows {
+            let placed = table
+                .cells
+                .iter()
+                .zip(&cells_pieces)
+                .filter(|(cell, _)| cell.row == row)
+                .flat_map(|(_, paragraphs)| paragraphs.iter().flat_map(|(_, pieces)| pieces))
+                .find(|piece| piece.bbox.is_some());
+            if let Some(piece) = placed {
+                page_idx = piece.page_idx;
+            }
+            row_pages.push(page_idx);
+        }
+
+        let rows: Vec<usize> = (0..table.rows).collect();
+        for group in rows.chunk_by(|a, b| row_pages[*a] == row_pages[*b]) {
+            let (first_row, end_row) = (group[0], group[group.len() - 1] + 1);
+            let page_idx = row_pages[first_row];
+            let members: Vec<(&DocxCell, &CellPieces)> = table
+                .cells
+                .iter()
+                .zip(&cells_pieces)
+                .filter(|(cell, _)| (first_row..end_row).contains(&cell.row))
+                .collect();
+            let boxes: Vec<Option<BBox>> = members
+                .iter()
+                .map(|(_, paragraphs)| {
+                    paragraphs
+                        .iter()
+                        .filter_map(|(_, pieces)| pieces_bbox(pieces, page_idx))
+                        .reduce(|a, b| a.union(&b))
+                })
+                .collect();
+            let bbox = boxes
+                .iter()
+                .flatten()
+                .copied()
+                .reduce(|a, b| a.union(&b))
+                .unwrap_or_else(|| self.free_box(page_idx, group.len()));
+
+            let extent = |overlaps: &dyn Fn(&DocxCell) -> bool| {
+                members
+                    .iter()
+                    .zip(&boxes)
+                    .filter(|((cell, _), _)| overlaps(cell))
+                    .filter_map(|(_, bbox)| *bbox)
+                    .reduce(|a, b| a.union(&b))
+            };
+            let cells = members
+                .iter()
+                .zip(&boxes)
+                .map(|((cell, paragraphs), own)| {
+                    let cell_bbox = own.unwrap_or_else(|| {
+                        let rows = cell.row..cell.row + cell.rowspan;
+                        let cols = cell.col..cell.col + cell.colspan;
+                        let row_extent = extent(&|other| rows.contains(&other.row)).unwrap_or(bbox);
+                        let col_extent =
+                            extent(&|other| cols.contains(&other.col) && other.colspan == 1)
+                                .unwrap_or(bbox);
+                        BBox::new(col_extent.x0, row_extent.y0, col_extent.x1, row_extent.y1)
+                    });
+                    TableCell {
+                        row: cell.row - first_row,
+                        col: cell.col,
+                        rowspan: cell.rowspan.min(end_row - cell.row),
+                        colspan: cell.colspan,
+                        bbox: cell_bbox,
+                        lines: paragraphs
+                            .iter()
+                            .flat_map(|(paragraph, pieces)| {
+                                paragraph_lines(paragraph, pieces, cell_bbox, true)
+                            })
+                            .collect(),
+                        header: cell.header,
+                    }
+                })
+                .collect();
+            let block = Block::TableBlock {
+                bbox,
+                confidence: 0.6,
+                source: Provenance::Parser,
+                cells,
+                order: self.next_order(page_idx),
+                tracks: Vec::new(),
+                debug: None,
+            };
+            self.push(page_idx, bbox, block);
+        }
+    }
+}
+
+/// Union of the boxes of `pieces` placed on `page_idx`.
+fn pieces_bbox(pieces: &[PlacedText], page_idx: usize) -> Option<BBox> {
+    pieces
+        .iter()
+        .filter(|piece| piece.page_idx == page_idx)
+        .filter_map(|piece| piece.bbox)
+        .reduce(|a, b| a.union(&b))
+}
+
+/// Every run of a paragraph whole on one page, without boxes.
+fn unplaced_pieces(paragraph: &DocxParagraph, page_idx: usize) -> Vec<PlacedText> {
+    paragraph
+        .lines
+        .iter()
+        .enumerate()
+        .flat_map(|(line, runs)| {
+            runs.iter()
+                .enumerate()
+                .map(move |(run, docx_run)| PlacedText {
+                    line,
+                    run,
+                    text: docx_run.text.clone(),
+                    page_idx,
+                    bbox: None,
+                })
+        })
+        .collect()
+}
+
+/// Lines of the pieces of a paragraph on one page. Pieces without a box of
+/// their own take the block's; the list label leads the paragraph's first
+/// block in the style of its first run.
+fn paragraph_lines(
+    paragraph: &DocxParagraph,
+    pieces: &[PlacedText],
+    bbox: BBox,
+    with_label: bool,
+) -> Vec<Line> {
+    let span = |run: &DocxRun, text: String, bbox: BBox| Span {
+        text,
+        bbox,
+        source: Provenance::Parser,
+        style: Some(TextStyle {
+            font: run.font.clone(),
+            size: run.size,
+            bold: run.bold,
+            italic: run.italic,
+        }),
+        link: run.link.clone(),
+        revision: run.revision.clone(),
+    };
+
+    let mut lines: Vec<(usize, Vec<Span>)> = Vec::new();
+    for piece in pieces {
+        let run = &paragraph.lines[piece.line][piece.run];
+        let piece_span = span(run, piece.text.clone(), piece.bbox.unwrap_or(bbox));
+        match lines.last_mut() {
+            Some((line, spans)) if *line == piece.line => spans.push(piece_span),
+            _ => lines.push((piece.line, vec![piece_span])),
+        }
+    }
+    if let (true, Some(label), Some((_, spans))) =
+        (with_label, &paragraph.list_label, lines.first_mut())
+    {
+        let first = &paragraph.lines[pieces[0].line][pieces[0].run];
+        let label_span = Span {
+            link: None,
+            ..span(first, format!("{label} "), spans[0].bbox)
         };
+        spans.insert(0, label_span);
+    }
+    lines.into_iter().map(|(_, spans)| Line { spans }).collect()
+}
+
+impl ParserTrack for DocxParser {
+    fn page_count(&self) -> Result<usize> {
+        Ok(self.pages.len())
+    }
+
+    fn page_size(&self, page_idx: usize) -> Result<PageSize> {
+        self.pages
+            .get(page_idx)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("page {page_idx} out of range"))
+    }
 
+    fn analyze_page(&self, page_idx: usize) -> Result<PageHypothesis> {
+        let page = self.page_size(page_idx)?;
         Ok(PageHypothesis {
             page_idx,
-            blocks,
-            width: 1000,
-            height: 1400,
+            blocks: self.blocks[page_idx].clone(),
+            width: page.width.round() as u32,
+            height: page.height.round() as u32,
         })
     }
 
+    fn page_links(&self, _page_idx: usize) -> Result<Vec<PageLink>> {
+        Ok(Vec::new())
+    }
+
+    fn page_images(&self, _page_idx: usize) -> Result<Vec<PageImage>> {
+        Ok(Vec::new())
+    }
+
+    fn outline(&self) -> Result<Vec<OutlineEntry>> {
+        Ok(OutlineEntry::nest(self.headings.clone()))
+    }
+
+    fn metadata(&self) -> Result<DocumentMetadata> {
+        ooxml::read_core_properties(&self.path)
+    }
+
     fn supports_ocr_rendering(&self) -> bool {
         true
     }
@@ -127,3 +598,280 @@ impl ParserTrack for DocxParser {
         Some(&self.render_pdf_path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::docx_reader::DocxNote;
+
+    fn paragraph(text: &str, list_label: Option<&str>) -> DocxParagraph {
+        DocxParagraph {
+            lines: vec![vec![DocxRun {
+                text: text.to_string(),
+                bold: false,
+                italic: false,
+                size: Some(11.0),
+                font: None,
+                link: None,
+                revision: None,
+            }]],
+            outline_level: None,
+            title: false,
+            list_label: list_label.map(str::to_string),
+            bookmarks: Vec::new(),
+        }
+    }
+
+    fn word(page_idx: usize, x: f32, y: f32, text: &str) -> RenderedWord {
+        RenderedWord {
+            page_idx,
+            text: text.to_string(),
+            bbox: BBox::new(x, y, x + 40.0, y + 12.0),
+        }
+    }
+
+    #[test]
+    fn splits_paragraphs_at_page_breaks() {
+        let page = PageSize {
+            width: 612.0,
+            height: 792.0,
+            rotation: 0,
+        };
+        let body = vec![
+            DocxBlock::Paragraph(paragraph("Intro text", None)),
+            DocxBlock::Paragraph(paragraph("Listed item continues", Some("1."))),
+        ];
+        let words = vec![
+            word(0, 72.0, 100.0, "Intro"),
+            word(0, 120.0, 100.0, "text"),
+            word(0, 72.0, 700.0, "1."),
+            word(0, 100.0, 700.0, "Listed"),
+            word(0, 150.0, 700.0, "item"),
+            word(1, 72.0, 80.0, "continues"),
+        ];
+
+        let document = DocxDocument {
+            blocks: body,
+            ..Default::default()
+        };
+
+        let (blocks, _) = layout_blocks(&document, &words, &[page, page]);
+
+        assert_eq!(blocks[0].len(), 2);
+        assert_eq!(blocks[1].len(), 1);
+        let Block::TextBlock {
+            bbox, lines, order, ..
+        } = &blocks[0][1]
+        else {
+            panic!("text block expected");
+        };
+        assert_eq!(*order, Some(1));
+        assert_eq!(bbox.y0, 700.0);
+        assert_eq!(lines[0].text(), "1. Listed item ");
+        let Block::TextBlock { lines, order, .. } = &blocks[1][0] else {
+            panic!("text block expected");
+        };
+        assert_eq!(*order, Some(0));
+        assert_eq!(lines[0].text(), "continues");
+    }
+
+    #[test]
+    fn points_bookmark_links_at_their_page() {
+        let page = PageSize {
+            width: 612.0,
+            height: 792.0,
+            rotation: 0,
+        };
+        let link = |text: &str, anchor: &str| DocxRun {
+            link: Some(LinkTarget::Uri(format!("#{anchor}"))),
+            ..paragraph(text, None).lines[0][0].clone()
+        };
+        let mut contents = paragraph("See ", None);
+        contents.lines[0].push(link("Scope", "scope"));
+        contents.lines[0].push(link(" or gone", "missing"));
+        let mut scope = paragraph("Scope", None);
+        scope.bookmarks.push("scope".to_string());
+        let document = DocxDocument {
+            blocks: vec![DocxBlock::Paragraph(contents), DocxBlock::Paragraph(scope)],
+            ..Default::default()
+        };
+        let words = vec![
+            word(0, 72.0, 100.0, "See"),
+            word(0, 110.0, 100.0, "Scope"),
+            word(0, 160.0, 100.0, "or"),
+            word(0, 190.0, 100.0, "gone"),
+            word(1, 72.0, 80.0, "Scope"),
+        ];
+
+        let (blocks, _) = layout_blocks(&document, &words, &[page, page]);
+
+        let Block::TextBlock { lines, .. } = &blocks[0][0] else {
+            panic!("text block expected");
+        };
+        let links: Vec<(String, Option<LinkTarget>)> = lines[0]
+            .spans
+            .iter()
+            .map(|span| (span.text.clone(), span.link.clone()))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("See ".to_string(), None),
+                ("Scope".to_string(), Some(LinkTarget::Page(1))),
+                (" or gone".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn lays_out_table_cells_with_spans() {
+        let page = PageSize {
+            width: 612.0,
+            height: 792.0,
+            rotation: 0,
+        };
+        let cell = |row, col, rowspan, text: &str| DocxCell {
+            row,
+            col,
+            rowspan,
+            colspan: 1,
+            header: row == 0,
+            paragraphs: vec![paragraph(text, None)],
+        };
+        let table = DocxTable {
+            rows: 3,
+            cells: vec![
+                cell(0, 0, 1, "Party"),
+                cell(0, 1, 1, "Term"),
+                cell(1, 0, 2, "Seller"),
+                cell(1, 1, 1, "Annual"),
+                DocxCell {
+                    paragraphs: Vec::new(),
+                    ..cell(2, 1, 1, "")
+                },
+            ],
+        };
+        let words = vec![
+            word(0, 72.0, 100.0, "Party"),
+            word(0, 200.0, 100.0, "Term"),
+            word(0, 72.0, 120.0, "Seller"),
+            word(0, 200.0, 120.0, "Annual"),
+        ];
+
+        let document = DocxDocument {
+            blocks: vec![DocxBlock::Table(table)],
+            ..Default::default()
+        };
+
+        let (blocks, _) = layout_blocks(&document, &words, &[page]);
+
+        assert_eq!(blocks[0].len(), 1);
+        let Block::TableBlock { bbox, cells, .. } = &blocks[0][0] else {
+            panic!("table block expected");
+        };
+        assert_eq!((bbox.y0, bbox.y1), (100.0, 132.0));
+        let texts: Vec<(usize, usize, usize, String)> = cells
+            .iter()
+            .map(|cell| {
+                let text = cell.lines.iter().map(Line::text).collect();
+                (cell.row, cell.col, cell.rowspan, text)
+            })
+            .collect();
+        assert_eq!(texts[2], (1, 0, 2, "Seller".to_string()));
+        assert_eq!(texts[4], (2, 1, 1, String::new()));
+        assert!(cells[0].header);
+        // The empty cell takes its column's width.
+        assert_eq!((cells[4].bbox.x0, cells[4].bbox.x1), (200.0, 240.0));
+    }
+
+    #[test]
+    fn places_notes_and_running_headers() {
+        let page = PageSize {
+            width: 612.0,
+            height: 792.0,
+            rotation: 0,
+        };
+        let note = |kind, id: &str| NoteRef {
+            kind,
+            id: id.to_string(),
+        };
+        let mark = |text: &str, note: NoteRef| DocxRun {
+            link: Some(LinkTarget::Note(note)),
+            ..paragraph(text, None).lines[0][0].clone()
+        };
+        let mut clause = paragraph("Governed by law", None);
+        clause.lines[0].push(mark("1", note(NoteKind::Footnote, "2")));
+        clause.lines[0].push(mark("", note(NoteKind::Comment, "0")));
+        let mut footnote = paragraph(" See the Act.", None);
+        footnote.lines[0].insert(0, mark("1", note(NoteKind::Footnote, "2")));
+        let mut comment = paragraph("Which law?", None);
+        comment.lines[0].insert(0, mark("", note(NoteKind::Comment, "0")));
+        let document = DocxDocument {
+            blocks: vec![DocxBlock::Paragraph(clause)],
+            notes: vec![
+                DocxNote {
+                    note: note(NoteKind::Footnote, "2"),
+                    paragraphs: vec![footnote],
+                },
+                DocxNote {
+                    note: note(NoteKind::Comment, "0"),
+                    paragraphs: vec![comment],
+                },
+            ],
+            header: DocxHeaderFooter {
+                default: vec![paragraph("Confidential", None)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let words = vec![
+            word(0, 72.0, 30.0, "Confidential"),
+            word(0, 72.0, 100.0, "Governed"),
+            word(0, 120.0, 100.0, "by"),
+            word(0, 150.0, 100.0, "law1"),
+            word(0, 72.0, 700.0, "1"),
+            word(0, 90.0, 700.0, "See"),
+            word(0, 140.0, 700.0, "the"),
+            word(0, 190.0, 700.0, "Act."),
+        ];
+
+        let (blocks, _) = layout_blocks(&document, &words, &[page]);
+
+        let placed: Vec<(Option<TextRole>, f32, String)> = blocks[0]
+            .iter()
+            .map(|block| match block {
+                Block::TextBlock {
+                    bbox, lines, role, ..
+                } => (*role, bbox.y0, lines[0].text()),
+                _ => panic!("text block expected"),
+            })
+            .collect();
+        assert_eq!(
+            placed,
+            vec![
+                (Some(TextRole::PageHeader), 30.0, "Confidential".to_string()),
+                (
+                    Some(TextRole::Paragraph),
+                    100.0,
+                    "Governed by law1".to_string()
+                ),
+                (
+                    Some(TextRole::Footnote),
+                    700.0,
+                    "1 See the Act.".to_string()
+                ),
+                (Some(TextRole::Comment), 712.0, "Which law?".to_string()),
+            ]
+        );
+        // The comment mark keeps its place after the text it comments on.
+        let Block::TextBlock { lines, .. } = &blocks[0][1] else {
+            unreachable!();
+        };
+        let comment_mark = lines[0].spans.last().expect("comment mark");
+        assert_eq!(comment_mark.text, "");
+        assert_eq!(
+            comment_mark.link,
+            Some(LinkTarget::Note(note(NoteKind::Comment, "0")))
+        );
+    }
+}
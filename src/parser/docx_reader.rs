@@ -0,0 +1,1437 @@
+//! Native reading of WordprocessingML (`word/document.xml`).
+//!
+//! Paragraphs come out in document order with their effective run
+//! formatting (document defaults, then paragraph style, run style and direct
+//! `w:rPr`, following `w:basedOn` chains), their heading level from
+//! `w:outlineLvl` or a "heading N"/"Title" style, and their list label
+//! computed from `word/numbering.xml`. Tables come out as cell grids, with
+//! `w:gridSpan` and `w:vMerge` as column and row spans.
+//!
+//! Footnotes, endnotes and comments referenced from the body are read from
+//! their parts; each reference becomes a mark run linked to its note, and
+//! the note's own mark (`w:footnoteRef`, `w:annotationRef`) links back. The
+//! running header and footer come from the parts the last section names.
+
+use anyhow::{Context, Result};
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::core::model::{
+    LinkTarget, NoteKind, NoteRef, Revision, RevisionKind, RevisionMode, TextRole,
+};
+use crate::parser::ooxml::{Package, RELATIONSHIPS_NS};
+
+const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+/// Style chains longer than this are treated as cyclic.
+const MAX_STYLE_DEPTH: usize = 16;
+
+/// A run of text with its effective formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocxRun {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub size: Option<f32>,
+    pub font: Option<String>,
+    pub link: Option<LinkTarget>,
+    /// Tracked change the run is part of, when revisions are annotated.
+    pub revision: Option<Revision>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocxParagraph {
+    /// Lines of runs, split at `w:br`/`w:cr`.
+    pub lines: Vec<Vec<DocxRun>>,
+    /// Heading level (1-based) from `w:outlineLvl` or a heading style.
+    pub outline_level: Option<usize>,
+    /// Set for paragraphs in the document's Title style.
+    pub title: bool,
+    /// List label as rendered ("1.", "a)", "•"), for numbered paragraphs.
+    pub list_label: Option<String>,
+    /// Names of the bookmarks starting in the paragraph, which internal
+    /// hyperlinks point at.
+    pub bookmarks: Vec<String>,
+}
+
+impl DocxParagraph {
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|runs| runs.iter().map(|run| run.text.as_str()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// What the paragraph is: a heading (title counts as level 1, levels
+    /// beyond 6 as 6), a list item, or body text.
+    pub fn role(&self) -> TextRole {
+        match (self.outline_level, self.title) {
+            (Some(level), _) => TextRole::Heading(level.clamp(1, 6) as u8),
+            (None, true) => TextRole::Heading(1),
+            _ if self.list_label.is_some() => TextRole::ListItem,
+            _ => TextRole::Paragraph,
+        }
+    }
+}
+
+/// A table cell: its place in the grid and its paragraphs. Cells merged
+/// vertically are listed once, at their top row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocxCell {
+    pub row: usize,
+    pub col: usize,
+    pub rowspan: usize,
+    pub colspan: usize,
+    /// In a row marked to repeat as the table header.
+    pub header: bool,
+    pub paragraphs: Vec<DocxParagraph>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocxTable {
+    pub rows: usize,
+    pub cells: Vec<DocxCell>,
+}
+
+/// A block-level item of the body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocxBlock {
+    Paragraph(DocxParagraph),
+    Table(DocxTable),
+}
+
+impl DocxBlock {
+    /// Paragraphs of the block in document order; a table's go row by row.
+    pub fn paragraphs(&self) -> Vec<&DocxParagraph> {
+        match self {
+            DocxBlock::Paragraph(paragraph) => vec![paragraph],
+            DocxBlock::Table(table) => table
+                .cells
+                .iter()
+                .flat_map(|cell| &cell.paragraphs)
+                .collect(),
+        }
+    }
+}
+
+/// A footnote, endnote or comment with its text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocxNote {
+    pub note: NoteRef,
+    pub paragraphs: Vec<DocxParagraph>,
+}
+
+/// Running header or footer of the document's last section, by the pages
+/// it shows on.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocxHeaderFooter {
+    pub default: Vec<DocxParagraph>,
+    /// On the first page, when the section sets one apart (`w:titlePg`).
+    pub first: Option<Vec<DocxParagraph>>,
+    /// On even pages, when the settings enable it (`w:evenAndOddHeaders`).
+    pub even: Option<Vec<DocxParagraph>>,
+}
+
+impl DocxHeaderFooter {
+    /// Paragraphs shown on the page.
+    pub fn for_page(&self, page_idx: usize) -> &[DocxParagraph] {
+        let alternate = match page_idx {
+            0 => self.first.as_ref(),
+            _ if page_idx % 2 == 1 => self.even.as_ref(),
+            _ => None,
+        };
+        alternate.unwrap_or(&self.default)
+    }
+}
+
+/// The body of a DOCX file, its notes and its running header and footer.
+#[derive(Debug, Clone, Default)]
+pub struct DocxDocument {
+    pub blocks: Vec<DocxBlock>,
+    /// Referenced notes: footnotes, then endnotes, then comments, each in
+    /// order of reference.
+    pub notes: Vec<DocxNote>,
+    pub header: DocxHeaderFooter,
+    pub footer: DocxHeaderFooter,
+}
+
+impl DocxDocument {
+    pub fn open(path: &Path, revisions: RevisionMode) -> Result<Self> {
+        Self::from_package(&Package::open(path)?, revisions)
+    }
+
+    /// Read the package with tracked changes accepted, rejected (the
+    /// original text) or annotated, as `revisions` says.
+    pub fn from_package(package: &Package, revisions: RevisionMode) -> Result<Self> {
+        let xml = package
+            .read_string("word/document.xml")?
+            .context("DOCX has no word/document.xml")?;
+        let styles = match package.read_string("word/styles.xml")? {
+            Some(xml) => Styles::parse(&xml)?,
+            None => Styles::default(),
+        };
+        let numbering = match package.read_string("word/numbering.xml")? {
+            Some(xml) => Numbering::parse(&xml)?,
+            None => Numbering::default(),
+        };
+        let relationships = package.relationships("word/document.xml")?;
+
+        let doc = roxmltree::Document::parse(&xml).context("failed to parse word/document.xml")?;
+        let body = w_child(doc.root_element(), "body").context("DOCX has no w:body")?;
+        let mut reader = BodyReader::new(&styles, &numbering, &relationships, revisions);
+        let mut blocks = Vec::new();
+        reader.read_container(body, &mut blocks);
+
+        let (header, footer) = match w_child(body, "sectPr") {
+            Some(sect_pr) => {
+                let even_pages = match package.read_string("word/settings.xml")? {
+                    Some(xml) => {
+                        let settings = roxmltree::Document::parse(&xml)
+                            .context("failed to parse word/settings.xml")?;
+                        w_child(settings.root_element(), "evenAndOddHeaders")
+                            .is_some_and(|node| w_val(node).is_none_or(is_on))
+                    }
+                    None => false,
+                };
+                (
+                    reader.read_header_footer(package, sect_pr, "headerReference", even_pages)?,
+                    reader.read_header_footer(package, sect_pr, "footerReference", even_pages)?,
+                )
+            }
+            None => Default::default(),
+        };
+
+        let mut notes = Vec::new();
+        for (kind, part, tag) in [
+            (NoteKind::Footnote, "word/footnotes.xml", "footnote"),
+            (NoteKind::Endnote, "word/endnotes.xml", "endnote"),
+            (NoteKind::Comment, "word/comments.xml", "comment"),
+        ] {
+            let Some(xml) = package.read_string(part)? else {
+                continue;
+            };
+            let doc = roxmltree::Document::parse(&xml)
+                .with_context(|| format!("failed to parse {part}"))?;
+            let bodies: HashMap<&str, Node> = doc
+                .root_element()
+                .children()
+                .filter(|node| node.has_tag_name((W_NS, tag)))
+                .filter_map(|node| Some((node.attribute((W_NS, "id"))?, node)))
+                .collect();
+            let referenced: Vec<NoteRef> = reader
+                .referenced
+                .iter()
+                .filter(|note| note.kind == kind)
+                .cloned()
+                .collect();
+            for note in referenced {
+                let Some(node) = bodies.get(note.id.as_str()) else {
+                    continue;
+                };
+                reader.current_note = Some(note.clone());
+                let mut note_blocks = Vec::new();
+                reader.read_container(*node, &mut note_blocks);
+                let paragraphs = note_blocks
+                    .iter()
+                    .flat_map(DocxBlock::paragraphs)
+                    .cloned()
+                    .collect();
+                notes.push(DocxNote { note, paragraphs });
+            }
+        }
+
+        Ok(Self {
+            blocks,
+            notes,
+            header,
+            footer,
+        })
+    }
+}
+
+struct BodyReader<'a> {
+    styles: &'a Styles,
+    numbering: &'a Numbering,
+    relationships: &'a HashMap<String, String>,
+    /// Current counter per list and level, for list labels.
+    counters: HashMap<String, [Option<u32>; 9]>,
+    /// Mark text of each referenced note.
+    marks: HashMap<NoteRef, String>,
+    /// Notes in order of first reference.
+    referenced: Vec<NoteRef>,
+    /// Note whose text is being read, for its own mark.
+    current_note: Option<NoteRef>,
+    revisions: RevisionMode,
+}
+
+impl<'a> BodyReader<'a> {
+    fn new(
+        styles: &'a Styles,
+        numbering: &'a Numbering,
+        relationships: &'a HashMap<String, String>,
+        revisions: RevisionMode,
+    ) -> Self {
+        Self {
+            styles,
+            numbering,
+            relationships,
+            counters: HashMap::new(),
+            marks: HashMap::new(),
+            referenced: Vec::new(),
+            current_note: None,
+            revisions,
+        }
+    }
+
+    /// Header or footer parts a section names through `reference`
+    /// (`w:headerReference`/`w:footerReference`), by the pages they show on.
+    fn read_header_footer(
+        &mut self,
+        package: &Package,
+        sect_pr: Node,
+        reference: &str,
+        even_pages: bool,
+    ) -> Result<DocxHeaderFooter> {
+        let mut parts: HashMap<&str, Vec<DocxParagraph>> = HashMap::new();
+        for node in sect_pr
+            .children()
+            .filter(|node| node.has_tag_name((W_NS, reference)))
+        {
+            let Some(part) = node
+                .attribute((RELATIONSHIPS_NS, "id"))
+                .and_then(|id| self.relationships.get(id))
+            else {
+                continue;
+            };
+            let Some(xml) = package.read_string(part)? else {
+                continue;
+            };
+            let doc = roxmltree::Document::parse(&xml)
+                .with_context(|| format!("failed to parse {part}"))?;
+            let mut blocks = Vec::new();
+            self.read_container(doc.root_element(), &mut blocks);
+            parts.insert(
+                node.attribute((W_NS, "type")).unwrap_or("default"),
+                blocks
+                    .iter()
+                    .flat_map(DocxBlock::paragraphs)
+                    .cloned()
+                    .collect(),
+            );
+        }
+        let title_page =
+            w_child(sect_pr, "titlePg").is_some_and(|node| w_val(node).is_none_or(is_on));
+        Ok(DocxHeaderFooter {
+            default: parts.remove("default").unwrap_or_default(),
+            first: title_page.then(|| parts.remove("first").unwrap_or_default()),
+            even: even_pages.then(|| parts.remove("even").unwrap_or_default()),
+        })
+    }
+
+    /// Paragraphs and tables of a block-level container (body, table cell,
+    /// content control) in document order.
+    fn read_container(&mut self, container: Node, blocks: &mut Vec<DocxBlock>) {
+        for node in container.children().filter(|node| is_w(node)) {
+            match node.tag_name().name() {
+                "p" => blocks.extend(self.read_paragraph(node).map(DocxBlock::Paragraph)),
+                "tbl" => {
+                    let table = self.read_table(node);
+                    if !table.cells.is_empty() {
+                        blocks.push(DocxBlock::Table(table));
+                    }
+                }
+                "sdt" => {
+                    if let Some(content) = w_child(node, "sdtContent") {
+                        self.read_container(content, blocks);
+                    }
+                }
+                "customXml" => self.read_container(node, blocks),
+                _ => {}
+            }
+        }
+    }
+
+    /// Rows of cells placed on the table grid. `w:gridBefore` skips grid
+    /// columns, `w:gridSpan` widens a cell, and a `w:vMerge` cell without
+    /// `restart` extends the cell above it down a row. Nested tables are
+    /// flattened into their cell's paragraphs.
+    fn read_table(&mut self, table: Node) -> DocxTable {
+        let header_first_row = w_child(table, "tblPr")
+            .and_then(|tbl_pr| w_child(tbl_pr, "tblLook"))
+            .and_then(|look| look.attribute((W_NS, "firstRow")))
+            .is_some_and(is_on);
+        let mut cells: Vec<DocxCell> = Vec::new();
+        // Cell still open for vertical merging, by grid column.
+        let mut open: HashMap<usize, usize> = HashMap::new();
+        let rows: Vec<Node> = table
+            .children()
+            .filter(|node| node.has_tag_name((W_NS, "tr")))
+            .collect();
+        // Spans stay inside the grid, so a huge `w:gridSpan` cannot push
+        // columns past what the table declares.
+        let grid_cols = w_child(table, "tblGrid").map_or(0, |grid| {
+            grid.children()
+                .filter(|node| node.has_tag_name((W_NS, "gridCol")))
+                .count()
+        });
+        let widest_row = rows
+            .iter()
+            .map(|tr| {
+                tr.children()
+                    .filter(|node| node.has_tag_name((W_NS, "tc")))
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        let width = grid_cols.max(widest_row);
+        for (row, tr) in rows.iter().enumerate() {
+            let tr_pr = w_child(*tr, "trPr");
+            let header = tr_pr
+                .and_then(|tr_pr| w_child(tr_pr, "tblHeader"))
+                .is_some_and(|node| w_val(node).is_none_or(is_on))
+                || (row == 0 && header_first_row);
+            let mut col = tr_pr
+                .and_then(|tr_pr| w_child(tr_pr, "gridBefore"))
+                .and_then(w_val)
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(0)
+                .min(width);
+            for tc in tr.children().filter(|node| node.has_tag_name((W_NS, "tc"))) {
+                let tc_pr = w_child(tc, "tcPr");
+                let colspan = tc_pr
+                    .and_then(|tc_pr| w_child(tc_pr, "gridSpan"))
+                    .and_then(w_val)
+                    .and_then(|span| span.parse::<usize>().ok())
+                    .unwrap_or(1)
+                    .clamp(1, width.saturating_sub(col).max(1));
+                let merge = tc_pr.and_then(|tc_pr| w_child(tc_pr, "vMerge"));
+                let restart = merge.and_then(w_val) == Some("restart");
+
+                let mut content = Vec::new();
+                self.read_container(tc, &mut content);
+                let paragraphs: Vec<DocxParagraph> = content
+                    .iter()
+                    .flat_map(DocxBlock::paragraphs)
+                    .cloned()
+                    .collect();
+
+                match open.get(&col) {
+                    Some(&above) if merge.is_some() && !restart => {
+                        let cell = &mut cells[above];
+                        cell.rowspan = row - cell.row + 1;
+                        cell.paragraphs.extend(paragraphs);
+                    }
+                    _ => {
+                        if restart {
+                            open.insert(col, cells.len());
+                        } else {
+                            open.remove(&col);
+                        }
+                        cells.push(DocxCell {
+                            row,
+                            col,
+                            rowspan: 1,
+                            colspan,
+                            header,
+                            paragraphs,
+                        });
+                    }
+                }
+                col = col.saturating_add(colspan);
+            }
+        }
+        DocxTable {
+            rows: rows.len(),
+            cells,
+        }
+    }
+
+    fn read_paragraph(&mut self, paragraph: Node) -> Option<DocxParagraph> {
+        let properties = w_child(paragraph, "pPr");
+        let style_id = properties
+            .and_then(|ppr| w_child(ppr, "pStyle"))
+            .and_then(w_val)
+            .or(self.styles.default_paragraph.as_deref());
+
+        let mut base = self.styles.defaults.clone();
+        if let Some(style_id) = style_id {
+            base.apply(&self.styles.run_properties(style_id));
+        }
+
+        let mut lines = vec![Vec::new()];
+        self.read_runs(paragraph, &base, None, None, &mut lines);
+        lines.retain(|runs: &Vec<DocxRun>| !runs.is_empty());
+        if lines.iter().flatten().all(|run| run.text.trim().is_empty()) {
+            return None;
+        }
+
+        let outline_level = properties
+            .and_then(outline_level)
+            .or_else(|| style_id.and_then(|id| self.styles.outline_level(id)))
+            .filter(|&level| level > 0);
+        let title = style_id.is_some_and(|id| self.styles.is_title(id));
+        let list = properties
+            .and_then(|ppr| w_child(ppr, "numPr"))
+            .map(ListRef::parse)
+            .or_else(|| style_id.and_then(|id| self.styles.list(id)));
+        let list_label = list.and_then(|list| self.list_label(&list));
+        let bookmarks = paragraph
+            .descendants()
+            .filter(|node| is_w(node) && node.tag_name().name() == "bookmarkStart")
+            .filter_map(|node| node.attribute((W_NS, "name")))
+            .map(str::to_string)
+            .collect();
+
+        Some(DocxParagraph {
+            lines,
+            outline_level,
+            title,
+            list_label,
+            bookmarks,
+        })
+    }
+
+    /// Runs under `node`, descending into hyperlinks, fields and content
+    /// controls, and into tracked insertions and deletions as the revision
+    /// mode says. Field instructions, drawings and hidden runs are left out.
+    fn read_runs(
+        &mut self,
+        node: Node,
+        base: &RunProperties,
+        link: Option<&LinkTarget>,
+        revision: Option<&Revision>,
+        lines: &mut Vec<Vec<DocxRun>>,
+    ) {
+        for child in node.children().filter(|node| is_w(node)) {
+            match child.tag_name().name() {
+                "r" => self.read_run(child, base, link, revision, lines),
+                "hyperlink" => {
+                    let target = self.hyperlink_target(child);
+                    self.read_runs(child, base, target.as_ref().or(link), revision, lines);
+                }
+                "smartTag" | "fldSimple" | "customXml" => {
+                    self.read_runs(child, base, link, revision, lines)
+                }
+                name @ ("ins" | "moveTo" | "del" | "moveFrom") => {
+                    let kind = match name {
+                        "ins" | "moveTo" => RevisionKind::Insertion,
+                        _ => RevisionKind::Deletion,
+                    };
+                    match (self.revisions, kind) {
+                        (RevisionMode::Accepted, RevisionKind::Insertion)
+                        | (RevisionMode::Original, RevisionKind::Deletion) => {
+                            self.read_runs(child, base, link, revision, lines)
+                        }
+                        (RevisionMode::Annotated, _) => {
+                            let change = Revision {
+                                kind,
+                                author: child.attribute((W_NS, "author")).map(str::to_string),
+                                date: child.attribute((W_NS, "date")).map(str::to_string),
+                            };
+                            self.read_runs(child, base, link, Some(&change), lines)
+                        }
+                        _ => {}
+                    }
+                }
+                "sdt" => {
+                    if let Some(content) = w_child(child, "sdtContent") {
+                        self.read_runs(content, base, link, revision, lines);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn read_run(
+        &mut self,
+        run: Node,
+        base: &RunProperties,
+        link: Option<&LinkTarget>,
+        revision: Option<&Revision>,
+        lines: &mut Vec<Vec<DocxRun>>,
+    ) {
+        let mut properties = base.clone();
+        if let Some(rpr) = w_child(run, "rPr") {
+            if let Some(style_id) = w_child(rpr, "rStyle").and_then(w_val) {
+                properties.apply(&self.styles.run_properties(style_id));
+            }
+            properties.apply(&RunProperties::parse(rpr));
+        }
+        if properties.hidden == Some(true) {
+            return;
+        }
+
+        let mut text = String::new();
+        let flush = |text: &mut String, lines: &mut Vec<Vec<DocxRun>>| {
+            if !text.is_empty() {
+                lines
+                    .last_mut()
+                    .expect("at least one line")
+                    .push(properties.run(std::mem::take(text), link, revision));
+            }
+        };
+        for child in run.children().filter(|node| is_w(node)) {
+            match child.tag_name().name() {
+                // Deleted text is only reached inside a deletion being read.
+                "t" | "delText" => text.push_str(child.text().unwrap_or_default()),
+                "tab" => text.push('\t'),
+                "noBreakHyphen" => text.push('-'),
+                "br" | "cr" => {
+                    flush(&mut text, lines);
+                    lines.push(Vec::new());
+                }
+                "footnoteReference" | "endnoteReference" | "commentReference" => {
+                    let Some(note) = note_ref(child) else {
+                        continue;
+                    };
+                    let custom = child
+                        .attribute((W_NS, "customMarkFollows"))
+                        .is_some_and(is_on);
+                    let mark = self.reference_mark(&note, custom);
+                    flush(&mut text, lines);
+                    let link = LinkTarget::Note(note);
+                    lines
+                        .last_mut()
+                        .expect("at least one line")
+                        .push(properties.run(mark, Some(&link), revision));
+                }
+                "footnoteRef" | "endnoteRef" | "annotationRef" => {
+                    let Some(note) = self.current_note.clone() else {
+                        continue;
+                    };
+                    let mark = self.marks.get(&note).cloned().unwrap_or_default();
+                    flush(&mut text, lines);
+                    let link = LinkTarget::Note(note);
+                    lines
+                        .last_mut()
+                        .expect("at least one line")
+                        .push(properties.run(mark, Some(&link), revision));
+                }
+                _ => {}
+            }
+        }
+        flush(&mut text, lines);
+    }
+
+    /// Text of a note's reference mark. Footnotes count 1, 2, 3 and
+    /// endnotes i, ii, iii in order of first reference, as Word numbers
+    /// them by default; comments and custom marks have none of their own.
+    fn reference_mark(&mut self, note: &NoteRef, custom: bool) -> String {
+        if let Some(mark) = self.marks.get(note) {
+            return mark.clone();
+        }
+        let count = self
+            .referenced
+            .iter()
+            .filter(|other| other.kind == note.kind)
+            .count() as u32
+            + 1;
+        let mark = match note.kind {
+            _ if custom => String::new(),
+            NoteKind::Footnote => count.to_string(),
+            NoteKind::Endnote => roman(count).to_lowercase(),
+            NoteKind::Comment => String::new(),
+        };
+        self.referenced.push(note.clone());
+        self.marks.insert(note.clone(), mark.clone());
+        mark
+    }
+
+    fn hyperlink_target(&self, hyperlink: Node) -> Option<LinkTarget> {
+        let target = hyperlink
+            .attribute((RELATIONSHIPS_NS, "id"))
+            .and_then(|id| self.relationships.get(id));
+        let anchor = hyperlink.attribute((W_NS, "anchor"));
+        match (target, anchor) {
+            (Some(target), Some(anchor)) => Some(LinkTarget::Uri(format!("{target}#{anchor}"))),
+            (Some(target), None) => Some(LinkTarget::Uri(target.clone())),
+            // Stands for the bookmark until the parser knows its page.
+            (None, Some(anchor)) => Some(LinkTarget::Uri(format!("#{anchor}"))),
+            (None, None) => None,
+        }
+    }
+
+    /// Advance the list's counter at the paragraph's level and render its
+    /// label. Deeper levels restart after it.
+    fn list_label(&mut self, list: &ListRef) -> Option<String> {
+        let levels = self.numbering.levels(&list.num_id)?;
+        let level = levels.get(list.level)?;
+        let counters = self.counters.entry(list.num_id.clone()).or_default();
+        let current = counters[list.level].map_or(level.start, |count| count.saturating_add(1));
+        counters[list.level] = Some(current);
+        for deeper in counters.iter_mut().skip(list.level + 1) {
+            *deeper = None;
+        }
+
+        if level.format == "bullet" {
+            return Some("•".to_string());
+        }
+        let mut label = level.text.clone();
+        for (idx, other) in levels.iter().enumerate().take(list.level + 1) {
+            let count = counters[idx].unwrap_or(other.start);
+            label = label.replace(
+                &format!("%{}", idx + 1),
+                &format_number(count, &other.format),
+            );
+        }
+        let label = label.trim().to_string();
+        (!label.is_empty()).then_some(label)
+    }
+}
+
+/// Run formatting where set; unset fields inherit.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RunProperties {
+    bold: Option<bool>,
+    italic: Option<bool>,
+    size: Option<f32>,
+    font: Option<String>,
+    hidden: Option<bool>,
+}
+
+impl RunProperties {
+    fn parse(rpr: Node) -> Self {
+        let toggle = |name: &str| w_child(rpr, name).map(|node| w_val(node).is_none_or(is_on));
+        let font = w_child(rpr, "rFonts").and_then(|fonts| {
+            ["ascii", "hAnsi", "eastAsia", "cs"]
+                .iter()
+                .find_map(|attr| fonts.attribute((W_NS, *attr)))
+                .map(str::to_string)
+        });
+        Self {
+            bold: toggle("b"),
+            italic: toggle("i"),
+            size: w_child(rpr, "sz")
+                .and_then(w_val)
+                .and_then(|half_points| half_points.parse::<f32>().ok())
+                .map(|half_points| half_points / 2.0),
+            font,
+            hidden: toggle("vanish"),
+        }
+    }
+
+    fn apply(&mut self, other: &RunProperties) {
+        self.bold = other.bold.or(self.bold);
+        self.italic = other.italic.or(self.italic);
+        self.size = other.size.or(self.size);
+        self.font = other.font.clone().or(self.font.take());
+        self.hidden = other.hidden.or(self.hidden);
+    }
+
+    fn run(&self, text: String, link: Option<&LinkTarget>, revision: Option<&Revision>) -> DocxRun {
+        DocxRun {
+            text,
+            bold: self.bold.unwrap_or(false),
+            italic: self.italic.unwrap_or(false),
+            size: self.size,
+            font: self.font.clone(),
+            link: link.cloned(),
+            revision: revision.cloned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Style {
+    based_on: Option<String>,
+    /// Lowercased display name.
+    name: String,
+    run: RunProperties,
+    outline_level: Option<usize>,
+    list: Option<ListRef>,
+}
+
+/// `word/styles.xml`: document defaults and styles by id.
+#[derive(Debug, Clone, Default)]
+struct Styles {
+    defaults: RunProperties,
+    default_paragraph: Option<String>,
+    styles: HashMap<String, Style>,
+}
+
+impl Styles {
+    fn parse(xml: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(xml).context("failed to parse word/styles.xml")?;
+        let root = doc.root_element();
+        let defaults = w_child(root, "docDefaults")
+            .and_then(|defaults| w_child(defaults, "rPrDefault"))
+            .and_then(|default| w_child(default, "rPr"))
+            .map(RunProperties::parse)
+            .unwrap_or_default();
+
+        let mut styles = HashMap::new();
+        let mut default_paragraph = None;
+        for node in root
+            .children()
+            .filter(|node| node.has_tag_name((W_NS, "style")))
+        {
+            let Some(id) = node.attribute((W_NS, "styleId")) else {
+                continue;
+            };
+            if node.attribute((W_NS, "type")) == Some("paragraph")
+                && node.attribute((W_NS, "default")).is_some_and(is_on)
+            {
+                default_paragraph = Some(id.to_string());
+            }
+            let ppr = w_child(node, "pPr");
+            let name = w_child(node, "name")
+                .and_then(w_val)
+                .unwrap_or_default()
+                .to_lowercase();
+            let outline_level = ppr.and_then(outline_level).or_else(|| {
+                name.strip_prefix("heading ")
+                    .and_then(|level| level.trim().parse().ok())
+            });
+            styles.insert(
+                id.to_string(),
+                Style {
+                    based_on: w_child(node, "basedOn").and_then(w_val).map(str::to_string),
+                    name,
+                    run: w_child(node, "rPr")
+                        .map(RunProperties::parse)
+                        .unwrap_or_default(),
+                    outline_level,
+                    list: ppr
+                        .and_then(|ppr| w_child(ppr, "numPr"))
+                        .map(ListRef::parse),
+                },
+            );
+        }
+        Ok(Self {
+            defaults,
+            default_paragraph,
+            styles,
+        })
+    }
+
+    /// The style and its ancestors, nearest first.
+    fn chain<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Style> + 'a {
+        std::iter::successors(self.styles.get(id), |style| {
+            style
+                .based_on
+                .as_deref()
+                .and_then(|parent| self.styles.get(parent))
+        })
+        .take(MAX_STYLE_DEPTH)
+    }
+
+    fn run_properties(&self, id: &str) -> RunProperties {
+        let chain: Vec<&Style> = self.chain(id).collect();
+        let mut properties = RunProperties::default();
+        for style in chain.iter().rev() {
+            properties.apply(&style.run);
+        }
+        properties
+    }
+
+    fn outline_level(&self, id: &str) -> Option<usize> {
+        self.chain(id).find_map(|style| style.outline_level)
+    }
+
+    fn is_title(&self, id: &str) -> bool {
+        self.chain(id).any(|style| style.name == "title")
+    }
+
+    fn list(&self, id: &str) -> Option<ListRef> {
+        self.chain(id).find_map(|style| style.list.clone())
+    }
+}
+
+/// A paragraph's place in a list: `w:numPr`.
+#[derive(Debug, Clone, PartialEq)]
+struct ListRef {
+    num_id: String,
+    level: usize,
+}
+
+impl ListRef {
+    fn parse(num_pr: Node) -> Self {
+        Self {
+            num_id: w_child(num_pr, "numId")
+                .and_then(w_val)
+                .unwrap_or("0")
+                .to_string(),
+            level: w_child(num_pr, "ilvl")
+                .and_then(w_val)
+                .and_then(|level| level.parse().ok())
+                .unwrap_or(0)
+                .min(8),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ListLevel {
+    start: u32,
+    format: String,
+    text: String,
+}
+
+/// `word/numbering.xml`: the levels of each list instance, with start
+/// overrides applied.
+#[derive(Debug, Clone, Default)]
+struct Numbering {
+    lists: HashMap<String, Vec<ListLevel>>,
+}
+
+impl Numbering {
+    fn parse(xml: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(xml).context("failed to parse word/numbering.xml")?;
+        let root = doc.root_element();
+        let abstracts: HashMap<&str, Vec<ListLevel>> = root
+            .children()
+            .filter(|node| node.has_tag_name((W_NS, "abstractNum")))
+            .filter_map(|node| {
+                let id = node.attribute((W_NS, "abstractNumId"))?;
+                let mut levels = vec![
+                    ListLevel {
+                        start: 1,
+                        format: "decimal".to_string(),
+                        text: String::new(),
+                    };
+                    9
+                ];
+                for lvl in node.children().filter(|n| n.has_tag_name((W_NS, "lvl"))) {
+                    let Some(slot) = lvl
+                        .attribute((W_NS, "ilvl"))
+                        .and_then(|ilvl| ilvl.parse::<usize>().ok())
+                        .and_then(|ilvl| levels.get_mut(ilvl))
+                    else {
+                        continue;
+                    };
+                    let val = |name: &str| w_child(lvl, name).and_then(w_val);
+                    *slot = ListLevel {
+                        start: val("start").and_then(|v| v.parse().ok()).unwrap_or(1),
+                        format: val("numFmt").unwrap_or("decimal").to_string(),
+                        text: val("lvlText").unwrap_or_default().to_string(),
+                    };
+                }
+                Some((id, levels))
+            })
+            .collect();
+
+        let lists = root
+            .children()
+            .filter(|node| node.has_tag_name((W_NS, "num")))
+            .filter_map(|num| {
+                let id = num.attribute((W_NS, "numId"))?;
+                let abstract_id = w_child(num, "abstractNumId").and_then(w_val)?;
+                let mut levels = abstracts.get(abstract_id)?.clone();
+                for change in num
+                    .children()
+                    .filter(|n| n.has_tag_name((W_NS, "lvlOverride")))
+                {
+                    let start = w_child(change, "startOverride")
+                        .and_then(w_val)
+                        .and_then(|v| v.parse().ok());
+                    let slot = change
+                        .attribute((W_NS, "ilvl"))
+                        .and_then(|ilvl| ilvl.parse::<usize>().ok())
+                        .and_then(|ilvl| levels.get_mut(ilvl));
+                    if let (Some(start), Some(slot)) = (start, slot) {
+                        slot.start = start;
+                    }
+                }
+                Some((id.to_string(), levels))
+            })
+            .collect();
+        Ok(Self { lists })
+    }
+
+    /// Levels of list `num_id`; `0` removes numbering.
+    fn levels(&self, num_id: &str) -> Option<&[ListLevel]> {
+        self.lists.get(num_id).map(Vec::as_slice)
+    }
+}
+
+/// `count` in a `w:numFmt` format; unknown formats fall back to decimal.
+fn format_number(count: u32, format: &str) -> String {
+    match format {
+        "lowerLetter" => letters(count).to_lowercase(),
+        "upperLetter" => letters(count),
+        "lowerRoman" => roman(count).to_lowercase(),
+        "upperRoman" => roman(count),
+        "decimalZero" => format!("{count:02}"),
+        "none" => String::new(),
+        _ => count.to_string(),
+    }
+}
+
+/// Letter and Roman labels past this count fall back to decimal; they
+/// would grow with the count, and `w:start` can be anything.
+const MAX_ALPHABETIC_COUNT: u32 = 3999;
+
+/// A, B, …, Z, AA, BB, … as Word counts letters.
+fn letters(count: u32) -> String {
+    if count > MAX_ALPHABETIC_COUNT {
+        return count.to_string();
+    }
+    let count = count.max(1) - 1;
+    let letter = char::from(b'A' + (count % 26) as u8);
+    letter.to_string().repeat(count as usize / 26 + 1)
+}
+
+fn roman(mut count: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    if count > MAX_ALPHABETIC_COUNT {
+        return count.to_string();
+    }
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while count >= value {
+            out.push_str(numeral);
+            count -= value;
+        }
+    }
+    out
+}
+
+/// `w:outlineLvl` of paragraph properties, 1-based; level 9 (body text)
+/// reads as 0.
+fn outline_level(ppr: Node) -> Option<usize> {
+    let level: usize = w_child(ppr, "outlineLvl").and_then(w_val)?.parse().ok()?;
+    Some(if level < 9 { level + 1 } else { 0 })
+}
+
+/// The note a `w:footnoteReference`, `w:endnoteReference` or
+/// `w:commentReference` points at.
+fn note_ref(reference: Node) -> Option<NoteRef> {
+    let kind = match reference.tag_name().name() {
+        "footnoteReference" => NoteKind::Footnote,
+        "endnoteReference" => NoteKind::Endnote,
+        _ => NoteKind::Comment,
+    };
+    Some(NoteRef {
+        kind,
+        id: reference.attribute((W_NS, "id"))?.to_string(),
+    })
+}
+
+fn is_w(node: &Node) -> bool {
+    node.is_element() && node.tag_name().namespace() == Some(W_NS)
+}
+
+fn w_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.has_tag_name((W_NS, name)))
+}
+
+fn w_val<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.attribute((W_NS, "val"))
+}
+
+/// OOXML on/off values.
+fn is_on(value: &str) -> bool {
+    !matches!(value, "0" | "false" | "off")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_headings_lists_and_formatting() -> Result<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/test_office.docx");
+        let document = DocxDocument::open(&path, RevisionMode::Accepted)?;
+        let find = |text: &str| {
+            document
+                .blocks
+                .iter()
+                .flat_map(DocxBlock::paragraphs)
+                .find(|paragraph| paragraph.text().starts_with(text))
+                .unwrap_or_else(|| panic!("no paragraph {text}"))
+        };
+
+        assert_eq!(find("DocStruct Rich").role(), TextRole::Heading(1));
+        assert_eq!(find("문서 개요").outline_level, Some(1));
+        assert_eq!(find("인라인 수식").role(), TextRole::Heading(2));
+
+        let bullet = find("특수문자");
+        assert_eq!(bullet.role(), TextRole::ListItem);
+        assert_eq!(bullet.list_label.as_deref(), Some("•"));
+
+        let intro = find("이 문서는");
+        assert_eq!(intro.role(), TextRole::Paragraph);
+        let bold: Vec<&str> = intro.lines[0]
+            .iter()
+            .filter(|run| run.bold)
+            .map(|run| run.text.as_str())
+            .collect();
+        assert_eq!(bold, vec!["DOCX/PPT/PPTX 파서 검증"]);
+
+        // Line breaks split the code block into lines.
+        assert_eq!(find("def area_circle").lines.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn numbers_list_levels() -> Result<()> {
+        let numbering = Numbering::parse(&format!(
+            r#"<w:numbering xmlns:w="{W_NS}">
+              <w:abstractNum w:abstractNumId="1">
+                <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl>
+                <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="lowerLetter"/><w:lvlText w:val="%1.%2)"/></w:lvl>
+              </w:abstractNum>
+              <w:num w:numId="5"><w:abstractNumId w:val="1"/>
+                <w:lvlOverride w:ilvl="0"><w:startOverride w:val="3"/></w:lvlOverride>
+              </w:num>
+              <w:num w:numId="6"><w:abstractNumId w:val="1"/>
+                <w:lvlOverride w:ilvl="1"><w:startOverride w:val="4294967295"/></w:lvlOverride>
+              </w:num>
+            </w:numbering>"#
+        ))?;
+        let styles = Styles::default();
+        let relationships = HashMap::new();
+        let mut reader =
+            BodyReader::new(&styles, &numbering, &relationships, RevisionMode::Accepted);
+        let mut label = |num_id: &str, level| {
+            reader.list_label(&ListRef {
+                num_id: num_id.to_string(),
+                level,
+            })
+        };
+
+        let labels: Vec<Option<String>> = [0, 1, 1, 0, 1]
+            .into_iter()
+            .map(|level| label("5", level))
+            .collect();
+        assert_eq!(
+            labels,
+            ["3.", "3.a)", "3.b)", "4.", "4.a)"]
+                .map(|label| Some(label.to_string()))
+                .to_vec()
+        );
+        // A start at the top of the range neither overflows nor spells the
+        // count out in letters.
+        let huge = format!("1.{})", u32::MAX);
+        assert_eq!(label("6", 0).as_deref(), Some("1."));
+        assert_eq!(label("6", 1).as_deref(), Some(huge.as_str()));
+        assert_eq!(label("6", 1).as_deref(), Some(huge.as_str()));
+        assert_eq!(roman(1994), "MCMXCIV");
+        assert_eq!(letters(28), "BB");
+        assert_eq!(letters(u32::MAX), u32::MAX.to_string());
+        assert_eq!(roman(4_000_000_000), "4000000000");
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_table_spans_inside_the_grid() -> Result<()> {
+        let xml = format!(
+            r#"<w:tbl xmlns:w="{W_NS}">
+              <w:tblGrid><w:gridCol/><w:gridCol/><w:gridCol/></w:tblGrid>
+              <w:tr>
+                <w:trPr><w:gridBefore w:val="18446744073709551615"/></w:trPr>
+                <w:tc><w:p><w:r><w:t>Late</w:t></w:r></w:p></w:tc>
+              </w:tr>
+              <w:tr>
+                <w:tc><w:tcPr><w:gridSpan w:val="4000000000"/></w:tcPr><w:p><w:r><w:t>Wide</w:t></w:r></w:p></w:tc>
+                <w:tc><w:tcPr><w:gridSpan w:val="18446744073709551615"/></w:tcPr><w:p><w:r><w:t>Wider</w:t></w:r></w:p></w:tc>
+              </w:tr>
+            </w:tbl>"#
+        );
+        let doc = roxmltree::Document::parse(&xml)?;
+        let (styles, numbering, relationships) =
+            (Styles::default(), Numbering::default(), HashMap::new());
+        let mut reader =
+            BodyReader::new(&styles, &numbering, &relationships, RevisionMode::Accepted);
+
+        let table = reader.read_table(doc.root_element());
+
+        let grid: Vec<(usize, usize, usize)> = table
+            .cells
+            .iter()
+            .map(|cell| (cell.row, cell.col, cell.colspan))
+            .collect();
+        assert_eq!(grid, vec![(0, 3, 1), (1, 0, 3), (1, 3, 1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_table_spans() -> Result<()> {
+        let xml = format!(
+            r#"<w:tbl xmlns:w="{W_NS}">
+              <w:tblPr><w:tblLook w:firstRow="1"/></w:tblPr>
+              <w:tr>
+                <w:tc><w:tcPr><w:gridSpan w:val="2"/></w:tcPr><w:p><w:r><w:t>Party</w:t></w:r></w:p></w:tc>
+                <w:tc><w:p><w:r><w:t>Term</w:t></w:r></w:p></w:tc>
+              </w:tr>
+              <w:tr>
+                <w:tc><w:tcPr><w:vMerge w:val="restart"/></w:tcPr><w:p><w:r><w:t>Seller</w:t></w:r></w:p></w:tc>
+                <w:tc><w:p><w:r><w:t>Acme</w:t></w:r></w:p></w:tc>
+                <w:tc><w:p><w:r><w:t>1 year</w:t></w:r></w:p></w:tc>
+              </w:tr>
+              <w:tr>
+                <w:tc><w:tcPr><w:vMerge/></w:tcPr><w:p/></w:tc>
+                <w:tc><w:p><w:r><w:t>Globex</w:t></w:r></w:p></w:tc>
+                <w:tc><w:p><w:r><w:t>2 years</w:t></w:r></w:p></w:tc>
+              </w:tr>
+            </w:tbl>"#
+        );
+        let doc = roxmltree::Document::parse(&xml)?;
+        let (styles, numbering, relationships) =
+            (Styles::default(), Numbering::default(), HashMap::new());
+        let mut reader =
+            BodyReader::new(&styles, &numbering, &relationships, RevisionMode::Accepted);
+
+        let table = reader.read_table(doc.root_element());
+
+        assert_eq!(table.rows, 3);
+        let grid: Vec<(usize, usize, usize, usize, bool, String)> = table
+            .cells
+            .iter()
+            .map(|cell| {
+                let text = cell.paragraphs.iter().map(DocxParagraph::text).collect();
+                (
+                    cell.row,
+                    cell.col,
+                    cell.rowspan,
+                    cell.colspan,
+                    cell.header,
+                    text,
+                )
+            })
+            .collect();
+        assert_eq!(
+            grid,
+            vec![
+                (0, 0, 1, 2, true, "Party".to_string()),
+                (0, 2, 1, 1, true, "Term".to_string()),
+                (1, 0, 2, 1, false, "Seller".to_string()),
+                (1, 1, 1, 1, false, "Acme".to_string()),
+                (1, 2, 1, 1, false, "1 year".to_string()),
+                (2, 1, 1, 1, false, "Globex".to_string()),
+                (2, 2, 1, 1, false, "2 years".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_tracked_changes_in_each_mode() -> Result<()> {
+        let xml = format!(
+            r#"<w:p xmlns:w="{W_NS}">
+              <w:r><w:t xml:space="preserve">Payment within </w:t></w:r>
+              <w:del w:id="1" w:author="Kim" w:date="2024-03-01T10:00:00Z">
+                <w:r><w:delText>30</w:delText></w:r>
+              </w:del>
+              <w:ins w:id="2" w:author="Lee">
+                <w:r><w:t>45</w:t></w:r>
+              </w:ins>
+              <w:r><w:t xml:space="preserve"> days</w:t></w:r>
+            </w:p>"#
+        );
+        let doc = roxmltree::Document::parse(&xml)?;
+        let (styles, numbering, relationships) =
+            (Styles::default(), Numbering::default(), HashMap::new());
+        let read = |revisions| {
+            BodyReader::new(&styles, &numbering, &relationships, revisions)
+                .read_paragraph(doc.root_element())
+                .expect("paragraph has text")
+        };
+
+        assert_eq!(
+            read(RevisionMode::Accepted).text(),
+            "Payment within 45 days"
+        );
+        assert_eq!(
+            read(RevisionMode::Original).text(),
+            "Payment within 30 days"
+        );
+
+        let annotated = read(RevisionMode::Annotated);
+        assert_eq!(annotated.text(), "Payment within 3045 days");
+        let changes: Vec<(&str, Option<Revision>)> = annotated.lines[0]
+            .iter()
+            .map(|run| (run.text.as_str(), run.revision.clone()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("Payment within ", None),
+                (
+                    "30",
+                    Some(Revision {
+                        kind: RevisionKind::Deletion,
+                        author: Some("Kim".to_string()),
+                        date: Some("2024-03-01T10:00:00Z".to_string()),
+                    })
+                ),
+                (
+                    "45",
+                    Some(Revision {
+                        kind: RevisionKind::Insertion,
+                        author: Some("Lee".to_string()),
+                        date: None,
+                    })
+                ),
+                (" days", None),
+            ]
+        );
+        Ok(())
+    }
+
+    /// A ZIP archive of stored (uncompressed) parts.
+    fn stored_zip(parts: &[(&str, String)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        for (name, content) in parts {
+            let offset = data.len() as u32;
+            let (name, content) = (name.as_bytes(), content.as_bytes());
+            let size = content.len() as u32;
+            data.extend(0x0403_4b50u32.to_le_bytes());
+            data.extend([0u8; 14]);
+            data.extend(size.to_le_bytes());
+            data.extend(size.to_le_bytes());
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend(0u16.to_le_bytes());
+            data.extend(name);
+            data.extend(content);
+
+            directory.extend(0x0201_4b50u32.to_le_bytes());
+            directory.extend([0u8; 16]);
+            directory.extend(size.to_le_bytes());
+            directory.extend(size.to_le_bytes());
+            directory.extend((name.len() as u16).to_le_bytes());
+            directory.extend([0u8; 12]);
+            directory.extend(offset.to_le_bytes());
+            directory.extend(name);
+        }
+        let directory_offset = data.len() as u32;
+        data.extend(&directory);
+        data.extend(0x0605_4b50u32.to_le_bytes());
+        data.extend([0u8; 4]);
+        data.extend((parts.len() as u16).to_le_bytes());
+        data.extend((parts.len() as u16).to_le_bytes());
+        data.extend((directory.len() as u32).to_le_bytes());
+        data.extend(directory_offset.to_le_bytes());
+        data.extend(0u16.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn reads_notes_and_headers_footers() -> Result<()> {
+        let part = |root: &str, body: &str| {
+            format!(r#"<w:{root} xmlns:w="{W_NS}" xmlns:r="{RELATIONSHIPS_NS}">{body}</w:{root}>"#)
+        };
+        let paragraph = |text: &str| format!("<w:p><w:r><w:t>{text}</w:t></w:r></w:p>");
+        let package = Package::from_bytes(stored_zip(&[
+            (
+                "word/document.xml",
+                part(
+                    "document",
+                    r#"<w:body>
+                      <w:p><w:r><w:t>Governed by law</w:t></w:r><w:r><w:footnoteReference w:id="2"/></w:r><w:r><w:t xml:space="preserve"> and equity</w:t></w:r><w:r><w:commentReference w:id="0"/></w:r></w:p>
+                      <w:p><w:r><w:t>Schedule</w:t></w:r><w:r><w:endnoteReference w:id="1"/></w:r></w:p>
+                      <w:sectPr>
+                        <w:headerReference w:type="default" r:id="rId1"/>
+                        <w:headerReference w:type="first" r:id="rId2"/>
+                        <w:footerReference w:type="default" r:id="rId3"/>
+                        <w:titlePg/>
+                      </w:sectPr>
+                    </w:body>"#,
+                ),
+            ),
+            (
+                "word/_rels/document.xml.rels",
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+                  <Relationship Id="rId1" Target="header1.xml"/>
+                  <Relationship Id="rId2" Target="header2.xml"/>
+                  <Relationship Id="rId3" Target="footer1.xml"/>
+                </Relationships>"#
+                    .to_string(),
+            ),
+            (
+                "word/footnotes.xml",
+                part(
+                    "footnotes",
+                    r#"<w:footnote w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>
+                    <w:footnote w:id="2"><w:p><w:r><w:footnoteRef/></w:r><w:r><w:t xml:space="preserve"> See the Act.</w:t></w:r></w:p></w:footnote>"#,
+                ),
+            ),
+            (
+                "word/endnotes.xml",
+                part(
+                    "endnotes",
+                    r#"<w:endnote w:id="1"><w:p><w:r><w:endnoteRef/></w:r><w:r><w:t>Annex A.</w:t></w:r></w:p></w:endnote>"#,
+                ),
+            ),
+            (
+                "word/comments.xml",
+                part(
+                    "comments",
+                    r#"<w:comment w:id="0" w:author="Kim"><w:p><w:r><w:annotationRef/></w:r><w:r><w:t>Which law?</w:t></w:r></w:p></w:comment>"#,
+                ),
+            ),
+            ("word/header1.xml", part("hdr", &paragraph("Confidential"))),
+            ("word/header2.xml", part("hdr", &paragraph("Draft cover"))),
+            ("word/footer1.xml", part("ftr", &paragraph("Footer"))),
+        ]))?;
+
+        let document = DocxDocument::from_package(&package, RevisionMode::Accepted)?;
+
+        let note = |kind, id: &str| {
+            Some(LinkTarget::Note(NoteRef {
+                kind,
+                id: id.to_string(),
+            }))
+        };
+        let DocxBlock::Paragraph(clause) = &document.blocks[0] else {
+            panic!("paragraph expected");
+        };
+        let runs: Vec<(&str, &Option<LinkTarget>)> = clause.lines[0]
+            .iter()
+            .map(|run| (run.text.as_str(), &run.link))
+            .collect();
+        assert_eq!(
+            runs,
+            vec![
+                ("Governed by law", &None),
+                ("1", &note(NoteKind::Footnote, "2")),
+                (" and equity", &None),
+                ("", &note(NoteKind::Comment, "0")),
+            ]
+        );
+
+        let notes: Vec<(Option<LinkTarget>, String)> = document
+            .notes
+            .iter()
+            .map(|docx_note| {
+                let mark = &docx_note.paragraphs[0].lines[0][0];
+                assert_eq!(mark.link, Some(LinkTarget::Note(docx_note.note.clone())));
+                (mark.link.clone(), docx_note.paragraphs[0].text())
+            })
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                (note(NoteKind::Footnote, "2"), "1 See the Act.".to_string()),
+                (note(NoteKind::Endnote, "1"), "iAnnex A.".to_string()),
+                (note(NoteKind::Comment, "0"), "Which law?".to_string()),
+            ]
+        );
+
+        let header_text = |page_idx| document.header.for_page(page_idx)[0].text();
+        assert_eq!(header_text(0), "Draft cover");
+        assert_eq!(header_text(1), "Confidential");
+        assert_eq!(document.footer.for_page(0).len(), 0);
+        assert_eq!(document.footer.for_page(2)[0].text(), "Footer");
+        Ok(())
+    }
+}
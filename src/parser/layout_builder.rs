@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 
+use crate::core::model::RevisionMode;
 use crate::parser::docx_parser::DocxParser;
 use crate::parser::pdf_parser::PdfParser;
 use crate::parser::pptx_parser::PptxParser;
@@ -12,6 +13,22 @@ pub struct ParserLayoutBuilder {
 
 impl ParserLayoutBuilder {
     pub fn new(path: PathBuf) -> Result<Self> {
+        Self::with_password(path, None)
+    }
+
+    /// Like [`ParserLayoutBuilder::new`], with an owner or user password for
+    /// encrypted PDFs. Other formats ignore it.
+    pub fn with_password(path: PathBuf, password: Option<String>) -> Result<Self> {
+        Self::with_options(path, password, RevisionMode::default())
+    }
+
+    /// Like [`ParserLayoutBuilder::with_password`], also choosing how DOCX
+    /// tracked changes are read. Other formats ignore it.
+    pub fn with_options(
+        path: PathBuf,
+        password: Option<String>,
+        revisions: RevisionMode,
+    ) -> Result<Self> {
         let ext = path
             .extension()
             .and_then(|s| s.to_str())
@@ -19,8 +36,8 @@ impl ParserLayoutBuilder {
             .to_ascii_lowercase();
 
         let parser: Box<dyn ParserTrack> = match ext.as_str() {
-            "pdf" => Box::new(PdfParser::new(path)),
-            "docx" => Box::new(DocxParser::new(path)?),
+            "pdf" => Box::new(PdfParser::with_password(path, password)),
+            "docx" => Box::new(DocxParser::with_revisions(path, revisions)?),
             "pptx" => Box::new(PptxParser::new(path)?),
             "ppt" => Box::new(PptxParser::from_ppt(path)?),
             _ => {
@@ -37,12 +54,42 @@ impl ParserTrack for ParserLayoutBuilder {
         self.parser.page_count().context("failed to count pages")
     }
 
+    fn page_size(&self, page_idx: usize) -> Result<crate::core::model::PageSize> {
+        self.parser
+            .page_size(page_idx)
+            .with_context(|| format!("failed to read size of page {}", page_idx + 1))
+    }
+
     fn analyze_page(&self, page_idx: usize) -> Result<crate::core::model::PageHypothesis> {
         self.parser
             .analyze_page(page_idx)
             .with_context(|| format!("parser failed on page {}", page_idx + 1))
     }
 
+    fn page_links(&self, page_idx: usize) -> Result<Vec<crate::core::model::PageLink>> {
+        self.parser
+            .page_links(page_idx)
+            .with_context(|| format!("failed to read links on page {}", page_idx + 1))
+    }
+
+    fn page_images(&self, page_idx: usize) -> Result<Vec<crate::parser::PageImage>> {
+        self.parser
+            .page_images(page_idx)
+            .with_context(|| format!("failed to read images on page {}", page_idx + 1))
+    }
+
+    fn outline(&self) -> Result<Vec<crate::core::model::OutlineEntry>> {
+        self.parser
+            .outline()
+            .context("failed to read document outline")
+    }
+
+    fn metadata(&self) -> Result<crate::core::model::DocumentMetadata> {
+        self.parser
+            .metadata()
+            .context("failed to read document metadata")
+    }
+
     fn supports_ocr_rendering(&self) -> bool {
         self.parser.supports_ocr_rendering()
     }
@@ -1,9 +1,22 @@
+pub mod docx_layout;
 pub mod docx_parser;
+pub mod docx_reader;
 pub mod hangul;
 pub mod layout_builder;
+pub mod ooxml;
+#[cfg(feature = "native-pdf")]
+pub mod pdf_content;
+#[cfg(feature = "native-pdf")]
+pub mod pdf_images;
 pub mod pdf_parser;
 pub mod pdf_reader;
+#[cfg(feature = "native-pdf")]
+pub mod pdf_structure;
+#[cfg(feature = "native-pdf")]
+pub mod pdf_tags;
+pub mod poppler;
 pub mod pptx_parser;
+pub mod tables;
 pub mod text_extractor;
 
 pub use layout_builder::ParserLayoutBuilder;
@@ -11,11 +24,35 @@ pub use layout_builder::ParserLayoutBuilder;
 use anyhow::Result;
 use std::path::Path;
 
-use crate::core::model::PageHypothesis;
+use crate::core::geometry::BBox;
+use crate::core::model::{DocumentMetadata, OutlineEntry, PageHypothesis, PageLink, PageSize};
 
 pub trait ParserTrack {
     fn page_count(&self) -> Result<usize>;
+    /// Size and rotation of a page as displayed. Hypotheses returned by
+    /// [`ParserTrack::analyze_page`] use this space: points, top-left origin.
+    fn page_size(&self, page_idx: usize) -> Result<PageSize>;
     fn analyze_page(&self, page_idx: usize) -> Result<PageHypothesis>;
+    /// Link areas on a page, in the same space as the page's hypothesis.
+    /// Formats whose links are known per run attach them to spans directly.
+    fn page_links(&self, page_idx: usize) -> Result<Vec<PageLink>>;
+    /// Document outline (bookmarks, headings or slide titles) with target
+    /// pages; block targets are resolved later against the fused pages.
+    fn outline(&self) -> Result<Vec<OutlineEntry>>;
+    /// Raster images drawn on a page, placed in the page's hypothesis space.
+    fn page_images(&self, page_idx: usize) -> Result<Vec<PageImage>>;
+    fn metadata(&self) -> Result<DocumentMetadata>;
     fn supports_ocr_rendering(&self) -> bool;
     fn rendering_source_path(&self) -> Option<&Path>;
 }
+
+/// An embedded image and where it is drawn.
+#[derive(Debug, Clone)]
+pub struct PageImage {
+    pub bbox: BBox,
+    /// Encoded image file: the original stream for JPEG and JPEG 2000,
+    /// otherwise the decoded samples as PNG, at the image's own resolution.
+    pub data: Vec<u8>,
+    /// File extension matching `data` (`jpg`, `jp2`, `png`).
+    pub extension: String,
+}
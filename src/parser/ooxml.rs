@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use flate2::read::DeflateDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use crate::core::model::DocumentMetadata;
+
+/// Relationships namespace, for `r:id` attributes in OOXML parts.
+pub const RELATIONSHIPS_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+const CORE_NS: &str = "http://schemas.openxmlformats.org/package/2006/metadata/core-properties";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const DCTERMS_NS: &str = "http://purl.org/dc/terms/";
+const EXTENDED_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/extended-properties";
+const PACKAGE_RELATIONSHIPS_NS: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships";
+
+const LOCAL_HEADER_SIG: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIG: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIR_SIG: u32 = 0x0605_4b50;
+
+/// An OOXML package: the ZIP archive of a DOCX/PPTX file, read into memory.
+/// Parts are stored or deflated; ZIP64 archives are not supported.
+#[derive(Debug, Clone)]
+pub struct Package {
+    data: Vec<u8>,
+    entries: HashMap<String, Entry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    method: u16,
+    compressed_size: usize,
+    size: usize,
+    local_header: usize,
+}
+
+impl Package {
+    pub fn open(path: &Path) -> Result<Self> {
+        let data =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_bytes(data).with_context(|| format!("invalid package {}", path.display()))
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        // The end-of-central-directory record closes the archive, followed
+        // only by a comment of at most 64 KiB.
+        let search_from = data.len().saturating_sub(22 + usize::from(u16::MAX));
+        let end = (search_from..data.len().saturating_sub(21))
+            .rev()
+            .find(|&pos| read_u32(&data, pos) == Some(END_OF_CENTRAL_DIR_SIG))
+            .context("not a ZIP archive")?;
+        let count = read_u16(&data, end + 10).context("truncated ZIP directory")?;
+        let mut pos = read_u32(&data, end + 16).context("truncated ZIP directory")? as usize;
+        if pos == u32::MAX as usize {
+            anyhow::bail!("ZIP64 archives are not supported");
+        }
+
+        let mut entries = HashMap::with_capacity(usize::from(count));
+        for _ in 0..count {
+            if read_u32(&data, pos) != Some(CENTRAL_HEADER_SIG) {
+                anyhow::bail!("corrupt ZIP central directory");
+            }
+            let field = |offset: usize| read_u16(&data, pos + offset).map(usize::from);
+            let wide = |offset: usize| read_u32(&data, pos + offset).map(|v| v as usize);
+            let (Some(method), Some(compressed_size), Some(size), Some(local_header)) =
+                (field(10), wide(20), wide(24), wide(42))
+            else {
+                anyhow::bail!("truncated ZIP central directory");
+            };
+            let (Some(name_len), Some(extra_len), Some(comment_len)) =
+                (field(28), field(30), field(32))
+            else {
+                anyhow::bail!("truncated ZIP central directory");
+            };
+            let name = data
+                .get(pos + 46..pos + 46 + name_len)
+                .context("truncated ZIP central directory")?;
+            entries.insert(
+                String::from_utf8_lossy(name).into_owned(),
+                Entry {
+                    method: method as u16,
+                    compressed_size,
+                    size,
+                    local_header,
+                },
+            );
+            pos += 46 + name_len + extra_len + comment_len;
+        }
+        Ok(Self { data, entries })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Bytes of the part `name`, or `None` when the package lacks it.
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let Some(entry) = self.entries.get(name) else {
+            return Ok(None);
+        };
+        let header = entry.local_header;
+        if read_u32(&self.data, header) != Some(LOCAL_HEADER_SIG) {
+            anyhow::bail!("corrupt ZIP entry {name}");
+        }
+        let name_len = read_u16(&self.data, header + 26).context("truncated ZIP entry")?;
+        let extra_len = read_u16(&self.data, header + 28).context("truncated ZIP entry")?;
+        let start = header + 30 + usize::from(name_len) + usize::from(extra_len);
+        let raw = self
+            .data
+            .get(start..start + entry.compressed_size)
+            .with_context(|| format!("truncated ZIP entry {name}"))?;
+        match entry.method {
+            0 => Ok(Some(raw.to_vec())),
+            8 => {
+                let mut out = Vec::with_capacity(entry.size);
+                DeflateDecoder::new(raw)
+                    .read_to_end(&mut out)
+                    .with_context(|| format!("failed to inflate {name}"))?;
+                Ok(Some(out))
+            }
+            method => anyhow::bail!("unsupported compression method {method} for {name}"),
+        }
+    }
+
+    /// The part `name` as UTF-8 text, or `None` when the package lacks it.
+    pub fn read_string(&self, name: &str) -> Result<Option<String>> {
+        self.read(name)?
+            .map(|bytes| String::from_utf8(bytes).with_context(|| format!("{name} is not UTF-8")))
+            .transpose()
+    }
+
+    /// Relationship targets of a part by id, from its `_rels/*.rels` part.
+    /// Internal targets are resolved against the part's folder.
+    pub fn relationships(&self, part: &str) -> Result<HashMap<String, String>> {
+        let (folder, file) = part.rsplit_once('/').unwrap_or(("", part));
+        let rels_name = if folder.is_empty() {
+            format!("_rels/{file}.rels")
+        } else {
+            format!("{folder}/_rels/{file}.rels")
+        };
+        let Some(xml) = self.read_string(&rels_name)? else {
+            return Ok(HashMap::new());
+        };
+        let doc = roxmltree::Document::parse(&xml)
+            .with_context(|| format!("failed to parse {rels_name}"))?;
+        Ok(doc
+            .root_element()
+            .children()
+            .filter(|node| node.has_tag_name((PACKAGE_RELATIONSHIPS_NS, "Relationship")))
+            .filter_map(|rel| {
+                let id = rel.attribute("Id")?;
+                let target = rel.attribute("Target")?;
+                let target = if rel.attribute("TargetMode") == Some("External") {
+                    target.to_string()
+                } else if let Some(absolute) = target.strip_prefix('/') {
+                    absolute.to_string()
+                } else {
+                    resolve_part(folder, target)
+                };
+                Some((id.to_string(), target))
+            })
+            .collect())
+    }
+}
+
+/// `target` relative to `folder`, with `..` segments applied.
+fn resolve_part(folder: &str, target: &str) -> String {
+    let mut segments: Vec<&str> = folder.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Read `docProps/core.xml` (and the application name from
+/// `docProps/app.xml`) of a DOCX/PPTX package. Missing parts yield empty
+/// fields rather than an error.
+pub fn read_core_properties(path: &Path) -> Result<DocumentMetadata> {
+    let package = Package::open(path)?;
+    let mut metadata = DocumentMetadata::default();
+
+    if let Some(xml) = package.read_string("docProps/core.xml")? {
+        let doc = roxmltree::Document::parse(&xml).context("failed to parse docProps/core.xml")?;
+        let field = |ns: &str, name: &str| element_text(doc.root_element(), ns, name);
+        metadata.title = field(DC_NS, "title");
+        metadata.author = field(DC_NS, "creator");
+        metadata.subject = field(DC_NS, "subject");
+        metadata.keywords = field(CORE_NS, "keywords");
+        metadata.created = field(DCTERMS_NS, "created");
+        metadata.modified = field(DCTERMS_NS, "modified");
+        metadata.language = field(DC_NS, "language");
+    }
+    if let Some(xml) = package.read_string("docProps/app.xml")? {
+        let doc = roxmltree::Document::parse(&xml).context("failed to parse docProps/app.xml")?;
+        metadata.creator = element_text(doc.root_element(), EXTENDED_NS, "Application");
+    }
+    Ok(metadata)
+}
+
+/// Trimmed text of the first child element `ns:name`, if not blank.
+fn element_text(parent: roxmltree::Node, ns: &str, name: &str) -> Option<String> {
+    let text = parent
+        .children()
+        .find(|node| node.has_tag_name((ns, name)))?
+        .text()?
+        .trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/test_office.docx")
+    }
+
+    #[test]
+    fn reads_parts_and_relationships() -> Result<()> {
+        let package = Package::open(&fixture())?;
+
+        let document = package
+            .read_string("word/document.xml")?
+            .expect("main part");
+        assert!(document.contains("DocStruct Rich Office Test"));
+        assert!(package.read("word/missing.xml")?.is_none());
+        let rels = package.relationships("word/document.xml")?;
+        assert_eq!(
+            rels.values()
+                .find(|target| target.ends_with("numbering.xml")),
+            Some(&"word/numbering.xml".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_core_properties_natively() -> Result<()> {
+        let metadata = read_core_properties(&fixture())?;
+        assert_eq!(
+            metadata.title.as_deref(),
+            Some("DocStruct Rich Office Test")
+        );
+        Ok(())
+    }
+}
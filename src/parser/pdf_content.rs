@@ -0,0 +1,1804 @@
+//! Pure-Rust PDF text extraction from page content streams.
+//!
+//! This backend interprets the text operators of each page (and of the Form
+//! XObjects it draws), tracking the CTM and text matrices so every glyph gets
+//! a position in page space. Character codes are mapped to Unicode through the
+//! font's ToUnicode CMap when present, otherwise through its simple encoding
+//! (base encoding plus `/Differences`). Glyphs are then grouped into words,
+//! lines and blocks with the same [`PageTextLayout`] shape the poppler
+//! `-bbox-layout` path produces. The image XObjects and straight path
+//! segments a page draws are recorded along the way. Invisible text (render
+//! mode 3), as left by OCR tools on scanned pages, is kept apart from the
+//! visible text.
+
+use anyhow::{Context, Result};
+use lopdf::encryption::DecryptionError;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::core::error::DocumentError;
+use crate::core::geometry::{BBox, Transform};
+use crate::core::model::{PageSize, TextStyle};
+use crate::parser::tables::Ruling;
+use crate::parser::text_extractor::{GlyphRun, PageTextLayout, TextBlockLayout, TextLineLayout};
+
+/// Form XObjects can nest; stop following them past this depth.
+const MAX_FORM_DEPTH: usize = 8;
+
+type Matrix = [f32; 6];
+
+type Point = (f32, f32);
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+fn multiply(m1: &Matrix, m2: &Matrix) -> Matrix {
+    [
+        m1[0] * m2[0] + m1[1] * m2[2],
+        m1[0] * m2[1] + m1[1] * m2[3],
+        m1[2] * m2[0] + m1[3] * m2[2],
+        m1[2] * m2[1] + m1[3] * m2[3],
+        m1[4] * m2[0] + m1[5] * m2[2] + m2[4],
+        m1[4] * m2[1] + m1[5] * m2[3] + m2[5],
+    ]
+}
+
+fn translate(tx: f32, ty: f32) -> Matrix {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+fn apply(m: &Matrix, x: f32, y: f32) -> Point {
+    (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+}
+
+/// A single positioned glyph in user space (PDF points, bottom-left origin).
+#[derive(Debug, Clone)]
+pub struct PositionedGlyph {
+    pub text: String,
+    pub bbox: BBox,
+    pub style: TextStyle,
+    /// Effective size in points after the text and graphics matrices.
+    pub font_size: f32,
+    /// Marked-content ID of the innermost tagged sequence around the glyph,
+    /// linking it to the structure tree.
+    pub mcid: Option<u32>,
+    /// Drawn inside an `/Artifact` sequence: headers, footers, page numbers
+    /// and other decoration that is not part of the content.
+    pub artifact: bool,
+    /// Drawn with text render mode 3: neither filled nor stroked. Scanned
+    /// pages put their OCR text layer in this mode over the page image.
+    pub invisible: bool,
+}
+
+#[derive(Debug)]
+struct CMap {
+    /// (byte length, low code, high code) of each code space range.
+    codespaces: Vec<(usize, u32, u32)>,
+    map: HashMap<(usize, u32), String>,
+}
+
+impl CMap {
+    fn parse(data: &[u8]) -> Self {
+        let tokens = tokenize_cmap(data);
+        let mut codespaces = Vec::new();
+        let mut map = HashMap::new();
+        let mut i = 0;
+
+        while i < tokens.len() {
+            match &tokens[i] {
+                CMapToken::Word(word) if word == "begincodespacerange" => {
+                    i += 1;
+                    while i + 1 < tokens.len() {
+                        match (&tokens[i], &tokens[i + 1]) {
+                            (CMapToken::Hex(lo), CMapToken::Hex(hi)) => {
+                                codespaces.push((lo.len(), be_code(lo), be_code(hi)));
+                                i += 2;
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+                CMapToken::Word(word) if word == "beginbfchar" => {
+                    i += 1;
+                    while i + 1 < tokens.len() {
+                        match (&tokens[i], &tokens[i + 1]) {
+                            (CMapToken::Hex(src), CMapToken::Hex(dst)) => {
+                                map.insert((src.len(), be_code(src)), utf16_be(dst));
+                                i += 2;
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+                CMapToken::Word(word) if word == "beginbfrange" => {
+                    i += 1;
+                    while i + 2 < tokens.len() {
+                        let (lo, hi) = match (&tokens[i], &tokens[i + 1]) {
+                            (CMapToken::Hex(lo), CMapToken::Hex(hi)) => (lo, hi),
+                            _ => break,
+                        };
+                        let len = lo.len();
+                        let (lo, hi) = (be_code(lo), be_code(hi));
+                        match &tokens[i + 2] {
+                            CMapToken::Hex(dst) => {
+                                let base = utf16_units(dst);
+                                for (offset, code) in
+                                    (lo..=hi.min(lo.saturating_add(0xFFFF))).enumerate()
+                                {
+                                    let mut units = base.clone();
+                                    if let Some(last) = units.last_mut() {
+                                        *last = last.wrapping_add(offset as u16);
+                                    }
+                                    map.insert((len, code), String::from_utf16_lossy(&units));
+                                }
+                                i += 3;
+                            }
+                            CMapToken::ArrayStart => {
+                                let mut j = i + 3;
+                                // `None` once the codes run past `u32::MAX`.
+                                let mut code = Some(lo);
+                                while j < tokens.len() {
+                                    match &tokens[j] {
+                                        CMapToken::Hex(dst) => {
+                                            if let Some(code) = code.filter(|code| *code <= hi) {
+                                                map.insert((len, code), utf16_be(dst));
+                                            }
+                                            code = code.and_then(|code| code.checked_add(1));
+                                            j += 1;
+                                        }
+                                        CMapToken::ArrayEnd => {
+                                            j += 1;
+                                            break;
+                                        }
+                                        _ => break,
+                                    }
+                                }
+                                i = j;
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+                _ => i += 1,
+            }
+        }
+
+        Self { codespaces, map }
+    }
+
+    /// Length in bytes of the code starting at `bytes[0]`, if the code space
+    /// ranges say anything about it.
+    fn code_length(&self, bytes: &[u8]) -> Option<usize> {
+        (1..=4).find(|&len| {
+            len <= bytes.len()
+                && self.codespaces.iter().any(|&(range_len, lo, hi)| {
+                    range_len == len && (lo..=hi).contains(&be_code(&bytes[..len]))
+                })
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CMapToken {
+    Hex(Vec<u8>),
+    Word(String),
+    ArrayStart,
+    ArrayEnd,
+}
+
+fn tokenize_cmap(data: &[u8]) -> Vec<CMapToken> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let c = data[i];
+        if c == b'%' {
+            while i < data.len() && data[i] != b'\n' && data[i] != b'\r' {
+                i += 1;
+            }
+        } else if c == b'<' && data.get(i + 1) != Some(&b'<') {
+            let start = i + 1;
+            let end = data[start..]
+                .iter()
+                .position(|&b| b == b'>')
+                .map(|pos| start + pos)
+                .unwrap_or(data.len());
+            tokens.push(CMapToken::Hex(decode_hex(&data[start..end])));
+            i = end + 1;
+        } else if c == b'[' {
+            tokens.push(CMapToken::ArrayStart);
+            i += 1;
+        } else if c == b']' {
+            tokens.push(CMapToken::ArrayEnd);
+            i += 1;
+        } else if c.is_ascii_whitespace() {
+            i += 1;
+        } else {
+            let start = i;
+            while i < data.len() && !data[i].is_ascii_whitespace() && !b"<>[]%".contains(&data[i]) {
+                i += 1;
+            }
+            if i == start {
+                i += 1;
+                continue;
+            }
+            tokens.push(CMapToken::Word(
+                String::from_utf8_lossy(&data[start..i]).into_owned(),
+            ));
+        }
+    }
+    tokens
+}
+
+fn decode_hex(hex: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .iter()
+        .filter_map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+fn be_code(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32)
+}
+
+fn utf16_units(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| ((pair[0] as u16) << 8) | pair.get(1).copied().unwrap_or(0) as u16)
+        .collect()
+}
+
+fn utf16_be(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(bytes))
+}
+
+/// Unicode for the glyph names that commonly appear in `/Differences`.
+fn glyph_name_to_unicode(name: &str) -> Option<String> {
+    if let Some(hex) = name.strip_prefix("uni") {
+        if hex.len() >= 4 && hex.len() % 4 == 0 {
+            let units: Option<Vec<u16>> = hex
+                .as_bytes()
+                .chunks(4)
+                .map(|chunk| u16::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok())
+                .collect();
+            return units.map(|units| String::from_utf16_lossy(&units));
+        }
+    }
+    if let Some(hex) = name.strip_prefix('u') {
+        if (4..=6).contains(&hex.len()) {
+            if let Some(c) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                return Some(c.to_string());
+            }
+        }
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c.to_string());
+    }
+
+    let mapped = match name {
+        "space" | "nbspace" => " ",
+        "exclam" => "!",
+        "quotedbl" => "\"",
+        "numbersign" => "#",
+        "dollar" => "$",
+        "percent" => "%",
+        "ampersand" => "&",
+        "quotesingle" | "quoteright" => "’",
+        "quoteleft" => "‘",
+        "parenleft" => "(",
+        "parenright" => ")",
+        "asterisk" => "*",
+        "plus" => "+",
+        "comma" => ",",
+        "hyphen" | "minus" => "-",
+        "period" => ".",
+        "slash" => "/",
+        "zero" => "0",
+        "one" => "1",
+        "two" => "2",
+        "three" => "3",
+        "four" => "4",
+        "five" => "5",
+        "six" => "6",
+        "seven" => "7",
+        "eight" => "8",
+        "nine" => "9",
+        "colon" => ":",
+        "semicolon" => ";",
+        "less" => "<",
+        "equal" => "=",
+        "greater" => ">",
+        "question" => "?",
+        "at" => "@",
+        "bracketleft" => "[",
+        "backslash" => "\\",
+        "bracketright" => "]",
+        "asciicircum" => "^",
+        "underscore" => "_",
+        "grave" => "`",
+        "braceleft" => "{",
+        "bar" => "|",
+        "braceright" => "}",
+        "asciitilde" => "~",
+        "bullet" => "•",
+        "endash" => "–",
+        "emdash" => "—",
+        "quotedblleft" => "“",
+        "quotedblright" => "”",
+        "ellipsis" => "…",
+        "fi" => "fi",
+        "fl" => "fl",
+        "ff" => "ff",
+        "ffi" => "ffi",
+        "ffl" => "ffl",
+        "degree" => "°",
+        "copyright" => "©",
+        "registered" => "®",
+        "trademark" => "™",
+        "section" => "§",
+        "paragraph" => "¶",
+        "dagger" => "†",
+        "daggerdbl" => "‡",
+        "multiply" => "×",
+        "divide" => "÷",
+        "plusminus" => "±",
+        _ => return None,
+    };
+    Some(mapped.to_string())
+}
+
+#[derive(Debug)]
+struct FontInfo {
+    name: Option<String>,
+    bold: bool,
+    italic: bool,
+    composite: bool,
+    to_unicode: Option<CMap>,
+    simple_encoding: Option<[Option<String>; 256]>,
+    /// Predefined CMaps such as `UniKS-UCS2-H` whose codes are UTF-16BE.
+    utf16_codes: bool,
+    first_char: u32,
+    widths: Vec<f32>,
+    cid_widths: HashMap<u32, f32>,
+    default_width: f32,
+}
+
+impl FontInfo {
+    fn load(doc: &Document, font: &Dictionary) -> Self {
+        let subtype = name_of(doc, font, b"Subtype").unwrap_or_default();
+        let composite = subtype == "Type0";
+        let name = name_of(doc, font, b"BaseFont").map(|name| strip_subset_prefix(&name));
+
+        let to_unicode = font
+            .get(b"ToUnicode")
+            .ok()
+            .and_then(|obj| deref(doc, obj).as_stream().ok())
+            .and_then(|stream| stream.get_plain_content().ok())
+            .map(|data| CMap::parse(&data));
+
+        let encoding_name = name_of(doc, font, b"Encoding");
+        let utf16_codes = composite
+            && encoding_name
+                .as_deref()
+                .map(|name| name.contains("UCS2") || name.contains("UTF16"))
+                .unwrap_or(false);
+
+        let simple_encoding = if composite {
+            None
+        } else {
+            Some(simple_encoding(doc, font, &subtype))
+        };
+
+        let mut info = Self {
+            name,
+            bold: false,
+            italic: false,
+            composite,
+            to_unicode,
+            simple_encoding,
+            utf16_codes,
+            first_char: 0,
+            widths: Vec::new(),
+            cid_widths: HashMap::new(),
+            default_width: if composite { 1000.0 } else { 500.0 },
+        };
+
+        let descendant = if composite {
+            font.get(b"DescendantFonts")
+                .ok()
+                .and_then(|obj| deref(doc, obj).as_array().ok())
+                .and_then(|arr| arr.first())
+                .and_then(|obj| deref(doc, obj).as_dict().ok())
+        } else {
+            None
+        };
+        let descriptor = descendant
+            .unwrap_or(font)
+            .get(b"FontDescriptor")
+            .ok()
+            .and_then(|obj| deref(doc, obj).as_dict().ok());
+        let named = TextStyle::from_font_name(info.name.as_deref().unwrap_or_default(), None);
+        info.bold = named.bold;
+        info.italic = named.italic;
+        if let Some(descriptor) = descriptor {
+            let flags = descriptor
+                .get(b"Flags")
+                .ok()
+                .and_then(|o| number(doc, o))
+                .unwrap_or(0.0) as u32;
+            let weight = descriptor
+                .get(b"FontWeight")
+                .ok()
+                .and_then(|o| number(doc, o))
+                .unwrap_or(0.0);
+            let italic_angle = descriptor
+                .get(b"ItalicAngle")
+                .ok()
+                .and_then(|o| number(doc, o))
+                .unwrap_or(0.0);
+            // Flags bit 7 is Italic, bit 19 is ForceBold.
+            info.bold |= flags & (1 << 18) != 0 || weight >= 600.0;
+            info.italic |= flags & (1 << 6) != 0 || italic_angle != 0.0;
+        }
+
+        if composite {
+            if let Some(descendant) = descendant {
+                if let Some(dw) = descendant.get(b"DW").ok().and_then(|o| number(doc, o)) {
+                    info.default_width = dw;
+                }
+                if let Ok(w) = descendant.get(b"W") {
+                    info.cid_widths = parse_cid_widths(doc, deref(doc, w));
+                }
+            }
+        } else {
+            info.first_char = font
+                .get(b"FirstChar")
+                .ok()
+                .and_then(|o| number(doc, o))
+                .unwrap_or(0.0) as u32;
+            info.widths = font
+                .get(b"Widths")
+                .ok()
+                .and_then(|obj| deref(doc, obj).as_array().ok())
+                .map(|arr| arr.iter().filter_map(|o| number(doc, o)).collect())
+                .unwrap_or_default();
+            let missing = font
+                .get(b"FontDescriptor")
+                .ok()
+                .and_then(|obj| deref(doc, obj).as_dict().ok())
+                .and_then(|desc| desc.get(b"MissingWidth").ok())
+                .and_then(|o| number(doc, o));
+            if let Some(missing) = missing {
+                info.default_width = missing;
+            }
+        }
+
+        info
+    }
+
+    /// Split a shown string into (code, byte length) pairs.
+    fn codes(&self, bytes: &[u8]) -> Vec<(u32, usize)> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            let len = self
+                .to_unicode
+                .as_ref()
+                .and_then(|cmap| cmap.code_length(&bytes[i..]))
+                .unwrap_or(if self.composite { 2 } else { 1 })
+                .min(bytes.len() - i);
+            out.push((be_code(&bytes[i..i + len]), len));
+            i += len;
+        }
+        out
+    }
+
+    fn unicode(&self, code: u32, len: usize) -> Option<String> {
+        if let Some(text) = self
+            .to_unicode
+            .as_ref()
+            .and_then(|cmap| cmap.map.get(&(len, code)))
+        {
+            return Some(text.clone());
+        }
+        if self.utf16_codes {
+            return char::from_u32(code).map(|c| c.to_string());
+        }
+        self.simple_encoding
+            .as_ref()
+            .and_then(|table| table.get(code as usize).cloned().flatten())
+    }
+
+    /// Glyph advance in thousandths of text space units.
+    fn width(&self, code: u32) -> f32 {
+        if self.composite {
+            return self
+                .cid_widths
+                .get(&code)
+                .copied()
+                .unwrap_or(self.default_width);
+        }
+        code.checked_sub(self.first_char)
+            .and_then(|idx| self.widths.get(idx as usize).copied())
+            .filter(|w| *w > 0.0)
+            .unwrap_or(self.default_width)
+    }
+}
+
+fn strip_subset_prefix(name: &str) -> String {
+    match name.split_once('+') {
+        Some((prefix, rest))
+            if prefix.len() == 6 && prefix.chars().all(|c| c.is_ascii_uppercase()) =>
+        {
+            rest.to_string()
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// One of the standard single-byte encodings, as a code → text table.
+///
+/// lopdf keeps its tables private but hands them out through
+/// `Dictionary::get_font_encoding`, so ask it about a stub font dictionary.
+fn builtin_encoding(doc: &Document, name: &str) -> Option<[Option<String>; 256]> {
+    if !matches!(
+        name,
+        "WinAnsiEncoding" | "MacRomanEncoding" | "StandardEncoding" | "PDFDocEncoding"
+    ) {
+        return None;
+    }
+    let mut stub = Dictionary::new();
+    stub.set("Type", Object::Name(b"Font".to_vec()));
+    stub.set("Encoding", Object::Name(name.as_bytes().to_vec()));
+    match stub.get_font_encoding(doc).ok()? {
+        lopdf::Encoding::OneByteEncoding(table) => Some(std::array::from_fn(|idx| {
+            table[idx].map(|unit| String::from_utf16_lossy(&[unit]))
+        })),
+        _ => None,
+    }
+}
+
+fn simple_encoding(doc: &Document, font: &Dictionary, subtype: &str) -> [Option<String>; 256] {
+    let base_for = |name: &str| builtin_encoding(doc, name);
+    let default_table = || {
+        let name = if subtype == "TrueType" {
+            "WinAnsiEncoding"
+        } else {
+            "StandardEncoding"
+        };
+        builtin_encoding(doc, name).unwrap_or_else(|| std::array::from_fn(|_| None))
+    };
+
+    let encoding = match font.get(b"Encoding") {
+        Ok(obj) => deref(doc, obj),
+        Err(_) => return default_table(),
+    };
+    match encoding {
+        Object::Name(name) => {
+            base_for(&String::from_utf8_lossy(name)).unwrap_or_else(default_table)
+        }
+        Object::Dictionary(dict) => {
+            let mut table = name_of(doc, dict, b"BaseEncoding")
+                .and_then(|name| base_for(&name))
+                .unwrap_or_else(default_table);
+            if let Ok(differences) = dict.get(b"Differences").and_then(Object::as_array) {
+                let mut code = 0usize;
+                for item in differences {
+                    match deref(doc, item) {
+                        Object::Integer(start) => code = (*start).max(0) as usize,
+                        Object::Name(glyph) => {
+                            if code < 256 {
+                                table[code] =
+                                    glyph_name_to_unicode(&String::from_utf8_lossy(glyph));
+                            }
+                            code = code.saturating_add(1);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            table
+        }
+        _ => default_table(),
+    }
+}
+
+fn parse_cid_widths(doc: &Document, w: &Object) -> HashMap<u32, f32> {
+    let mut widths = HashMap::new();
+    let items = match w.as_array() {
+        Ok(items) => items,
+        Err(_) => return widths,
+    };
+    let mut i = 0;
+    while i < items.len() {
+        let first = match number(doc, &items[i]) {
+            Some(first) => first as u32,
+            None => break,
+        };
+        match items.get(i + 1).map(|o| deref(doc, o)) {
+            Some(Object::Array(list)) => {
+                for (offset, value) in list.iter().enumerate() {
+                    let cid = u32::try_from(offset)
+                        .ok()
+                        .and_then(|offset| first.checked_add(offset));
+                    if let (Some(cid), Some(width)) = (cid, number(doc, value)) {
+                        widths.insert(cid, width);
+                    }
+                }
+                i += 2;
+            }
+            Some(other) => {
+                let last = number(doc, other).unwrap_or(first as f32) as u32;
+                let width = items
+                    .get(i + 2)
+                    .and_then(|o| number(doc, o))
+                    .unwrap_or(1000.0);
+                for cid in first..=last.min(first.saturating_add(0xFFFF)) {
+                    widths.insert(cid, width);
+                }
+                i += 3;
+            }
+            None => break,
+        }
+    }
+    widths
+}
+
+pub(crate) fn deref<'a>(doc: &'a Document, mut obj: &'a Object) -> &'a Object {
+    for _ in 0..16 {
+        match obj {
+            Object::Reference(id) => match doc.get_object(*id) {
+                Ok(next) => obj = next,
+                Err(_) => return &Object::Null,
+            },
+            _ => return obj,
+        }
+    }
+    obj
+}
+
+pub(crate) fn number(doc: &Document, obj: &Object) -> Option<f32> {
+    match deref(doc, obj) {
+        Object::Integer(value) => Some(*value as f32),
+        Object::Real(value) => Some(*value),
+        _ => None,
+    }
+}
+
+pub(crate) fn name_of(doc: &Document, dict: &Dictionary, key: &[u8]) -> Option<String> {
+    match deref(doc, dict.get(key).ok()?) {
+        Object::Name(name) => Some(String::from_utf8_lossy(name).into_owned()),
+        _ => None,
+    }
+}
+
+fn operand_f32(operands: &[Object], idx: usize) -> f32 {
+    match operands.get(idx) {
+        Some(Object::Integer(value)) => *value as f32,
+        Some(Object::Real(value)) => *value,
+        _ => 0.0,
+    }
+}
+
+fn operand_matrix(operands: &[Object]) -> Matrix {
+    std::array::from_fn(|idx| operand_f32(operands, idx))
+}
+
+/// Remove inline images (`BI ... ID <binary> EI`) which the content parser
+/// cannot tokenize; they carry no text.
+fn strip_inline_images(data: &[u8]) -> Vec<u8> {
+    let is_delim = |b: Option<&u8>| b.map(|b| b.is_ascii_whitespace()).unwrap_or(true);
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i..].starts_with(b"BI")
+            && (i == 0 || is_delim(data.get(i - 1)))
+            && is_delim(data.get(i + 2))
+        {
+            let id_pos = (i + 2..data.len().saturating_sub(1)).find(|&j| {
+                &data[j..j + 2] == b"ID" && is_delim(data.get(j - 1)) && is_delim(data.get(j + 2))
+            });
+            let end = id_pos.and_then(|id| {
+                (id + 3..data.len().saturating_sub(1)).find(|&j| {
+                    &data[j..j + 2] == b"EI"
+                        && is_delim(data.get(j - 1))
+                        && is_delim(data.get(j + 2))
+                })
+            });
+            if let Some(end) = end {
+                out.push(b' ');
+                i = end + 2;
+                continue;
+            }
+        }
+        out.push(data[i]);
+        i += 1;
+    }
+    out
+}
+
+#[derive(Debug, Clone)]
+struct GraphicsState {
+    ctm: Matrix,
+    char_spacing: f32,
+    word_spacing: f32,
+    horizontal_scale: f32,
+    leading: f32,
+    rise: f32,
+    font: Option<Rc<FontInfo>>,
+    font_size: f32,
+    render_mode: i64,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            ctm: IDENTITY,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scale: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+            font: None,
+            font_size: 0.0,
+            render_mode: 0,
+        }
+    }
+}
+
+/// An open marked-content sequence (`BMC`/`BDC` ... `EMC`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkedContent {
+    Mcid(u32),
+    Artifact,
+    Other,
+}
+
+struct Interpreter<'a> {
+    doc: &'a Document,
+    fonts: HashMap<ObjectId, Rc<FontInfo>>,
+    glyphs: Vec<PositionedGlyph>,
+    /// Image XObjects drawn so far, with the CTM that maps the unit square
+    /// onto their placement and the MCID they were drawn under.
+    images: Vec<(Matrix, ObjectId, Option<u32>)>,
+    /// Marked-content sequences open at the current operator, outermost
+    /// first. Form XObjects run inside the sequences of their caller.
+    marked: Vec<MarkedContent>,
+    /// Straight stroked segments, in user space.
+    strokes: Vec<(Point, Point)>,
+    /// Bounding boxes of filled straight-edged shapes, in user space.
+    fills: Vec<BBox>,
+}
+
+/// Subpaths under construction: points in user space, each flagged with
+/// whether it is joined to the previous point by a straight line.
+#[derive(Debug, Default)]
+struct PathBuilder {
+    subpaths: Vec<Vec<(Point, bool)>>,
+}
+
+impl PathBuilder {
+    fn move_to(&mut self, point: Point) {
+        self.subpaths.push(vec![(point, false)]);
+    }
+
+    fn segment_to(&mut self, point: Point, straight: bool) {
+        match self.subpaths.last_mut() {
+            Some(subpath) => subpath.push((point, straight)),
+            None => self.move_to(point),
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(subpath) = self.subpaths.last_mut() {
+            if let Some(&(first, _)) = subpath.first() {
+                subpath.push((first, true));
+            }
+        }
+    }
+
+    fn rect(&mut self, ctm: &Matrix, operands: &[Object]) {
+        let (x, y) = (operand_f32(operands, 0), operand_f32(operands, 1));
+        let (w, h) = (operand_f32(operands, 2), operand_f32(operands, 3));
+        self.move_to(apply(ctm, x, y));
+        for (px, py) in [(x + w, y), (x + w, y + h), (x, y + h), (x, y)] {
+            self.segment_to(apply(ctm, px, py), true);
+        }
+    }
+
+    fn stroke_into(&self, strokes: &mut Vec<(Point, Point)>) {
+        for subpath in &self.subpaths {
+            for pair in subpath.windows(2) {
+                if pair[1].1 {
+                    strokes.push((pair[0].0, pair[1].0));
+                }
+            }
+        }
+    }
+
+    fn fill_into(&self, fills: &mut Vec<BBox>) {
+        for subpath in &self.subpaths {
+            if subpath.len() < 3 || subpath.iter().skip(1).any(|(_, straight)| !straight) {
+                continue;
+            }
+            let (x, y) = subpath[0].0;
+            fills.push(
+                subpath
+                    .iter()
+                    .fold(BBox::new(x, y, x, y), |bbox, &((x, y), _)| {
+                        bbox.union(&BBox::new(x, y, x, y))
+                    }),
+            );
+        }
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    fn run(
+        &mut self,
+        content: &[u8],
+        resources: Option<&'a Dictionary>,
+        ctm: Matrix,
+        depth: usize,
+    ) {
+        let data = strip_inline_images(content);
+        let operations = match lopdf::content::Content::decode(&data) {
+            Ok(content) => content.operations,
+            Err(_) => return,
+        };
+
+        let mut state = GraphicsState {
+            ctm,
+            ..GraphicsState::default()
+        };
+        let mut stack: Vec<GraphicsState> = Vec::new();
+        let mut tm = IDENTITY;
+        let mut tlm = IDENTITY;
+        let mut path = PathBuilder::default();
+        let marked_depth = self.marked.len();
+
+        for op in &operations {
+            let operands = op.operands.as_slice();
+            match op.operator.as_str() {
+                "q" => stack.push(state.clone()),
+                "Q" => {
+                    if let Some(saved) = stack.pop() {
+                        state = saved;
+                    }
+                }
+                "cm" => state.ctm = multiply(&operand_matrix(operands), &state.ctm),
+                "m" => path.move_to(apply(
+                    &state.ctm,
+                    operand_f32(operands, 0),
+                    operand_f32(operands, 1),
+                )),
+                "l" => path.segment_to(
+                    apply(
+                        &state.ctm,
+                        operand_f32(operands, 0),
+                        operand_f32(operands, 1),
+                    ),
+                    true,
+                ),
+                "c" | "v" | "y" => {
+                    let last = operands.len().saturating_sub(2);
+                    path.segment_to(
+                        apply(
+                            &state.ctm,
+                            operand_f32(operands, last),
+                            operand_f32(operands, last + 1),
+                        ),
+                        false,
+                    );
+                }
+                "h" => path.close(),
+                "re" => path.rect(&state.ctm, operands),
+                "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" | "n" => {
+                    let op = op.operator.as_str();
+                    if matches!(op, "s" | "b" | "b*") {
+                        path.close();
+                    }
+                    if matches!(op, "S" | "s" | "B" | "B*" | "b" | "b*") {
+                        path.stroke_into(&mut self.strokes);
+                    }
+                    if matches!(op, "f" | "F" | "f*" | "B" | "B*" | "b" | "b*") {
+                        path.fill_into(&mut self.fills);
+                    }
+                    path = PathBuilder::default();
+                }
+                "BT" => {
+                    tm = IDENTITY;
+                    tlm = IDENTITY;
+                }
+                "Tc" => state.char_spacing = operand_f32(operands, 0),
+                "Tw" => state.word_spacing = operand_f32(operands, 0),
+                "Tz" => state.horizontal_scale = operand_f32(operands, 0) / 100.0,
+                "TL" => state.leading = operand_f32(operands, 0),
+                "Ts" => state.rise = operand_f32(operands, 0),
+                "Tr" => state.render_mode = operand_f32(operands, 0) as i64,
+                "Tf" => {
+                    state.font_size = operand_f32(operands, 1);
+                    state.font = operands
+                        .first()
+                        .and_then(|o| o.as_name().ok())
+                        .and_then(|name| self.font(resources, name));
+                }
+                "Td" => {
+                    tlm = multiply(
+                        &translate(operand_f32(operands, 0), operand_f32(operands, 1)),
+                        &tlm,
+                    );
+                    tm = tlm;
+                }
+                "TD" => {
+                    state.leading = -operand_f32(operands, 1);
+                    tlm = multiply(
+                        &translate(operand_f32(operands, 0), operand_f32(operands, 1)),
+                        &tlm,
+                    );
+                    tm = tlm;
+                }
+                "Tm" => {
+                    tlm = operand_matrix(operands);
+                    tm = tlm;
+                }
+                "T*" => {
+                    tlm = multiply(&translate(0.0, -state.leading), &tlm);
+                    tm = tlm;
+                }
+                "Tj" => {
+                    if let Some(Object::String(bytes, _)) = operands.first() {
+                        self.show(bytes, &state, &mut tm);
+                    }
+                }
+                "'" => {
+                    tlm = multiply(&translate(0.0, -state.leading), &tlm);
+                    tm = tlm;
+                    if let Some(Object::String(bytes, _)) = operands.first() {
+                        self.show(bytes, &state, &mut tm);
+                    }
+                }
+                "\"" => {
+                    state.word_spacing = operand_f32(operands, 0);
+                    state.char_spacing = operand_f32(operands, 1);
+                    tlm = multiply(&translate(0.0, -state.leading), &tlm);
+                    tm = tlm;
+                    if let Some(Object::String(bytes, _)) = operands.get(2) {
+                        self.show(bytes, &state, &mut tm);
+                    }
+                }
+                "TJ" => {
+                    if let Some(Object::Array(items)) = operands.first() {
+                        for item in items {
+                            match item {
+                                Object::String(bytes, _) => self.show(bytes, &state, &mut tm),
+                                Object::Integer(_) | Object::Real(_) => {
+                                    let adjust = operand_f32(std::slice::from_ref(item), 0);
+                                    let tx =
+                                        -adjust / 1000.0 * state.font_size * state.horizontal_scale;
+                                    tm = multiply(&translate(tx, 0.0), &tm);
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                "Do" if depth < MAX_FORM_DEPTH => {
+                    if let Some(name) = operands.first().and_then(|o| o.as_name().ok()) {
+                        self.draw_xobject(resources, name, &state, depth);
+                    }
+                }
+                "BMC" | "BDC" => {
+                    let marked = self.marked_content(resources, operands);
+                    self.marked.push(marked);
+                }
+                "EMC" if self.marked.len() > marked_depth => {
+                    self.marked.pop();
+                }
+                _ => {}
+            }
+        }
+        // A stream may leave sequences open; they end with it.
+        self.marked.truncate(marked_depth);
+    }
+
+    /// The sequence a `BMC`/`BDC` opens. `BDC` properties are inline or a
+    /// name in the resources' `/Properties`.
+    fn marked_content(
+        &self,
+        resources: Option<&'a Dictionary>,
+        operands: &[Object],
+    ) -> MarkedContent {
+        let doc = self.doc;
+        if operands.first().and_then(|o| o.as_name().ok()) == Some(b"Artifact".as_slice()) {
+            return MarkedContent::Artifact;
+        }
+        let properties = match operands.get(1) {
+            Some(Object::Dictionary(dict)) => Some(dict),
+            Some(Object::Name(name)) => resources
+                .and_then(|res| res.get(b"Properties").ok())
+                .and_then(|obj| deref(doc, obj).as_dict().ok())
+                .and_then(|props| props.get(name).ok())
+                .and_then(|obj| deref(doc, obj).as_dict().ok()),
+            _ => None,
+        };
+        properties
+            .and_then(|props| props.get(b"MCID").ok())
+            .and_then(|obj| deref(doc, obj).as_i64().ok())
+            .and_then(|mcid| u32::try_from(mcid).ok())
+            .map_or(MarkedContent::Other, MarkedContent::Mcid)
+    }
+
+    /// MCID of the innermost tagged sequence that is open.
+    fn mcid(&self) -> Option<u32> {
+        self.marked.iter().rev().find_map(|marked| match marked {
+            MarkedContent::Mcid(mcid) => Some(*mcid),
+            _ => None,
+        })
+    }
+
+    fn font(&mut self, resources: Option<&'a Dictionary>, name: &[u8]) -> Option<Rc<FontInfo>> {
+        let doc = self.doc;
+        let fonts = resources?.get(b"Font").ok().map(|obj| deref(doc, obj))?;
+        let entry = fonts.as_dict().ok()?.get(name).ok()?;
+        if let Object::Reference(id) = entry {
+            if let Some(cached) = self.fonts.get(id) {
+                return Some(cached.clone());
+            }
+            let info = Rc::new(FontInfo::load(doc, deref(doc, entry).as_dict().ok()?));
+            self.fonts.insert(*id, info.clone());
+            return Some(info);
+        }
+        Some(Rc::new(FontInfo::load(doc, entry.as_dict().ok()?)))
+    }
+
+    fn draw_xobject(
+        &mut self,
+        resources: Option<&'a Dictionary>,
+        name: &[u8],
+        state: &GraphicsState,
+        depth: usize,
+    ) {
+        let doc = self.doc;
+        let entry = resources
+            .and_then(|res| res.get(b"XObject").ok())
+            .and_then(|obj| deref(doc, obj).as_dict().ok())
+            .and_then(|xobjects| xobjects.get(name).ok());
+        let stream = match entry.and_then(|obj| deref(doc, obj).as_stream().ok()) {
+            Some(stream) => stream,
+            None => return,
+        };
+        match name_of(doc, &stream.dict, b"Subtype").as_deref() {
+            Some("Form") => {}
+            Some("Image") => {
+                // Streams are indirect objects, so the entry is a reference.
+                if let Some(Ok(id)) = entry.map(Object::as_reference) {
+                    let mcid = self.mcid();
+                    self.images.push((state.ctm, id, mcid));
+                }
+                return;
+            }
+            _ => return,
+        }
+
+        let matrix = stream
+            .dict
+            .get(b"Matrix")
+            .ok()
+            .and_then(|obj| deref(doc, obj).as_array().ok())
+            .map(|arr| {
+                std::array::from_fn(|idx| arr.get(idx).and_then(|o| number(doc, o)).unwrap_or(0.0))
+            })
+            .unwrap_or(IDENTITY);
+        let form_resources = stream
+            .dict
+            .get(b"Resources")
+            .ok()
+            .and_then(|obj| deref(doc, obj).as_dict().ok())
+            .or(resources);
+        let content = match stream.get_plain_content() {
+            Ok(content) => content,
+            Err(_) => return,
+        };
+
+        self.run(
+            &content,
+            form_resources,
+            multiply(&matrix, &state.ctm),
+            depth + 1,
+        );
+    }
+
+    fn show(&mut self, bytes: &[u8], state: &GraphicsState, tm: &mut Matrix) {
+        let font = match &state.font {
+            Some(font) => font.clone(),
+            None => return,
+        };
+        let size = state.font_size;
+        let scale = state.horizontal_scale;
+
+        for (code, len) in font.codes(bytes) {
+            let advance = font.width(code) / 1000.0;
+            let render = multiply(
+                &multiply(&[size * scale, 0.0, 0.0, size, 0.0, state.rise], tm),
+                &state.ctm,
+            );
+
+            // Approximate the glyph box with a 0.8 ascent / 0.2 descent em box.
+            let corners = [
+                apply(&render, 0.0, -0.2),
+                apply(&render, advance, -0.2),
+                apply(&render, 0.0, 0.8),
+                apply(&render, advance, 0.8),
+            ];
+            let bbox = BBox::new(
+                corners.iter().map(|p| p.0).fold(f32::MAX, f32::min),
+                corners.iter().map(|p| p.1).fold(f32::MAX, f32::min),
+                corners.iter().map(|p| p.0).fold(f32::MIN, f32::max),
+                corners.iter().map(|p| p.1).fold(f32::MIN, f32::max),
+            );
+            let effective_size = (render[2].powi(2) + render[3].powi(2)).sqrt();
+
+            let text = font
+                .unicode(code, len)
+                .unwrap_or_else(|| "\u{FFFD}".to_string());
+            let is_space = len == 1 && code == 32;
+            if !text.is_empty() {
+                self.glyphs.push(PositionedGlyph {
+                    text,
+                    bbox,
+                    style: TextStyle {
+                        font: font.name.clone(),
+                        size: Some(effective_size),
+                        bold: font.bold,
+                        italic: font.italic,
+                    },
+                    font_size: effective_size,
+                    mcid: self.mcid(),
+                    artifact: self.marked.contains(&MarkedContent::Artifact),
+                    invisible: state.render_mode == 3,
+                });
+            }
+
+            let spacing = state.char_spacing + if is_space { state.word_spacing } else { 0.0 };
+            let tx = (advance * size + spacing) * scale;
+            *tm = multiply(&translate(tx, 0.0), tm);
+        }
+    }
+}
+
+/// What a page's content stream draws, in displayed page space.
+#[derive(Debug)]
+struct PageContent {
+    size: PageSize,
+    glyphs: Vec<PositionedGlyph>,
+    /// Image placements with the image XObject and its MCID.
+    images: Vec<(BBox, ObjectId, Option<u32>)>,
+    rulings: Vec<Ruling>,
+}
+
+/// A PDF opened with lopdf for native text extraction.
+#[derive(Debug, Clone)]
+pub struct NativePdf {
+    pub(crate) doc: Document,
+    /// The last page interpreted. Text, tables, tags, images and pixel
+    /// signals all read the same page in turn, so its content stream runs
+    /// once while the pipeline walks the pages in order.
+    content: RefCell<Option<(usize, Rc<PageContent>)>>,
+}
+
+impl NativePdf {
+    /// Load a PDF, decrypting it when it is encrypted. Without a password
+    /// the empty user password is tried, which opens files that only carry
+    /// permission restrictions.
+    pub fn open(path: &Path, password: Option<&str>) -> Result<Self> {
+        let mut doc = Document::load(path)
+            .with_context(|| format!("failed to load PDF natively: {}", path.display()))?;
+        if doc.is_encrypted() {
+            match doc.decrypt(password.unwrap_or_default()) {
+                Ok(()) => {}
+                Err(lopdf::Error::Decryption(DecryptionError::IncorrectPassword)) => {
+                    let path = path.to_path_buf();
+                    return Err(match password {
+                        Some(_) => DocumentError::IncorrectPassword { path }.into(),
+                        None => DocumentError::Encrypted { path }.into(),
+                    });
+                }
+                Err(err) => {
+                    return Err(err).with_context(|| {
+                        format!("failed to decrypt PDF natively: {}", path.display())
+                    })
+                }
+            }
+        }
+        Ok(Self::from_document(doc))
+    }
+
+    pub(crate) fn from_document(doc: Document) -> Self {
+        Self {
+            doc,
+            content: RefCell::new(None),
+        }
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.doc.get_pages().len()
+    }
+
+    pub(crate) fn page_id(&self, page_idx: usize) -> Result<ObjectId> {
+        self.doc
+            .get_pages()
+            .get(&(page_idx as u32 + 1))
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("page {} not found in PDF", page_idx + 1))
+    }
+
+    /// Look up a page attribute, following `/Parent` for inheritable keys.
+    fn inherited<'a>(&'a self, page_id: ObjectId, key: &[u8]) -> Option<&'a Object> {
+        let mut node = self.doc.get_dictionary(page_id).ok()?;
+        for _ in 0..32 {
+            if let Ok(value) = node.get(key) {
+                return Some(deref(&self.doc, value));
+            }
+            let parent = node.get(b"Parent").ok()?.as_reference().ok()?;
+            node = self.doc.get_dictionary(parent).ok()?;
+        }
+        None
+    }
+
+    /// The visible page box (CropBox, else MediaBox) as `[x0, y0, x1, y1]`.
+    fn page_box(&self, page_id: ObjectId) -> [f32; 4] {
+        let read_box = |key: &[u8]| -> Option<[f32; 4]> {
+            let arr = self.inherited(page_id, key)?.as_array().ok()?;
+            let values: Vec<f32> = arr.iter().filter_map(|o| number(&self.doc, o)).collect();
+            (values.len() == 4).then(|| {
+                [
+                    values[0].min(values[2]),
+                    values[1].min(values[3]),
+                    values[0].max(values[2]),
+                    values[1].max(values[3]),
+                ]
+            })
+        };
+        read_box(b"CropBox")
+            .or_else(|| read_box(b"MediaBox"))
+            .unwrap_or([0.0, 0.0, 612.0, 792.0])
+    }
+
+    /// Displayed page size (CropBox with `/Rotate` applied) and the map from
+    /// PDF user space to that displayed space, top-left origin.
+    pub(crate) fn display_space(&self, page_id: ObjectId) -> (PageSize, Transform) {
+        let [x0, y0, x1, y1] = self.page_box(page_id);
+        let (width, height) = (x1 - x0, y1 - y0);
+        let rotation = self
+            .inherited(page_id, b"Rotate")
+            .and_then(|obj| number(&self.doc, obj))
+            .map(|degrees| ((degrees as i32 / 90) * 90).rem_euclid(360) as u16)
+            .unwrap_or(0);
+
+        let (a, b, c, d, e, f, size) = match rotation {
+            90 => (0.0, 1.0, 1.0, 0.0, -y0, -x0, (height, width)),
+            180 => (-1.0, 0.0, 0.0, 1.0, x1, -y0, (width, height)),
+            270 => (0.0, -1.0, -1.0, 0.0, y1, x1, (height, width)),
+            _ => (1.0, 0.0, 0.0, -1.0, -x0, y1, (width, height)),
+        };
+        (
+            PageSize {
+                width: size.0,
+                height: size.1,
+                rotation,
+            },
+            Transform { a, b, c, d, e, f },
+        )
+    }
+
+    /// Displayed size of a page in points.
+    pub fn page_size(&self, page_idx: usize) -> Result<PageSize> {
+        Ok(self.display_space(self.page_id(page_idx)?).0)
+    }
+
+    /// A page's glyphs in displayed page space (PDF points, top-left origin,
+    /// `/Rotate` applied), plus the displayed page size.
+    pub fn page_glyphs(&self, page_idx: usize) -> Result<(Vec<PositionedGlyph>, PageSize)> {
+        let content = self.page_content(page_idx)?;
+        Ok((content.glyphs.clone(), content.size))
+    }
+
+    /// Image XObjects drawn on a page (directly or through forms) with
+    /// their placement in displayed page space and marked-content ID.
+    pub(crate) fn page_image_streams(
+        &self,
+        page_idx: usize,
+    ) -> Result<Vec<(BBox, &Stream, Option<u32>)>> {
+        Ok(self
+            .page_content(page_idx)?
+            .images
+            .iter()
+            .filter_map(|&(bbox, id, mcid)| {
+                let stream = deref(&self.doc, self.doc.get_object(id).ok()?)
+                    .as_stream()
+                    .ok()?;
+                Some((bbox, stream, mcid))
+            })
+            .collect())
+    }
+
+    /// Placements of the images drawn inside tagged content, by MCID.
+    pub fn page_marked_images(&self, page_idx: usize) -> Result<Vec<(BBox, u32)>> {
+        Ok(self
+            .page_content(page_idx)?
+            .images
+            .iter()
+            .filter_map(|&(bbox, _, mcid)| Some((bbox, mcid?)))
+            .collect())
+    }
+
+    /// Horizontal and vertical lines a page draws, as stroked segments or
+    /// thin filled rectangles, in displayed page space.
+    pub fn page_rulings(&self, page_idx: usize) -> Result<Vec<Ruling>> {
+        Ok(self.page_content(page_idx)?.rulings.clone())
+    }
+
+    /// The page's interpreted content, from the cache when it holds the page.
+    fn page_content(&self, page_idx: usize) -> Result<Rc<PageContent>> {
+        if let Some((cached_idx, content)) = &*self.content.borrow() {
+            if *cached_idx == page_idx {
+                return Ok(Rc::clone(content));
+            }
+        }
+        let content = Rc::new(self.interpret(page_idx)?);
+        *self.content.borrow_mut() = Some((page_idx, Rc::clone(&content)));
+        Ok(content)
+    }
+
+    /// Run a page's content stream and map what it draws to displayed page
+    /// space.
+    fn interpret(&self, page_idx: usize) -> Result<PageContent> {
+        let page_id = self.page_id(page_idx)?;
+        let (size, to_display) = self.display_space(page_id);
+        let content = self
+            .doc
+            .get_page_content(page_id)
+            .with_context(|| format!("failed to read content of page {}", page_idx + 1))?;
+        let resources = self
+            .inherited(page_id, b"Resources")
+            .and_then(|obj| obj.as_dict().ok());
+
+        let mut interpreter = Interpreter {
+            doc: &self.doc,
+            fonts: HashMap::new(),
+            glyphs: Vec::new(),
+            images: Vec::new(),
+            marked: Vec::new(),
+            strokes: Vec::new(),
+            fills: Vec::new(),
+        };
+        interpreter.run(&content, resources, IDENTITY, 0);
+
+        let glyphs = interpreter
+            .glyphs
+            .into_iter()
+            .map(|mut glyph| {
+                glyph.bbox = to_display.apply_bbox(&glyph.bbox);
+                glyph
+            })
+            .collect();
+        let images = interpreter
+            .images
+            .into_iter()
+            .map(|(ctm, id, mcid)| {
+                // The image fills the unit square of its CTM.
+                let corners = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+                    .map(|(x, y)| apply(&ctm, x, y));
+                let xs = corners.map(|(x, _)| x);
+                let ys = corners.map(|(_, y)| y);
+                let bbox = BBox::new(
+                    xs.into_iter().fold(f32::INFINITY, f32::min),
+                    ys.into_iter().fold(f32::INFINITY, f32::min),
+                    xs.into_iter().fold(f32::NEG_INFINITY, f32::max),
+                    ys.into_iter().fold(f32::NEG_INFINITY, f32::max),
+                );
+                (to_display.apply_bbox(&bbox), id, mcid)
+            })
+            .collect();
+        let strokes = interpreter
+            .strokes
+            .iter()
+            .filter_map(|&((x0, y0), (x1, y1))| {
+                Ruling::from_segment(to_display.apply(x0, y0), to_display.apply(x1, y1))
+            });
+        let fills = interpreter
+            .fills
+            .iter()
+            .filter_map(|bbox| Ruling::from_filled_rect(&to_display.apply_bbox(bbox)));
+        let rulings = strokes.chain(fills).collect();
+
+        Ok(PageContent {
+            size,
+            glyphs,
+            images,
+            rulings,
+        })
+    }
+
+    /// Word/line/block layout of a page's visible text, built from its
+    /// content stream.
+    pub fn page_layout(&self, page_idx: usize) -> Result<PageTextLayout> {
+        self.layout_where(page_idx, |glyph| !glyph.invisible)
+    }
+
+    /// Word/line/block layout of a page's invisible text: the text layer an
+    /// OCR tool laid over a scanned page. Empty on ordinary pages.
+    pub fn page_text_layer(&self, page_idx: usize) -> Result<PageTextLayout> {
+        self.layout_where(page_idx, |glyph| glyph.invisible)
+    }
+
+    fn layout_where(
+        &self,
+        page_idx: usize,
+        keep: impl Fn(&PositionedGlyph) -> bool,
+    ) -> Result<PageTextLayout> {
+        let (mut glyphs, size) = self.page_glyphs(page_idx)?;
+        glyphs.retain(keep);
+        Ok(PageTextLayout {
+            width: size.width,
+            height: size.height,
+            blocks: group_glyphs(glyphs),
+        })
+    }
+}
+
+/// Group glyphs (in content-stream order) into words, lines and blocks.
+pub fn group_glyphs(glyphs: Vec<PositionedGlyph>) -> Vec<TextBlockLayout> {
+    let mut lines: Vec<TextLineLayout> = Vec::new();
+    let mut words: Vec<GlyphRun> = Vec::new();
+    let mut word: Option<GlyphRun> = None;
+    let mut prev: Option<PositionedGlyph> = None;
+
+    let flush_word = |word: &mut Option<GlyphRun>, words: &mut Vec<GlyphRun>| {
+        if let Some(done) = word.take() {
+            if !done.text.trim().is_empty() {
+                words.push(done);
+            }
+        }
+    };
+    let flush_line = |words: &mut Vec<GlyphRun>, lines: &mut Vec<TextLineLayout>| {
+        if !words.is_empty() {
+            let bbox = words
+                .iter()
+                .skip(1)
+                .fold(words[0].bbox, |acc, w| acc.union(&w.bbox));
+            lines.push(TextLineLayout {
+                bbox,
+                words: std::mem::take(words),
+            });
+        }
+    };
+
+    for glyph in glyphs {
+        let size = glyph.font_size.max(1.0);
+        let (same_line, gap) = match &prev {
+            Some(p) => {
+                let baseline_shift = (glyph.bbox.y1 - p.bbox.y1).abs();
+                let gap = glyph.bbox.x0 - p.bbox.x1;
+                // Overlapping glyphs (e.g. stacked Hangul jamo) stay on the
+                // line; jumping back past the previous glyph starts a new one.
+                let moves_back = glyph.bbox.x0 < p.bbox.x0 - size * 0.5;
+                (
+                    baseline_shift < size * 0.4 && !moves_back && gap < size * 3.0,
+                    gap,
+                )
+            }
+            None => (false, 0.0),
+        };
+
+        if !same_line {
+            flush_word(&mut word, &mut words);
+            flush_line(&mut words, &mut lines);
+        } else if gap > size * 0.2 {
+            flush_word(&mut word, &mut words);
+        }
+
+        if glyph.text.trim().is_empty() {
+            flush_word(&mut word, &mut words);
+        } else {
+            match word.as_mut() {
+                Some(current) => {
+                    current.text.push_str(&glyph.text);
+                    current.bbox = current.bbox.union(&glyph.bbox);
+                }
+                None => {
+                    word = Some(GlyphRun {
+                        text: glyph.text.clone(),
+                        bbox: glyph.bbox,
+                        style: Some(glyph.style.clone()),
+                    });
+                }
+            }
+        }
+        prev = Some(glyph);
+    }
+    flush_word(&mut word, &mut words);
+    flush_line(&mut words, &mut lines);
+
+    let mut blocks: Vec<TextBlockLayout> = Vec::new();
+    for line in lines {
+        let line_height = line.bbox.height().max(1.0);
+        let joins_previous = blocks.last().map(|block| {
+            let last = &block.lines[block.lines.len() - 1].bbox;
+            let vertical_gap = line.bbox.y0 - last.y1;
+            let horizontal_overlap =
+                line.bbox.x1.min(block.bbox.x1) - line.bbox.x0.max(block.bbox.x0);
+            vertical_gap > -line_height * 0.5
+                && vertical_gap < line_height * 0.8
+                && horizontal_overlap > 0.0
+        });
+        match (joins_previous, blocks.last_mut()) {
+            (Some(true), Some(block)) => {
+                block.bbox = block.bbox.union(&line.bbox);
+                block.lines.push(line);
+            }
+            _ => blocks.push(TextBlockLayout {
+                bbox: line.bbox,
+                lines: vec![line],
+            }),
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::tables::Orientation;
+    use lopdf::{dictionary, Stream};
+
+    /// A one-page 600x800 PDF whose resources map `/F1` to the given font.
+    fn build_pdf(content: &[u8], font: impl FnOnce(&mut Document) -> Dictionary) -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font = font(&mut doc);
+        let font_id = doc.add_object(font);
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 600.into(), 800.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    #[test]
+    fn extracts_positioned_words_from_simple_font() {
+        let font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "ABCDEF+Helvetica-Bold",
+            "Encoding" => "WinAnsiEncoding",
+        };
+        let content = b"BT /F1 10 Tf 72 700 Td (Hello world) Tj 0 -14 Td (Next) Tj ET";
+        let pdf = NativePdf::from_document(build_pdf(content, |_| font));
+
+        let layout = pdf.page_layout(0).unwrap();
+        assert_eq!((layout.width, layout.height), (600.0, 800.0));
+        assert_eq!(layout.blocks.len(), 1);
+        let lines = &layout.blocks[0].lines;
+        assert_eq!(lines.len(), 2);
+        let words: Vec<&str> = lines[0].words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(words, vec!["Hello", "world"]);
+        let style = lines[0].words[0].style.as_ref().unwrap();
+        assert_eq!(style.font.as_deref(), Some("Helvetica-Bold"));
+        assert_eq!(style.size, Some(10.0));
+        assert!(style.bold && !style.italic);
+        assert_eq!(lines[0].words[0].bbox.x0, 72.0);
+        // Baseline at y=700 with a 10pt font: top edge is 8pt above it.
+        assert!((lines[0].words[0].bbox.y0 - 92.0).abs() < 0.01);
+        assert_eq!(lines[1].words[0].text, "Next");
+    }
+
+    #[test]
+    fn tags_glyphs_with_marked_content() {
+        let font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        };
+        let content = b"/P <</MCID 3>> BDC BT /F1 10 Tf 72 700 Td (A) Tj \
+            /Span BMC (B) Tj EMC ET EMC \
+            /Artifact BMC BT /F1 10 Tf 72 40 Td (7) Tj ET EMC \
+            BT /F1 10 Tf 72 600 Td (C) Tj ET";
+        let pdf = NativePdf::from_document(build_pdf(content, |_| font));
+
+        let (glyphs, _) = pdf.page_glyphs(0).unwrap();
+        let marks: Vec<(&str, Option<u32>, bool)> = glyphs
+            .iter()
+            .map(|glyph| (glyph.text.as_str(), glyph.mcid, glyph.artifact))
+            .collect();
+        assert_eq!(
+            marks,
+            vec![
+                ("A", Some(3), false),
+                ("B", Some(3), false),
+                ("7", None, true),
+                ("C", None, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn separates_invisible_text_layer() {
+        let font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        };
+        let content = b"BT /F1 10 Tf 72 700 Td (Shown) Tj ET \
+            q BT 3 Tr /F1 10 Tf 72 600 Td (Hidden) Tj ET Q \
+            BT /F1 10 Tf 72 500 Td (Again) Tj ET";
+        let pdf = NativePdf::from_document(build_pdf(content, |_| font));
+
+        let words = |layout: PageTextLayout| -> Vec<String> {
+            layout
+                .blocks
+                .into_iter()
+                .flat_map(|block| block.lines)
+                .flat_map(|line| line.words)
+                .map(|word| word.text)
+                .collect()
+        };
+        assert_eq!(words(pdf.page_layout(0).unwrap()), vec!["Shown", "Again"]);
+        assert_eq!(words(pdf.page_text_layer(0).unwrap()), vec!["Hidden"]);
+    }
+
+    #[test]
+    fn maps_composite_font_codes_through_to_unicode() {
+        let cmap = b"/CIDInit /ProcSet findresource begin 12 dict begin begincmap\n\
+            1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+            1 beginbfchar <0001> <D55C> endbfchar\n\
+            1 beginbfrange <0002> <0003> <AE00> endbfrange\n\
+            endcmap end end";
+        let doc = build_pdf(b"BT /F1 12 Tf 10 10 Td <00010002> Tj ET", |doc| {
+            let to_unicode = doc.add_object(Stream::new(dictionary! {}, cmap.to_vec()));
+            dictionary! {
+                "Type" => "Font",
+                "Subtype" => "Type0",
+                "BaseFont" => "NanumGothic",
+                "Encoding" => "Identity-H",
+                "ToUnicode" => to_unicode,
+                "DescendantFonts" => vec![Object::Dictionary(dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "CIDFontType2",
+                    "DW" => 1000,
+                })],
+            }
+        });
+        let pdf = NativePdf::from_document(doc);
+
+        let layout = pdf.page_layout(0).unwrap();
+        assert_eq!(layout.blocks[0].lines[0].words[0].text, "한글");
+    }
+
+    #[test]
+    fn keeps_code_ranges_at_the_top_of_the_code_space() {
+        let cmap = CMap::parse(
+            b"1 beginbfrange <FFFFFFFE> <FFFFFFFF> <0041> endbfrange\n\
+              1 beginbfrange <FFFFFFFF> <FFFFFFFF> [<0042> <0043>] endbfrange",
+        );
+        assert_eq!(
+            cmap.map.get(&(4, u32::MAX - 1)).map(String::as_str),
+            Some("A")
+        );
+        assert_eq!(cmap.map.get(&(4, u32::MAX)).map(String::as_str), Some("B"));
+
+        let doc = Document::with_version("1.5");
+        let w = Object::Array(vec![
+            Object::Integer(u32::MAX as i64),
+            Object::Array(vec![500.into(), 600.into()]),
+            Object::Integer(u32::MAX as i64),
+            Object::Integer(u32::MAX as i64),
+            700.into(),
+        ]);
+        let widths = parse_cid_widths(&doc, &w);
+        assert_eq!(widths.len(), 1);
+        assert_eq!(widths.get(&u32::MAX), Some(&700.0));
+    }
+
+    #[test]
+    fn applies_page_rotation_to_size_and_boxes() {
+        let font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        };
+        let mut doc = build_pdf(b"BT /F1 10 Tf 72 700 Td (Up) Tj ET", |_| font);
+        let page_id = doc.get_pages()[&1];
+        doc.get_dictionary_mut(page_id).unwrap().set("Rotate", 90);
+        let pdf = NativePdf::from_document(doc);
+
+        let size = pdf.page_size(0).unwrap();
+        assert_eq!((size.width, size.height, size.rotation), (800.0, 600.0, 90));
+        // Rotated clockwise: the page top now sits on the right, so the
+        // baseline height becomes the horizontal position.
+        let layout = pdf.page_layout(0).unwrap();
+        let bbox = layout.blocks[0].lines[0].words[0].bbox;
+        assert!((bbox.y0 - 72.0).abs() < 0.01);
+        assert!(bbox.x0 > 690.0 && bbox.x1 < 712.0);
+    }
+
+    #[test]
+    fn records_stroked_lines_and_thin_fills_as_rulings() {
+        let font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        };
+        // A stroked frame, one stroked rule, a curve, a thin filled bar and
+        // a large filled background that is not a line.
+        let content = b"q 2 0 0 2 0 0 cm 25 300 150 50 re S Q \
+            50 650 m 350 650 l S \
+            50 500 m 100 550 150 550 200 500 c S \
+            50 619.5 300 1 re f \
+            0 0 600 100 re f";
+        let pdf = NativePdf::from_document(build_pdf(content, |_| font));
+
+        let rulings = pdf.page_rulings(0).unwrap();
+        let horizontal = |y: f32| Ruling {
+            orientation: Orientation::Horizontal,
+            position: y,
+            start: 50.0,
+            end: 350.0,
+        };
+        let vertical = |x: f32| Ruling {
+            orientation: Orientation::Vertical,
+            position: x,
+            start: 100.0,
+            end: 200.0,
+        };
+        assert_eq!(
+            rulings,
+            vec![
+                horizontal(200.0),
+                vertical(350.0),
+                horizontal(100.0),
+                vertical(50.0),
+                horizontal(150.0),
+                horizontal(180.0),
+            ]
+        );
+
+        // Later reads of the same page reuse the interpreted content.
+        let content = pdf.page_content(0).unwrap();
+        assert!(Rc::ptr_eq(&content, &pdf.page_content(0).unwrap()));
+        assert_eq!(content.rulings, rulings);
+    }
+
+    #[test]
+    fn strips_inline_images() {
+        let stripped = strip_inline_images(b"q BI /W 1 /H 1 ID \x00\xff EI Q BT ET");
+        assert_eq!(stripped, b"q   Q BT ET".to_vec());
+    }
+}
@@ -0,0 +1,326 @@
+//! Embedded raster images read natively with `lopdf`.
+//!
+//! JPEG and JPEG 2000 streams are kept byte for byte. Other images are
+//! decoded to samples and written as PNG at their own pixel size, for the
+//! common colour spaces (gray, RGB, CMYK, ICC-based and indexed over those)
+//! and bit depths. Soft masks are not applied.
+
+use anyhow::Result;
+use lopdf::{Document, Object, Stream};
+use std::io::Cursor;
+
+use crate::parser::pdf_content::{deref, number, NativePdf};
+use crate::parser::PageImage;
+
+impl NativePdf {
+    /// Images drawn on a page, in displayed page space. Images whose
+    /// encoding or colour space is not supported are left out.
+    pub fn page_images(&self, page_idx: usize) -> Result<Vec<PageImage>> {
+        Ok(self
+            .page_image_streams(page_idx)?
+            .into_iter()
+            .filter_map(|(bbox, stream, _)| {
+                let (data, extension) = encode_image(&self.doc, stream)?;
+                Some(PageImage {
+                    bbox,
+                    data,
+                    extension: extension.to_string(),
+                })
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ColorSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+    Indexed {
+        base: Box<ColorSpace>,
+        lookup: Vec<u8>,
+    },
+}
+
+impl ColorSpace {
+    fn components(&self) -> usize {
+        match self {
+            ColorSpace::Gray | ColorSpace::Indexed { .. } => 1,
+            ColorSpace::Rgb => 3,
+            ColorSpace::Cmyk => 4,
+        }
+    }
+
+    fn parse(doc: &Document, obj: &Object) -> Option<Self> {
+        match deref(doc, obj) {
+            Object::Name(name) => match name.as_slice() {
+                b"DeviceGray" | b"CalGray" | b"G" => Some(ColorSpace::Gray),
+                b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(ColorSpace::Rgb),
+                b"DeviceCMYK" | b"CMYK" => Some(ColorSpace::Cmyk),
+                _ => None,
+            },
+            Object::Array(items) => {
+                let family = items.first()?.as_name().ok()?;
+                match family {
+                    b"CalGray" => Some(ColorSpace::Gray),
+                    b"CalRGB" => Some(ColorSpace::Rgb),
+                    b"ICCBased" => {
+                        let profile = deref(doc, items.get(1)?).as_stream().ok()?;
+                        let n = profile.dict.get(b"N").ok().and_then(|n| number(doc, n));
+                        match n.map(|n| n as u32) {
+                            Some(1) => Some(ColorSpace::Gray),
+                            Some(3) => Some(ColorSpace::Rgb),
+                            Some(4) => Some(ColorSpace::Cmyk),
+                            _ => Self::parse(doc, profile.dict.get(b"Alternate").ok()?),
+                        }
+                    }
+                    b"Indexed" | b"I" => {
+                        let base = Self::parse(doc, items.get(1)?)?;
+                        let lookup = match deref(doc, items.get(3)?) {
+                            Object::String(bytes, _) => bytes.clone(),
+                            Object::Stream(stream) => stream.get_plain_content().ok()?,
+                            _ => return None,
+                        };
+                        Some(ColorSpace::Indexed {
+                            base: Box::new(base),
+                            lookup,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Convert one pixel's 8-bit components (or palette index) to RGB.
+    fn to_rgb(&self, components: &[u8]) -> [u8; 3] {
+        match self {
+            ColorSpace::Gray => [components[0]; 3],
+            ColorSpace::Rgb => [components[0], components[1], components[2]],
+            ColorSpace::Cmyk => {
+                let k = 255 - u16::from(components[3]);
+                let channel = |c: u8| ((255 - u16::from(c)) * k / 255) as u8;
+                [
+                    channel(components[0]),
+                    channel(components[1]),
+                    channel(components[2]),
+                ]
+            }
+            ColorSpace::Indexed { base, lookup } => {
+                let width = base.components();
+                let start = usize::from(components[0]) * width;
+                match lookup.get(start..start + width) {
+                    Some(entry) => base.to_rgb(entry),
+                    None => [0; 3],
+                }
+            }
+        }
+    }
+}
+
+/// Encoded bytes and file extension for an image XObject.
+fn encode_image(doc: &Document, stream: &Stream) -> Option<(Vec<u8>, &'static str)> {
+    let filters = stream.filters().unwrap_or_default();
+    match filters.last().map(String::as_str) {
+        Some("DCTDecode") if filters.len() == 1 => return Some((stream.content.clone(), "jpg")),
+        Some("JPXDecode") if filters.len() == 1 => return Some((stream.content.clone(), "jp2")),
+        Some("FlateDecode" | "LZWDecode" | "ASCII85Decode") | None => {}
+        Some(_) => return None,
+    }
+
+    let dict = &stream.dict;
+    let dimension = |key: &[u8]| {
+        dict.get(key)
+            .ok()
+            .and_then(|obj| number(doc, obj))
+            .filter(|value| *value >= 1.0)
+            .map(|value| value as u32)
+    };
+    let (width, height) = (dimension(b"Width")?, dimension(b"Height")?);
+    let image_mask = dict
+        .get(b"ImageMask")
+        .ok()
+        .and_then(|obj| deref(doc, obj).as_bool().ok())
+        .unwrap_or(false);
+    let color_space = if image_mask {
+        ColorSpace::Gray
+    } else {
+        ColorSpace::parse(doc, dict.get(b"ColorSpace").ok()?)?
+    };
+    let bits = if image_mask {
+        1
+    } else {
+        dict.get(b"BitsPerComponent")
+            .ok()
+            .and_then(|obj| number(doc, obj))
+            .map(|bits| bits as u32)
+            .unwrap_or(8)
+    };
+    if !matches!(bits, 1 | 2 | 4 | 8 | 16) {
+        return None;
+    }
+    let inverted = dict
+        .get(b"Decode")
+        .ok()
+        .and_then(|obj| deref(doc, obj).as_array().ok())
+        .and_then(|decode| Some(number(doc, decode.first()?)? > number(doc, decode.get(1)?)?))
+        .unwrap_or(false);
+
+    // lopdf refuses to decode image streams directly; the filters are the
+    // same as for any other stream.
+    let mut plain = stream.clone();
+    plain.dict.remove(b"Subtype");
+    let data = plain.get_plain_content().ok()?;
+
+    let components = color_space.components();
+    let samples = unpack_samples(&data, width, height, components, bits)?;
+    let indexed = matches!(color_space, ColorSpace::Indexed { .. });
+    let max = ((1u32 << bits.min(8)) - 1) as u16;
+    let mut pixels = Vec::with_capacity(samples.len() / components * 3);
+    let mut scaled = [0u8; 4];
+    for pixel in samples.chunks_exact(components) {
+        for (slot, &value) in scaled.iter_mut().zip(pixel) {
+            *slot = if indexed {
+                value
+            } else if inverted {
+                255 - (u16::from(value) * 255 / max) as u8
+            } else {
+                (u16::from(value) * 255 / max) as u8
+            };
+        }
+        pixels.extend_from_slice(&color_space.to_rgb(&scaled[..components]));
+    }
+
+    let image = image::RgbImage::from_raw(width, height, pixels)?;
+    let mut png = Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut png, image::ImageFormat::Png)
+        .ok()?;
+    Some((png.into_inner(), "png"))
+}
+
+/// Split packed rows into one value per component. 16-bit samples keep
+/// their high byte; rows start on a byte boundary.
+fn unpack_samples(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    components: usize,
+    bits: u32,
+) -> Option<Vec<u8>> {
+    let per_row = width as usize * components;
+    let row_bytes = (per_row * bits as usize).div_ceil(8);
+    if data.len() < row_bytes * height as usize {
+        return None;
+    }
+
+    let mut samples = Vec::with_capacity(per_row * height as usize);
+    for row in data.chunks_exact(row_bytes).take(height as usize) {
+        match bits {
+            8 => samples.extend_from_slice(&row[..per_row]),
+            16 => samples.extend(row.chunks_exact(2).take(per_row).map(|pair| pair[0])),
+            _ => {
+                let per_byte = 8 / bits as usize;
+                let mask = (1u8 << bits) - 1;
+                samples.extend((0..per_row).map(|idx| {
+                    let shift = 8 - bits as usize * (idx % per_byte + 1);
+                    (row[idx / per_byte] >> shift) & mask
+                }));
+            }
+        }
+    }
+    Some(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::geometry::BBox;
+    use lopdf::{dictionary, Stream};
+
+    fn page_with_image(image: Stream) -> NativePdf {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let image_id = doc.add_object(image);
+        let content = doc.add_object(Stream::new(
+            dictionary! {},
+            b"q 200 0 0 100 50 600 cm /Im1 Do Q".to_vec(),
+        ));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content,
+            "Resources" => dictionary! {
+                "XObject" => dictionary! { "Im1" => image_id },
+            },
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "MediaBox" => vec![0.into(), 0.into(), 600.into(), 800.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        NativePdf::from_document(doc)
+    }
+
+    #[test]
+    fn keeps_jpeg_streams_verbatim() {
+        let jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0xFF, 0xD9];
+        let pdf = page_with_image(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 640,
+                "Height" => 320,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+                "Filter" => "DCTDecode",
+            },
+            jpeg.clone(),
+        ));
+
+        let images = pdf.page_images(0).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].bbox, BBox::new(50.0, 100.0, 250.0, 200.0));
+        assert_eq!(images[0].extension, "jpg");
+        assert_eq!(images[0].data, jpeg);
+    }
+
+    #[test]
+    fn encodes_indexed_samples_as_png() {
+        // Two 4-bit palette entries per byte: red, blue, then padding.
+        let pdf = page_with_image(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 2,
+                "ColorSpace" => vec![
+                    "Indexed".into(),
+                    "DeviceRGB".into(),
+                    1.into(),
+                    Object::string_literal(vec![255, 0, 0, 0, 0, 255]),
+                ],
+                "BitsPerComponent" => 4,
+            },
+            vec![0x01, 0x10],
+        ));
+
+        let images = pdf.page_images(0).unwrap();
+        assert_eq!(images[0].extension, "png");
+        let decoded = image::load_from_memory(&images[0].data).unwrap().to_rgb8();
+        assert_eq!(decoded.dimensions(), (2, 2));
+        assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(decoded.get_pixel(1, 0).0, [0, 0, 255]);
+        assert_eq!(decoded.get_pixel(0, 1).0, [0, 0, 255]);
+    }
+}
@@ -1,29 +1,668 @@
 use anyhow::Result;
+#[cfg(feature = "native-pdf")]
+use std::collections::HashSet;
 use std::path::PathBuf;
 
-use crate::core::model::{Block, Line, PageHypothesis, Provenance, Span};
+use crate::core::geometry::BBox;
+use crate::core::model::{
+    Block, DocumentMetadata, Line, OutlineEntry, PageHypothesis, PageLink, PageSize, Provenance,
+    Span, TableCell,
+};
+#[cfg(feature = "native-pdf")]
+use crate::parser::hangul::combine_hangul;
+#[cfg(feature = "native-pdf")]
+use crate::parser::pdf_content::{group_glyphs, NativePdf, PositionedGlyph};
 use crate::parser::pdf_reader::PdfReader;
-use crate::parser::text_extractor::extract_glyph_runs;
-use crate::parser::ParserTrack;
+#[cfg(feature = "native-pdf")]
+use crate::parser::pdf_structure::{FormField, FormFieldKind};
+#[cfg(feature = "native-pdf")]
+use crate::parser::pdf_tags::{StructTree, TaggedBlock, TaggedCell, TaggedKind};
+#[cfg(feature = "native-pdf")]
+use crate::parser::tables::detect_tables;
+use crate::parser::tables::DetectedTable;
+#[cfg(feature = "native-pdf")]
+use crate::parser::text_extractor::normalize_page_layout;
+use crate::parser::text_extractor::{
+    extract_glyph_runs, extract_page_layout, GlyphRun, PageTextLayout,
+};
+use crate::parser::{PageImage, ParserTrack};
 
 #[derive(Debug, Clone)]
 pub struct PdfParser {
     path: PathBuf,
+    password: Option<String>,
+    /// Content-stream backend; `None` when lopdf cannot load the file, in
+    /// which case every page goes through poppler.
+    #[cfg(feature = "native-pdf")]
+    native: Option<NativePdf>,
+    /// Structure tree of a tagged PDF; pages it covers take their blocks
+    /// and reading order from it.
+    #[cfg(feature = "native-pdf")]
+    structure: Option<StructTree>,
 }
 
 impl PdfParser {
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self::with_password(path, None)
+    }
+
+    /// Open a PDF that may be encrypted; `password` is tried as both the
+    /// owner and the user password.
+    pub fn with_password(path: PathBuf, password: Option<String>) -> Self {
+        #[cfg(feature = "native-pdf")]
+        let native = match NativePdf::open(&path, password.as_deref()) {
+            Ok(native) => Some(native),
+            Err(err) => {
+                eprintln!("native PDF backend unavailable, using poppler: {err:#}");
+                None
+            }
+        };
+
+        #[cfg(feature = "native-pdf")]
+        let structure = native.as_ref().and_then(NativePdf::structure_tree);
+
+        Self {
+            path,
+            password,
+            #[cfg(feature = "native-pdf")]
+            native,
+            #[cfg(feature = "native-pdf")]
+            structure,
+        }
+    }
+
+    /// Words of a page with their boxes, in reading order; empty when the
+    /// page has no usable text.
+    pub fn page_words(&self, page_idx: usize) -> Vec<GlyphRun> {
+        self.native_page_layout(page_idx)
+            .or_else(|| extract_page_layout(&self.path, self.password.as_deref(), page_idx))
+            .map(|layout| {
+                layout
+                    .blocks
+                    .into_iter()
+                    .flat_map(|block| block.lines)
+                    .flat_map(|line| line.words)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn reader(&self) -> Result<PdfReader> {
+        Ok(PdfReader::new(self.path.clone())?.with_password(self.password.clone()))
+    }
+
+    /// Word layout from the native content-stream backend, if it produced
+    /// usable text for this page.
+    #[cfg(feature = "native-pdf")]
+    fn native_page_layout(&self, page_idx: usize) -> Option<PageTextLayout> {
+        let layout = self.native.as_ref()?.page_layout(page_idx).ok()?;
+        normalize_page_layout(layout)
+    }
+
+    #[cfg(not(feature = "native-pdf"))]
+    fn native_page_layout(&self, _page_idx: usize) -> Option<PageTextLayout> {
+        None
+    }
+
+    /// Blocks of a page covered by the structure tree, in its reading
+    /// order. `None` for untagged pages and for pages whose text is too
+    /// damaged to use, which then go through layout analysis.
+    #[cfg(feature = "native-pdf")]
+    fn tagged_blocks(&self, page_idx: usize) -> Option<Vec<Block>> {
+        let (native, structure) = (self.native.as_ref()?, self.structure.as_ref()?);
+        let tagged = structure.page_blocks(native.page_id(page_idx).ok()?);
+        if tagged.is_empty() {
+            return None;
+        }
+        let (mut glyphs, size) = native.page_glyphs(page_idx).ok()?;
+        glyphs.retain(|glyph| !glyph.invisible);
+        normalize_page_layout(PageTextLayout {
+            width: size.width,
+            height: size.height,
+            blocks: group_glyphs(glyphs.clone()),
+        })?;
+        let images = native.page_marked_images(page_idx).unwrap_or_else(|err| {
+            eprintln!("Warning: {err:#}");
+            Vec::new()
+        });
+        Some(tagged_page_blocks(tagged, &glyphs, &images))
+    }
+
+    #[cfg(not(feature = "native-pdf"))]
+    fn tagged_blocks(&self, _page_idx: usize) -> Option<Vec<Block>> {
+        None
+    }
+
+    /// The invisible text layer another OCR tool left on the page, as
+    /// [`Provenance::TextLayer`] blocks. Fusion weighs it against our own
+    /// OCR instead of taking it as the page's text.
+    #[cfg(feature = "native-pdf")]
+    fn text_layer_blocks(&self, page_idx: usize) -> Vec<Block> {
+        let Some(native) = &self.native else {
+            return Vec::new();
+        };
+        match native.page_text_layer(page_idx) {
+            Ok(layout) => normalize_page_layout(layout)
+                .map(|layout| layout_to_blocks(layout, Provenance::TextLayer))
+                .unwrap_or_default(),
+            Err(err) => {
+                eprintln!("failed to read text layer: {err:#}");
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(not(feature = "native-pdf"))]
+    fn text_layer_blocks(&self, _page_idx: usize) -> Vec<Block> {
+        Vec::new()
+    }
+
+    /// Filled-in AcroForm fields of the page as key/value text blocks. Their
+    /// values live in the form, not the content stream, so neither text
+    /// extraction nor OCR sees them.
+    #[cfg(feature = "native-pdf")]
+    fn form_field_blocks(&self, page_idx: usize) -> Vec<Block> {
+        let Some(native) = &self.native else {
+            return Vec::new();
+        };
+        match native.page_form_fields(page_idx) {
+            Ok(fields) => fields.into_iter().filter_map(form_field_block).collect(),
+            Err(err) => {
+                eprintln!("failed to read form fields: {err:#}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Tables outlined by the page's vector rulings, with `words` (page
+    /// points) deciding which grids hold content.
+    #[cfg(feature = "native-pdf")]
+    fn page_tables(&self, page_idx: usize, words: &[BBox]) -> Vec<DetectedTable> {
+        let Some(native) = &self.native else {
+            return Vec::new();
+        };
+        let rulings = match native.page_rulings(page_idx) {
+            Ok(rulings) => rulings,
+            Err(err) => {
+                eprintln!("failed to read ruling lines: {err:#}");
+                return Vec::new();
+            }
+        };
+        detect_tables(&rulings, words)
+    }
+
+    #[cfg(not(feature = "native-pdf"))]
+    fn page_tables(&self, _page_idx: usize, _words: &[BBox]) -> Vec<DetectedTable> {
+        Vec::new()
+    }
+
+    #[cfg(not(feature = "native-pdf"))]
+    fn form_field_blocks(&self, _page_idx: usize) -> Vec<Block> {
+        Vec::new()
+    }
+}
+
+/// Blocks for a page's tagged elements, numbered in structure order. Text
+/// elements group their glyphs into lines as untagged text does; figures
+/// and formulas cover their glyphs and images; table cells hold their own
+/// lines. Untagged content that is not an artifact follows as ordinary
+/// blocks without an order.
+#[cfg(feature = "native-pdf")]
+fn tagged_page_blocks(
+    tagged: Vec<TaggedBlock>,
+    glyphs: &[PositionedGlyph],
+    images: &[(BBox, u32)],
+) -> Vec<Block> {
+    let lines_of = |mcids: &[u32]| -> Vec<Line> {
+        let owned = glyphs
+            .iter()
+            .filter(|glyph| glyph.mcid.is_some_and(|mcid| mcids.contains(&mcid)))
+            .cloned()
+            .collect();
+        group_glyphs(owned)
+            .into_iter()
+            .flat_map(|block| block.lines)
+            .map(|line| {
+                let words = line
+                    .words
+                    .into_iter()
+                    .map(|mut word| {
+                        word.text = combine_hangul(&word.text);
+                        word
+                    })
+                    .collect();
+                words_to_line(words)
+            })
+            .collect()
+    };
+    let extent = |lines: &[Line], mcids: &[u32]| -> Option<BBox> {
+        let spans = lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .map(|span| span.bbox);
+        let pictures = images
+            .iter()
+            .filter(|(_, mcid)| mcids.contains(mcid))
+            .map(|(bbox, _)| *bbox);
+        spans.chain(pictures).reduce(|a, b| a.union(&b))
+    };
+
+    let mut blocks = Vec::new();
+    for block in &tagged {
+        let order = Some(blocks.len());
+        let lines = lines_of(&block.mcids);
+        let Some(bbox) = extent(&lines, &block.mcids) else {
+            continue;
+        };
+        blocks.push(match &block.kind {
+            TaggedKind::Text(role) => {
+                if lines.is_empty() {
+                    continue;
+                }
+                Block::TextBlock {
+                    bbox,
+                    lines,
+                    confidence: 0.6,
+                    source: Provenance::Parser,
+                    role: Some(*role),
+                    order,
+                    tracks: Vec::new(),
+                    debug: None,
+                }
+            }
+            TaggedKind::Figure => Block::FigureBlock {
+                bbox,
+                confidence: 0.6,
+                source: Provenance::Parser,
+                image: None,
+                order,
+                tracks: Vec::new(),
+                debug: None,
+            },
+            TaggedKind::Formula => Block::MathBlock {
+                bbox,
+                confidence: 0.6,
+                source: Provenance::Parser,
+                latex: None,
+                order,
+                tracks: Vec::new(),
+                debug: None,
+            },
+            TaggedKind::Table(cells) => Block::TableBlock {
+                bbox,
+                confidence: 0.6,
+                source: Provenance::Parser,
+                cells: tagged_cells(cells, &lines_of, bbox),
+                order,
+                tracks: Vec::new(),
+                debug: None,
+            },
+        });
+    }
+
+    let tagged_mcids: HashSet<u32> = tagged
+        .iter()
+        .flat_map(|block| block.mcids.iter().copied())
+        .collect();
+    let untagged: Vec<PositionedGlyph> = glyphs
+        .iter()
+        .filter(|glyph| {
+            !glyph.artifact && glyph.mcid.is_none_or(|mcid| !tagged_mcids.contains(&mcid))
+        })
+        .cloned()
+        .collect();
+    blocks.extend(layout_to_blocks(
+        PageTextLayout {
+            width: 0.0,
+            height: 0.0,
+            blocks: group_glyphs(untagged),
+        },
+        Provenance::Parser,
+    ));
+    blocks
+}
+
+/// Table cells with their lines. Empty cells take the row's vertical and
+/// the column's horizontal extent from their filled neighbours, or the
+/// table's where there are none.
+#[cfg(feature = "native-pdf")]
+fn tagged_cells(
+    cells: &[TaggedCell],
+    lines_of: &dyn Fn(&[u32]) -> Vec<Line>,
+    table: BBox,
+) -> Vec<TableCell> {
+    let filled: Vec<(&TaggedCell, Vec<Line>, Option<BBox>)> = cells
+        .iter()
+        .map(|cell| {
+            let lines = lines_of(&cell.mcids);
+            let bbox = lines
+                .iter()
+                .flat_map(|line| &line.spans)
+                .map(|span| span.bbox)
+                .reduce(|a, b| a.union(&b));
+            (cell, lines, bbox)
+        })
+        .collect();
+    let span_of = |matches: &dyn Fn(&TaggedCell) -> bool| {
+        filled
+            .iter()
+            .filter(|(cell, _, _)| matches(cell))
+            .filter_map(|(_, _, bbox)| *bbox)
+            .reduce(|a, b| a.union(&b))
+    };
+
+    filled
+        .iter()
+        .map(|(cell, lines, bbox)| {
+            let bbox = bbox.unwrap_or_else(|| {
+                let row = span_of(&|other| other.row == cell.row).unwrap_or(table);
+                let col = span_of(&|other| other.col == cell.col).unwrap_or(table);
+                BBox::new(col.x0, row.y0, col.x1, row.y1)
+            });
+            TableCell {
+                row: cell.row,
+                col: cell.col,
+                rowspan: cell.rowspan,
+                colspan: cell.colspan,
+                bbox,
+                lines: lines.clone(),
+                header: cell.header,
+            }
+        })
+        .collect()
+}
+
+/// A `label: value` block over the field's widget. Empty text fields, push
+/// buttons, signatures and unselected radio options are left out; check
+/// boxes always show their state.
+#[cfg(feature = "native-pdf")]
+fn form_field_block(field: FormField) -> Option<Block> {
+    let value = match field.kind {
+        FormFieldKind::Checkbox => {
+            if field.value.is_some() {
+                "☑".to_string()
+            } else {
+                "☐".to_string()
+            }
+        }
+        FormFieldKind::Text | FormFieldKind::Choice | FormFieldKind::Radio => {
+            field.value.filter(|value| !value.trim().is_empty())?
+        }
+        FormFieldKind::PushButton | FormFieldKind::Signature => return None,
+    };
+
+    let span = |text: String, bbox: BBox| Span {
+        text,
+        bbox,
+        source: Provenance::Parser,
+        style: None,
+        link: None,
+        revision: None,
+    };
+    let label = field.label.unwrap_or(field.name);
+    // Multi-line text fields separate lines with CR, LF or both.
+    let mut lines: Vec<Line> = value
+        .split(['\r', '\n'])
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Line {
+            spans: vec![span(line.to_string(), field.bbox)],
+        })
+        .collect();
+    lines
+        .first_mut()?
+        .spans
+        .insert(0, span(format!("{label}: "), field.bbox));
+
+    Some(Block::TextBlock {
+        bbox: field.bbox,
+        lines,
+        confidence: 0.6,
+        source: Provenance::Parser,
+        role: None,
+        order: None,
+        tracks: Vec::new(),
+        debug: None,
+    })
+}
+
+/// A table block whose cells hold the layout words inside the table. The
+/// words move out of `layout` so they are not emitted twice. A word goes to
+/// the cell holding its center, and words from one layout line stay on one
+/// cell line. Without header rules, a first row set entirely in bold is
+/// taken as the header.
+fn table_block(table: &DetectedTable, layout: &mut PageTextLayout) -> Block {
+    let cell_boxes: Vec<BBox> = table
+        .cells
+        .iter()
+        .map(|cell| table.cell_bbox(cell))
+        .collect();
+    let mut cell_lines: Vec<Vec<Line>> = vec![Vec::new(); cell_boxes.len()];
+
+    for block in &mut layout.blocks {
+        let mut changed = false;
+        for line in &mut block.lines {
+            let mut taken: Vec<Vec<GlyphRun>> = vec![Vec::new(); cell_boxes.len()];
+            line.words.retain(|word| {
+                let (cx, cy) = word.bbox.center();
+                let cell = cell_boxes
+                    .iter()
+                    .position(|b| cx >= b.x0 && cx <= b.x1 && cy >= b.y0 && cy <= b.y1);
+                match cell {
+                    Some(idx) => {
+                        taken[idx].push(word.clone());
+                        false
+                    }
+                    None => true,
+                }
+            });
+            for (idx, words) in taken.into_iter().enumerate() {
+                if !words.is_empty() {
+                    changed = true;
+                    cell_lines[idx].push(words_to_line(words));
+                }
+            }
+            if let Some(first) = line.words.first() {
+                line.bbox = line
+                    .words
+                    .iter()
+                    .fold(first.bbox, |bbox, word| bbox.union(&word.bbox));
+            }
+        }
+        if changed {
+            block.lines.retain(|line| !line.words.is_empty());
+            if let Some(first) = block.lines.first() {
+                block.bbox = block
+                    .lines
+                    .iter()
+                    .fold(first.bbox, |bbox, line| bbox.union(&line.bbox));
+            }
+        }
+    }
+    layout.blocks.retain(|block| !block.lines.is_empty());
+
+    let bold_first_row = table.rows.len() > 2
+        && table
+            .cells
+            .iter()
+            .zip(&cell_lines)
+            .filter(|(cell, _)| cell.row == 0)
+            .all(|(_, lines)| {
+                let mut spans = lines.iter().flat_map(|line| &line.spans).peekable();
+                spans.peek().is_some()
+                    && spans.all(|span| span.style.as_ref().is_some_and(|style| style.bold))
+            });
+    let header_rows = match table.header_rows {
+        0 if bold_first_row => 1,
+        rows => rows,
+    };
+
+    let cells = table
+        .cells
+        .iter()
+        .zip(cell_boxes)
+        .zip(cell_lines)
+        .map(|((cell, bbox), lines)| TableCell {
+            row: cell.row,
+            col: cell.col,
+            rowspan: cell.rowspan,
+            colspan: cell.colspan,
+            bbox,
+            lines,
+            header: cell.row < header_rows,
+        })
+        .collect();
+    Block::TableBlock {
+        bbox: table.bbox,
+        confidence: 0.6,
+        source: Provenance::Parser,
+        cells,
+        order: None,
+        tracks: Vec::new(),
+        debug: None,
     }
 }
 
+/// One span per word; the separating space is kept on the preceding word
+/// so spans concatenate.
+fn words_to_line(words: Vec<GlyphRun>) -> Line {
+    let word_count = words.len();
+    let spans = words
+        .into_iter()
+        .enumerate()
+        .map(|(idx, word)| Span {
+            text: if idx + 1 < word_count {
+                format!("{} ", word.text)
+            } else {
+                word.text
+            },
+            bbox: word.bbox,
+            source: Provenance::Parser,
+            style: word.style,
+            link: None,
+            revision: None,
+        })
+        .collect();
+    Line { spans }
+}
+
+/// Convert word-level layout into blocks from `source`; coordinates stay in
+/// page points. Each word becomes one span.
+fn layout_to_blocks(layout: PageTextLayout, source: Provenance) -> Vec<Block> {
+    layout
+        .blocks
+        .into_iter()
+        .map(|block| {
+            let lines = block
+                .lines
+                .into_iter()
+                .map(|line| {
+                    let mut line = words_to_line(line.words);
+                    for span in &mut line.spans {
+                        span.source = source;
+                    }
+                    line
+                })
+                .collect();
+            Block::TextBlock {
+                bbox: block.bbox,
+                lines,
+                confidence: 0.6,
+                source,
+                role: None,
+                order: None,
+                tracks: Vec::new(),
+                debug: None,
+            }
+        })
+        .collect()
+}
+
 impl ParserTrack for PdfParser {
     fn page_count(&self) -> Result<usize> {
-        PdfReader::new(self.path.clone())?.page_count()
+        #[cfg(feature = "native-pdf")]
+        if let Some(native) = &self.native {
+            return Ok(native.page_count());
+        }
+        self.reader()?.page_count()
+    }
+
+    fn page_size(&self, page_idx: usize) -> Result<PageSize> {
+        #[cfg(feature = "native-pdf")]
+        if let Some(size) = self
+            .native
+            .as_ref()
+            .and_then(|native| native.page_size(page_idx).ok())
+        {
+            return Ok(size);
+        }
+        self.reader()?.page_size(page_idx)
     }
 
     fn analyze_page(&self, page_idx: usize) -> Result<PageHypothesis> {
-        let glyph_runs = extract_glyph_runs(&self.path, page_idx);
+        let text_layer = self.text_layer_blocks(page_idx);
+        if let Some(mut blocks) = self.tagged_blocks(page_idx) {
+            let size = self.page_size(page_idx)?;
+            blocks.extend(self.form_field_blocks(page_idx));
+            blocks.extend(text_layer);
+            return Ok(PageHypothesis {
+                page_idx,
+                width: size.width.round() as u32,
+                height: size.height.round() as u32,
+                blocks,
+            });
+        }
+
+        // Poppler cannot tell invisible text from visible text, so a page
+        // whose only text is an OCR layer gets no parser text from it. That
+        // needs the native backend: built without `native-pdf`, or when lopdf
+        // cannot load the file, the layer goes undetected and poppler still
+        // returns it as parser text.
+        let layout = self.native_page_layout(page_idx).or_else(|| {
+            text_layer
+                .is_empty()
+                .then(|| extract_page_layout(&self.path, self.password.as_deref(), page_idx))
+                .flatten()
+        });
+        if let Some(mut layout) = layout {
+            let (width, height) = (layout.width.round() as u32, layout.height.round() as u32);
+            let words: Vec<BBox> = layout
+                .blocks
+                .iter()
+                .flat_map(|block| &block.lines)
+                .flat_map(|line| &line.words)
+                .map(|word| word.bbox)
+                .collect();
+            let tables: Vec<Block> = self
+                .page_tables(page_idx, &words)
+                .iter()
+                .map(|table| table_block(table, &mut layout))
+                .collect();
+            let mut blocks = layout_to_blocks(layout, Provenance::Parser);
+            blocks.extend(tables);
+            blocks.extend(self.form_field_blocks(page_idx));
+            blocks.extend(text_layer);
+            return Ok(PageHypothesis {
+                page_idx,
+                width,
+                height,
+                blocks,
+            });
+        }
+
+        // Older poppler builds or unusual pages: fall back to one coarse
+        // page-wide run chosen from the plain-text modes.
+        let size = self.page_size(page_idx)?;
+        let glyph_runs = if text_layer.is_empty() {
+            extract_glyph_runs(
+                &self.path,
+                self.password.as_deref(),
+                page_idx,
+                size.width,
+                size.height,
+            )
+        } else {
+            Vec::new()
+        };
         let mut blocks = Vec::new();
 
         if !glyph_runs.is_empty() {
@@ -36,6 +675,8 @@ impl ParserTrack for PdfParser {
                     bbox: run.bbox,
                     source: Provenance::Parser,
                     style: None,
+                    link: None,
+                    revision: None,
                 });
             }
             let line = Line { spans };
@@ -44,18 +685,63 @@ impl ParserTrack for PdfParser {
                 lines: vec![line],
                 confidence: 0.6,
                 source: Provenance::Parser,
+                role: None,
+                order: None,
+                tracks: Vec::new(),
                 debug: None,
             });
         }
+        blocks.extend(self.form_field_blocks(page_idx));
+        blocks.extend(text_layer);
 
         Ok(PageHypothesis {
             page_idx,
             blocks,
-            width: 1000,
-            height: 1400,
+            width: size.width.round() as u32,
+            height: size.height.round() as u32,
         })
     }
 
+    fn page_links(&self, page_idx: usize) -> Result<Vec<PageLink>> {
+        #[cfg(feature = "native-pdf")]
+        if let Some(links) = self
+            .native
+            .as_ref()
+            .and_then(|native| native.page_links(page_idx).ok())
+        {
+            return Ok(links);
+        }
+        self.reader()?.page_links(page_idx)
+    }
+
+    fn page_images(&self, page_idx: usize) -> Result<Vec<PageImage>> {
+        #[cfg(feature = "native-pdf")]
+        if let Some(images) = self
+            .native
+            .as_ref()
+            .and_then(|native| native.page_images(page_idx).ok())
+        {
+            return Ok(images);
+        }
+        self.reader()?.page_images(page_idx)
+    }
+
+    fn outline(&self) -> Result<Vec<OutlineEntry>> {
+        #[cfg(feature = "native-pdf")]
+        if let Some(native) = &self.native {
+            return Ok(native.outline());
+        }
+        self.reader()?.outline()
+    }
+
+    fn metadata(&self) -> Result<DocumentMetadata> {
+        #[cfg(feature = "native-pdf")]
+        if let Some(native) = &self.native {
+            return Ok(native.metadata());
+        }
+        self.reader()?.metadata()
+    }
+
     fn supports_ocr_rendering(&self) -> bool {
         true
     }
@@ -64,3 +750,200 @@ impl ParserTrack for PdfParser {
         Some(&self.path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::model::TextStyle;
+    use crate::parser::tables::CellSpan;
+    use crate::parser::text_extractor::{TextBlockLayout, TextLineLayout};
+
+    #[test]
+    fn layout_blocks_keep_page_points() {
+        let word = |text: &str, x0: f32, x1: f32| GlyphRun {
+            text: text.to_string(),
+            bbox: BBox::new(x0, 72.0, x1, 84.0),
+            style: None,
+        };
+        let layout = PageTextLayout {
+            width: 500.0,
+            height: 700.0,
+            blocks: vec![TextBlockLayout {
+                bbox: BBox::new(50.0, 72.0, 250.0, 84.0),
+                lines: vec![TextLineLayout {
+                    bbox: BBox::new(50.0, 72.0, 250.0, 84.0),
+                    words: vec![word("Hello", 50.0, 100.0), word("world", 110.0, 250.0)],
+                }],
+            }],
+        };
+
+        let blocks = layout_to_blocks(layout, Provenance::Parser);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].bbox(), BBox::new(50.0, 72.0, 250.0, 84.0));
+        assert_eq!(blocks[0].text_content().as_deref(), Some("Hello world"));
+    }
+
+    #[test]
+    fn table_words_move_into_cells() {
+        let word = |text: &str, x0: f32, y0: f32, bold: bool| GlyphRun {
+            text: text.to_string(),
+            bbox: BBox::new(x0, y0, x0 + 30.0, y0 + 10.0),
+            style: Some(TextStyle {
+                bold,
+                ..TextStyle::default()
+            }),
+        };
+        let line = |words: Vec<GlyphRun>| TextLineLayout {
+            bbox: words[0].bbox.union(&words[words.len() - 1].bbox),
+            words,
+        };
+        let mut layout = PageTextLayout {
+            width: 500.0,
+            height: 700.0,
+            blocks: vec![
+                TextBlockLayout {
+                    bbox: BBox::new(60.0, 50.0, 90.0, 60.0),
+                    lines: vec![line(vec![word("Caption", 60.0, 50.0, false)])],
+                },
+                TextBlockLayout {
+                    bbox: BBox::new(60.0, 110.0, 190.0, 150.0),
+                    lines: vec![
+                        line(vec![
+                            word("Name", 60.0, 110.0, true),
+                            word("Qty", 160.0, 110.0, true),
+                        ]),
+                        line(vec![
+                            word("Pens", 60.0, 140.0, false),
+                            word("12", 160.0, 140.0, false),
+                        ]),
+                    ],
+                },
+            ],
+        };
+        let table = DetectedTable {
+            bbox: BBox::new(50.0, 100.0, 250.0, 160.0),
+            rows: vec![100.0, 130.0, 160.0],
+            cols: vec![50.0, 150.0, 250.0],
+            cells: (0..4)
+                .map(|idx| CellSpan {
+                    row: idx / 2,
+                    col: idx % 2,
+                    rowspan: 1,
+                    colspan: 1,
+                })
+                .collect(),
+            header_rows: 0,
+        };
+
+        let Block::TableBlock { cells, .. } = table_block(&table, &mut layout) else {
+            unreachable!();
+        };
+        let texts: Vec<String> = cells.iter().map(TableCell::text).collect();
+        assert_eq!(texts, vec!["Name", "Qty", "Pens", "12"]);
+        assert!(cells[1].header && !cells[2].header);
+        assert_eq!(cells[3].bbox, BBox::new(150.0, 130.0, 250.0, 160.0));
+        assert_eq!(layout.blocks.len(), 1);
+        assert_eq!(layout.blocks[0].lines[0].words[0].text, "Caption");
+    }
+
+    #[cfg(feature = "native-pdf")]
+    #[test]
+    fn tagged_blocks_follow_structure_order() {
+        use crate::core::model::TextRole;
+
+        let glyph = |text: &str, y0: f32, mcid: Option<u32>, artifact: bool| PositionedGlyph {
+            text: text.to_string(),
+            bbox: BBox::new(72.0, y0, 72.0 + 8.0 * text.len() as f32, y0 + 10.0),
+            style: TextStyle::default(),
+            font_size: 10.0,
+            mcid,
+            artifact,
+            invisible: false,
+        };
+        let glyphs = vec![
+            glyph("Body", 200.0, Some(0), false),
+            glyph("Title", 100.0, Some(1), false),
+            glyph("12", 780.0, None, true),
+            glyph("Loose", 400.0, None, false),
+        ];
+        let tagged = vec![
+            TaggedBlock {
+                kind: TaggedKind::Text(TextRole::Heading(1)),
+                mcids: vec![1],
+            },
+            TaggedBlock {
+                kind: TaggedKind::Text(TextRole::Paragraph),
+                mcids: vec![0],
+            },
+        ];
+
+        let blocks = tagged_page_blocks(tagged, &glyphs, &[]);
+        let summary: Vec<(Option<String>, Option<usize>)> = blocks
+            .iter()
+            .map(|block| (block.text_content(), block.order()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("Title".to_string()), Some(0)),
+                (Some("Body".to_string()), Some(1)),
+                (Some("Loose".to_string()), None),
+            ]
+        );
+        assert!(matches!(
+            blocks[0],
+            Block::TextBlock {
+                role: Some(TextRole::Heading(1)),
+                ..
+            }
+        ));
+    }
+
+    #[cfg(feature = "native-pdf")]
+    #[test]
+    fn form_fields_become_labelled_blocks() {
+        let field = |name: &str, label: Option<&str>, kind, value: Option<&str>| FormField {
+            name: name.to_string(),
+            label: label.map(str::to_string),
+            kind,
+            value: value.map(str::to_string),
+            bbox: BBox::new(100.0, 80.0, 300.0, 100.0),
+        };
+        let text = |field: FormField| form_field_block(field).and_then(|b| b.text_content());
+
+        assert_eq!(
+            text(field(
+                "applicant.name",
+                Some("Name"),
+                FormFieldKind::Text,
+                Some("Hong\r\nGildong")
+            )),
+            Some("Name: Hong Gildong".to_string())
+        );
+        assert_eq!(
+            text(field("agree", None, FormFieldKind::Checkbox, Some("Yes"))),
+            Some("agree: ☑".to_string())
+        );
+        assert_eq!(
+            text(field("agree", None, FormFieldKind::Checkbox, None)),
+            Some("agree: ☐".to_string())
+        );
+        assert_eq!(
+            text(field(
+                "city",
+                Some("City"),
+                FormFieldKind::Choice,
+                Some("Seoul")
+            )),
+            Some("City: Seoul".to_string())
+        );
+        assert_eq!(
+            text(field("city", None, FormFieldKind::Choice, Some("  "))),
+            None
+        );
+        assert_eq!(
+            text(field("submit", None, FormFieldKind::PushButton, None)),
+            None
+        );
+    }
+}
@@ -1,30 +1,433 @@
 use anyhow::{Context, Result};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::core::geometry::BBox;
+use crate::core::model::{DocumentMetadata, LinkTarget, OutlineEntry, PageLink, PageSize};
+use crate::parser::{poppler, PageImage};
+
 #[derive(Debug, Clone)]
 pub struct PdfReader {
     path: PathBuf,
+    password: Option<String>,
 }
 
 impl PdfReader {
     pub fn new(path: PathBuf) -> Result<Self> {
-        Ok(Self { path })
+        Ok(Self {
+            path,
+            password: None,
+        })
+    }
+
+    /// Owner or user password for encrypted files.
+    pub fn with_password(mut self, password: Option<String>) -> Self {
+        self.password = password;
+        self
     }
 
     pub fn page_count(&self) -> Result<usize> {
-        get_page_count(&self.path)
+        get_page_count(&self.path, self.password.as_deref())
+    }
+
+    /// Displayed size of one page, from `pdfinfo -f n -l n`.
+    pub fn page_size(&self, page_idx: usize) -> Result<PageSize> {
+        let page_number = (page_idx + 1).to_string();
+        let output = poppler::password_args(&mut Command::new("pdfinfo"), self.password.as_deref())
+            .arg("-f")
+            .arg(&page_number)
+            .arg("-l")
+            .arg(&page_number)
+            .arg(&self.path)
+            .output()
+            .with_context(|| format!("failed to invoke pdfinfo on {}", self.path.display()))?;
+
+        if !output.status.success() {
+            return Err(poppler::failure(
+                "pdfinfo",
+                &self.path,
+                &output,
+                self.password.as_deref(),
+            ));
+        }
+
+        parse_page_size(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+            anyhow::anyhow!(
+                "pdfinfo output did not contain a size for page {} of {}",
+                page_number,
+                self.path.display()
+            )
+        })
+    }
+
+    /// Info dictionary fields as printed by `pdfinfo -isodates`.
+    pub fn metadata(&self) -> Result<DocumentMetadata> {
+        let output = poppler::password_args(&mut Command::new("pdfinfo"), self.password.as_deref())
+            .arg("-isodates")
+            .arg("-enc")
+            .arg("UTF-8")
+            .arg(&self.path)
+            .output()
+            .with_context(|| format!("failed to invoke pdfinfo on {}", self.path.display()))?;
+
+        if !output.status.success() {
+            return Err(poppler::failure(
+                "pdfinfo",
+                &self.path,
+                &output,
+                self.password.as_deref(),
+            ));
+        }
+
+        Ok(parse_metadata(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Links on one page from the `<a href>` runs of `pdftohtml -xml`. The
+    /// area is the enclosing text element, in points (`-zoom 1`).
+    pub fn page_links(&self, page_idx: usize) -> Result<Vec<PageLink>> {
+        let page_number = (page_idx + 1).to_string();
+        let output =
+            poppler::password_args(&mut Command::new("pdftohtml"), self.password.as_deref())
+                .arg("-xml")
+                .arg("-stdout")
+                .arg("-i")
+                .arg("-q")
+                .arg("-zoom")
+                .arg("1")
+                .arg("-f")
+                .arg(&page_number)
+                .arg("-l")
+                .arg(&page_number)
+                .arg(&self.path)
+                .output()
+                .with_context(|| {
+                    format!("failed to invoke pdftohtml on {}", self.path.display())
+                })?;
+
+        if !output.status.success() {
+            return Err(poppler::failure(
+                "pdftohtml",
+                &self.path,
+                &output,
+                self.password.as_deref(),
+            ));
+        }
+
+        Ok(parse_pdftohtml_links(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Images on one page as written by `pdftohtml -xml`, which copies
+    /// JPEG streams unchanged and saves other images as PNG at their own
+    /// resolution. Placement comes from the `<image>` elements (`-zoom 1`).
+    pub fn page_images(&self, page_idx: usize) -> Result<Vec<PageImage>> {
+        let page_number = (page_idx + 1).to_string();
+        let work_dir = std::env::temp_dir().join(format!(
+            "docstruct-images-{}-{}",
+            std::process::id(),
+            page_number
+        ));
+        fs::create_dir_all(&work_dir)?;
+        let result = self.extract_page_images(&page_number, &work_dir);
+        let _ = fs::remove_dir_all(&work_dir);
+        result
+    }
+
+    fn extract_page_images(&self, page_number: &str, work_dir: &Path) -> Result<Vec<PageImage>> {
+        let output =
+            poppler::password_args(&mut Command::new("pdftohtml"), self.password.as_deref())
+                .arg("-xml")
+                .arg("-q")
+                .arg("-zoom")
+                .arg("1")
+                .arg("-f")
+                .arg(page_number)
+                .arg("-l")
+                .arg(page_number)
+                .arg(&self.path)
+                .arg(work_dir.join("page"))
+                .output()
+                .with_context(|| {
+                    format!("failed to invoke pdftohtml on {}", self.path.display())
+                })?;
+
+        if !output.status.success() {
+            return Err(poppler::failure(
+                "pdftohtml",
+                &self.path,
+                &output,
+                self.password.as_deref(),
+            ));
+        }
+
+        let xml = fs::read_to_string(work_dir.join("page.xml"))
+            .context("pdftohtml did not write its XML output")?;
+        let mut images = Vec::new();
+        for (bbox, src) in parse_pdftohtml_images(&xml) {
+            // `src` is relative to the XML file unless poppler wrote it absolute.
+            let path = work_dir.join(&src);
+            let extension = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("png")
+                .to_ascii_lowercase();
+            let data = fs::read(&path)
+                .with_context(|| format!("failed to read extracted image {}", path.display()))?;
+            images.push(PageImage {
+                bbox,
+                data,
+                extension,
+            });
+        }
+        Ok(images)
+    }
+
+    /// Bookmark tree from the `<outline>` section of `pdftohtml -xml`.
+    pub fn outline(&self) -> Result<Vec<OutlineEntry>> {
+        let output =
+            poppler::password_args(&mut Command::new("pdftohtml"), self.password.as_deref())
+                .arg("-xml")
+                .arg("-stdout")
+                .arg("-i")
+                .arg("-q")
+                .arg("-f")
+                .arg("1")
+                .arg("-l")
+                .arg("1")
+                .arg(&self.path)
+                .output()
+                .with_context(|| {
+                    format!("failed to invoke pdftohtml on {}", self.path.display())
+                })?;
+
+        if !output.status.success() {
+            return Err(poppler::failure(
+                "pdftohtml",
+                &self.path,
+                &output,
+                self.password.as_deref(),
+            ));
+        }
+
+        Ok(parse_outline_xml(&String::from_utf8_lossy(&output.stdout)))
     }
 }
 
-fn get_page_count(pdf_path: &Path) -> Result<usize> {
-    let output = Command::new("pdfinfo")
+/// Value of `name="..."` in one line of pdftohtml XML.
+fn xml_attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+/// Placement (points) and file name of each `<image>` element in
+/// pdftohtml XML.
+fn parse_pdftohtml_images(xml: &str) -> Vec<(BBox, String)> {
+    xml.lines()
+        .map(str::trim_start)
+        .filter(|line| line.starts_with("<image "))
+        .filter_map(|line| {
+            let number = |name: &str| xml_attr(line, name)?.parse::<f32>().ok();
+            let (top, left) = (number("top")?, number("left")?);
+            let bbox = BBox::new(left, top, left + number("width")?, top + number("height")?);
+            Some((bbox, xml_attr(line, "src")?.to_string()))
+        })
+        .collect()
+}
+
+/// Scan `<text>` elements line by line: pdftohtml writes one per line, and
+/// the page text is not always well-formed enough for an XML parser. The
+/// element's box covers the whole line, so each `<a>` gets the share of its
+/// width that its characters take up, and only the linked words fall in it.
+fn parse_pdftohtml_links(xml: &str) -> Vec<PageLink> {
+    let mut links = Vec::new();
+    for line in xml.lines().map(str::trim_start) {
+        if !line.starts_with("<text ") || !line.contains("<a ") {
+            continue;
+        }
+        let number = |name: &str| xml_attr(line, name).and_then(|value| value.parse::<f32>().ok());
+        let (Some(top), Some(left), Some(width), Some(height)) = (
+            number("top"),
+            number("left"),
+            number("width"),
+            number("height"),
+        ) else {
+            continue;
+        };
+        let Some(content) = line
+            .find('>')
+            .map(|end| &line[end + 1..])
+            .map(|rest| rest.strip_suffix("</text>").unwrap_or(rest))
+        else {
+            continue;
+        };
+
+        let total = visible_chars(content).max(1) as f32;
+        let mut before = 0;
+        let mut rest = content;
+        while let Some(open) = rest.find("<a ") {
+            before += visible_chars(&rest[..open]);
+            let anchor = &rest[open..];
+            let (Some(tag_end), Some(close)) = (anchor.find('>'), anchor.find("</a>")) else {
+                break;
+            };
+            let chars = visible_chars(&anchor[tag_end + 1..close.max(tag_end + 1)]);
+            rest = &anchor[close + "</a>".len()..];
+            let Some(href) = xml_attr(&anchor[..tag_end], "href") else {
+                before += chars;
+                continue;
+            };
+            let href = href.replace("&amp;", "&");
+            // Internal links point at `<file>.html#<page>`.
+            let internal = href
+                .rsplit_once('#')
+                .filter(|(base, _)| !base.contains("://"))
+                .and_then(|(_, page)| page.parse::<usize>().ok());
+            let target = match internal {
+                Some(page) => LinkTarget::Page(page.saturating_sub(1)),
+                None => LinkTarget::Uri(href),
+            };
+            let x0 = left + width * before as f32 / total;
+            let x1 = left + width * (before + chars) as f32 / total;
+            links.push(PageLink {
+                bbox: BBox::new(x0, top, x1, top + height),
+                target,
+            });
+            before += chars;
+        }
+    }
+    links
+}
+
+/// Characters of pdftohtml markup as displayed: tags take no room and an
+/// entity is one character.
+fn visible_chars(markup: &str) -> usize {
+    let mut count = 0;
+    let mut chars = markup.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => {
+                chars.by_ref().find(|&c| c == '>');
+            }
+            '&' => {
+                chars.by_ref().find(|&c| c == ';');
+                count += 1;
+            }
+            _ => count += 1,
+        }
+    }
+    count
+}
+
+/// Parse the `<outline>` element of pdftohtml XML. Each `<item page="n">`
+/// is followed by an optional sibling `<outline>` holding its children.
+/// Only that section is parsed: the page text around it is not always
+/// well-formed XML.
+fn parse_outline_xml(xml: &str) -> Vec<OutlineEntry> {
+    fn items(node: roxmltree::Node, level: usize) -> Vec<OutlineEntry> {
+        let mut entries: Vec<OutlineEntry> = Vec::new();
+        for child in node.children().filter(|child| child.is_element()) {
+            match child.tag_name().name() {
+                "item" => {
+                    let title: String = child
+                        .descendants()
+                        .filter(|desc| desc.is_text())
+                        .filter_map(|desc| desc.text())
+                        .collect();
+                    let page_idx = child
+                        .attribute("page")
+                        .and_then(|page| page.parse::<usize>().ok())
+                        .and_then(|page| page.checked_sub(1));
+                    entries.push(OutlineEntry::new(title.trim(), level, page_idx));
+                }
+                "outline" => {
+                    let children = items(child, level + 1);
+                    match entries.last_mut() {
+                        Some(parent) => parent.children.extend(children),
+                        None => entries.extend(children),
+                    }
+                }
+                _ => {}
+            }
+        }
+        entries
+    }
+
+    let (Some(start), Some(end)) = (xml.find("<outline>"), xml.rfind("</outline>")) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    let section = &xml[start..end + "</outline>".len()];
+    match roxmltree::Document::parse(section) {
+        Ok(doc) => items(doc.root_element(), 1),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn parse_metadata(stdout: &str) -> DocumentMetadata {
+    let mut metadata = DocumentMetadata::default();
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let field = match key {
+            "Title" => &mut metadata.title,
+            "Author" => &mut metadata.author,
+            "Subject" => &mut metadata.subject,
+            "Keywords" => &mut metadata.keywords,
+            "Creator" => &mut metadata.creator,
+            "Producer" => &mut metadata.producer,
+            "CreationDate" => &mut metadata.created,
+            "ModDate" => &mut metadata.modified,
+            _ => continue,
+        };
+        *field = Some(value.to_string());
+    }
+    metadata
+}
+
+/// Parse the per-page `Page N size:` and `Page N rot:` lines of pdfinfo.
+/// pdfinfo already swaps the crop box sides for 90/270 degree pages.
+fn parse_page_size(stdout: &str) -> Option<PageSize> {
+    let mut size = None;
+    let mut rotation = 0u16;
+    for line in stdout.lines().filter(|line| line.starts_with("Page ")) {
+        if let Some((_, rest)) = line.split_once(" size:") {
+            let mut dims = rest.split_whitespace();
+            let width: f32 = dims.next()?.parse().ok()?;
+            let _x = dims.next()?;
+            let height: f32 = dims.next()?.parse().ok()?;
+            size = Some((width, height));
+        } else if let Some((_, rest)) = line.split_once(" rot:") {
+            let degrees: i32 = rest.trim().parse().ok()?;
+            rotation = degrees.rem_euclid(360) as u16;
+        }
+    }
+
+    let (width, height) = size?;
+    Some(PageSize {
+        width,
+        height,
+        rotation,
+    })
+}
+
+fn get_page_count(pdf_path: &Path, password: Option<&str>) -> Result<usize> {
+    let output = poppler::password_args(&mut Command::new("pdfinfo"), password)
         .arg(pdf_path)
         .output()
         .with_context(|| format!("failed to invoke pdfinfo on {}", pdf_path.display()))?;
 
     if !output.status.success() {
-        anyhow::bail!("pdfinfo failed with status: {}", output.status);
+        return Err(poppler::failure("pdfinfo", pdf_path, &output, password));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -43,3 +446,92 @@ fn get_page_count(pdf_path: &Path) -> Result<usize> {
         pdf_path.display()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rotated_page_size() {
+        let stdout = "Pages:          3\n\
+            Page    2 size: 792 x 612 pts (letter)\n\
+            Page    2 rot:  90\n";
+        let size = parse_page_size(stdout).unwrap();
+        assert_eq!((size.width, size.height, size.rotation), (792.0, 612.0, 90));
+    }
+
+    #[test]
+    fn parses_pdfinfo_metadata() {
+        let stdout = "Title:           2024 업무계획\n\
+            Author:          기획조정실\n\
+            Keywords:        \n\
+            Producer:        Hancom PDF 1.3.0.538\n\
+            CreationDate:    2024-01-15T09:30:00+09\n\
+            Pages:           42\n";
+        let metadata = parse_metadata(stdout);
+        assert_eq!(metadata.title.as_deref(), Some("2024 업무계획"));
+        assert_eq!(metadata.author.as_deref(), Some("기획조정실"));
+        assert_eq!(metadata.keywords, None);
+        assert_eq!(metadata.created.as_deref(), Some("2024-01-15T09:30:00+09"));
+        assert_eq!(metadata.fields().len(), 4);
+    }
+
+    #[test]
+    fn parses_pdftohtml_links() {
+        let xml = r##"<page number="1" position="absolute" top="0" left="0" height="842" width="595">
+<text top="100" left="72" width="120" height="12" font="0">plain text</text>
+<text top="120" left="72" width="150" height="12" font="0"><a href="https://example.com/?a=1&amp;b=2">example</a></text>
+<text top="140" left="72" width="40" height="12" font="0"><a href="doc.html#3">[12]</a></text>
+</page>"##;
+        let links = parse_pdftohtml_links(xml);
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            links[0].target,
+            LinkTarget::Uri("https://example.com/?a=1&b=2".to_string())
+        );
+        assert_eq!(links[0].bbox, BBox::new(72.0, 120.0, 222.0, 132.0));
+        assert_eq!(links[1].target, LinkTarget::Page(2));
+
+        // Only the anchor's share of a line links, not the whole line.
+        let inline = r#"<text top="160" left="72" width="180" height="12" font="0">Go <a href="https://example.com/">the site</a> &amp; <b>more</b></text>"#;
+        let links = parse_pdftohtml_links(inline);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].bbox, BBox::new(102.0, 160.0, 182.0, 172.0));
+    }
+
+    #[test]
+    fn parses_pdftohtml_images() {
+        let xml = r#"<page number="2" position="absolute" top="0" left="0" height="842" width="595">
+<image top="300" left="72" width="451" height="200" src="page-2_1.jpg"/>
+<text top="510" left="72" width="200" height="12" font="0">Figure 1. Results</text>
+</page>"#;
+        let images = parse_pdftohtml_images(xml);
+        assert_eq!(
+            images,
+            vec![(
+                BBox::new(72.0, 300.0, 523.0, 500.0),
+                "page-2_1.jpg".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_nested_pdftohtml_outline() {
+        let xml = r#"<pdf2xml producer="poppler" version="23.02.0">
+<page number="1"><text>body</text></page>
+<outline>
+<item page="1">Ⅰ. 개요</item>
+<outline>
+<item page="3">1. 추진 배경</item>
+</outline>
+<item>Appendix</item>
+</outline>
+</pdf2xml>"#;
+        let outline = parse_outline_xml(xml);
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].title, "Ⅰ. 개요");
+        assert_eq!(outline[0].children[0].page_idx, Some(2));
+        assert_eq!(outline[0].children[0].level, 2);
+        assert_eq!(outline[1].page_idx, None);
+    }
+}
@@ -0,0 +1,726 @@
+//! Document-level PDF objects read natively: the info dictionary, the
+//! outline (bookmarks) tree, link annotations and the destinations they
+//! point at, and AcroForm fields.
+
+use anyhow::Result;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::{HashMap, HashSet};
+
+use crate::core::geometry::BBox;
+use crate::core::model::{DocumentMetadata, LinkTarget, OutlineEntry, PageLink};
+use crate::parser::pdf_content::number;
+use crate::parser::pdf_content::{deref, NativePdf};
+
+/// Outlines and name trees can nest; stop descending past this depth.
+const MAX_TREE_DEPTH: usize = 32;
+
+/// Page object id to zero-based page index.
+type PageIndex = HashMap<ObjectId, usize>;
+
+/// An AcroForm field as placed on a page by one of its widget annotations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    /// Fully qualified field name (`parent.child`).
+    pub name: String,
+    /// Tooltip (`/TU`), the user-facing label when the form sets one.
+    pub label: Option<String>,
+    pub kind: FormFieldKind,
+    /// Text or choice value; for check boxes and radio buttons the on-state
+    /// name when this widget is selected, `None` when it is off.
+    pub value: Option<String>,
+    /// Widget rectangle in displayed page space.
+    pub bbox: BBox,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormFieldKind {
+    Text,
+    Choice,
+    Checkbox,
+    Radio,
+    PushButton,
+    Signature,
+}
+
+impl NativePdf {
+    /// Info dictionary fields plus the catalog `/Lang`.
+    pub fn metadata(&self) -> DocumentMetadata {
+        let info = self
+            .doc
+            .trailer
+            .get(b"Info")
+            .ok()
+            .and_then(|info| deref(&self.doc, info).as_dict().ok());
+        let text = |dict: Option<&Dictionary>, key: &[u8]| {
+            let value = deref(&self.doc, dict?.get(key).ok()?).as_str().ok()?;
+            let value = text_string(value).trim().to_string();
+            (!value.is_empty()).then_some(value)
+        };
+        DocumentMetadata {
+            title: text(info, b"Title"),
+            author: text(info, b"Author"),
+            subject: text(info, b"Subject"),
+            keywords: text(info, b"Keywords"),
+            creator: text(info, b"Creator"),
+            producer: text(info, b"Producer"),
+            created: text(info, b"CreationDate").map(|date| pdf_date_to_iso(&date)),
+            modified: text(info, b"ModDate").map(|date| pdf_date_to_iso(&date)),
+            language: text(self.catalog(), b"Lang"),
+        }
+    }
+
+    /// The bookmark tree, with every entry's target page resolved.
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        let Some(root) = self
+            .catalog()
+            .and_then(|catalog| catalog.get(b"Outlines").ok())
+            .and_then(|outlines| deref(&self.doc, outlines).as_dict().ok())
+        else {
+            return Vec::new();
+        };
+        let pages = self.page_index();
+        let mut visited = HashSet::new();
+        self.outline_items(root.get(b"First").ok(), 1, &pages, &mut visited)
+    }
+
+    /// `/Link` annotations of a page with URI or in-document targets, their
+    /// areas in displayed page space.
+    pub fn page_links(&self, page_idx: usize) -> Result<Vec<PageLink>> {
+        let page_id = self.page_id(page_idx)?;
+        let (_, to_display) = self.display_space(page_id);
+        let Some(annots) = self
+            .doc
+            .get_dictionary(page_id)?
+            .get(b"Annots")
+            .ok()
+            .and_then(|annots| deref(&self.doc, annots).as_array().ok())
+        else {
+            return Ok(Vec::new());
+        };
+
+        let pages = self.page_index();
+        let mut links = Vec::new();
+        for annot in annots {
+            let Ok(annot) = deref(&self.doc, annot).as_dict() else {
+                continue;
+            };
+            if annot.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Link") {
+                continue;
+            }
+            let Some(rect) = self.rect(annot) else {
+                continue;
+            };
+            if let Some(target) = self.link_target(annot, &pages) {
+                links.push(PageLink {
+                    bbox: to_display.apply_bbox(&rect),
+                    target,
+                });
+            }
+        }
+        Ok(links)
+    }
+
+    /// Target of a link annotation: a `/URI` action, or a page from `/Dest`
+    /// or a `/GoTo` action.
+    fn link_target(&self, annot: &Dictionary, pages: &PageIndex) -> Option<LinkTarget> {
+        if let Some(action) = annot
+            .get(b"A")
+            .ok()
+            .and_then(|action| deref(&self.doc, action).as_dict().ok())
+        {
+            if action.get(b"S").and_then(Object::as_name).ok() == Some(b"URI") {
+                let uri = deref(&self.doc, action.get(b"URI").ok()?).as_str().ok()?;
+                return Some(LinkTarget::Uri(
+                    String::from_utf8_lossy(uri).trim().to_string(),
+                ));
+            }
+        }
+        self.item_page(annot, pages).map(LinkTarget::Page)
+    }
+
+    /// Form fields with a widget on this page, in annotation order.
+    pub fn page_form_fields(&self, page_idx: usize) -> Result<Vec<FormField>> {
+        let page_id = self.page_id(page_idx)?;
+        let (_, to_display) = self.display_space(page_id);
+        let Some(annots) = self
+            .doc
+            .get_dictionary(page_id)?
+            .get(b"Annots")
+            .ok()
+            .and_then(|annots| deref(&self.doc, annots).as_array().ok())
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut fields = Vec::new();
+        for annot in annots {
+            let Ok(widget) = deref(&self.doc, annot).as_dict() else {
+                continue;
+            };
+            if widget.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Widget") {
+                continue;
+            }
+            let Some(rect) = self.rect(widget) else {
+                continue;
+            };
+            if let Some(mut field) = self.form_field(widget) {
+                field.bbox = to_display.apply_bbox(&rect);
+                fields.push(field);
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Read a widget's field, walking `/Parent` for the name parts and the
+    /// inheritable `/FT`, `/Ff`, `/V` and `/TU` entries.
+    fn form_field(&self, widget: &Dictionary) -> Option<FormField> {
+        let mut chain = vec![widget];
+        while chain.len() < MAX_TREE_DEPTH {
+            let Some(parent) = chain
+                .last()?
+                .get(b"Parent")
+                .ok()
+                .and_then(|parent| deref(&self.doc, parent).as_dict().ok())
+            else {
+                break;
+            };
+            chain.push(parent);
+        }
+        let inherited = |key: &[u8]| {
+            chain
+                .iter()
+                .find_map(|dict| dict.get(key).ok())
+                .map(|obj| deref(&self.doc, obj))
+        };
+        let text = |obj: &Object| match obj {
+            Object::String(bytes, _) => Some(text_string(bytes)),
+            Object::Name(name) => Some(String::from_utf8_lossy(name).into_owned()),
+            _ => None,
+        };
+
+        let name_parts: Vec<String> = chain
+            .iter()
+            .rev()
+            .filter_map(|dict| dict.get(b"T").ok())
+            .filter_map(|part| text(deref(&self.doc, part)))
+            .collect();
+        if name_parts.is_empty() {
+            return None;
+        }
+        let flags = inherited(b"Ff")
+            .and_then(|flags| flags.as_i64().ok())
+            .unwrap_or(0);
+        let kind = match inherited(b"FT").and_then(|ft| ft.as_name().ok())? {
+            b"Tx" => FormFieldKind::Text,
+            b"Ch" => FormFieldKind::Choice,
+            b"Sig" => FormFieldKind::Signature,
+            b"Btn" if flags & (1 << 16) != 0 => FormFieldKind::PushButton,
+            b"Btn" if flags & (1 << 15) != 0 => FormFieldKind::Radio,
+            b"Btn" => FormFieldKind::Checkbox,
+            _ => return None,
+        };
+
+        let value = match kind {
+            // A button widget is on when its appearance state is not /Off.
+            // A check box without `/AS` falls back to the field's `/V`;
+            // radio buttons share `/V` across widgets, so only `/AS` tells
+            // which one is selected.
+            FormFieldKind::Checkbox | FormFieldKind::Radio => widget
+                .get(b"AS")
+                .ok()
+                .or_else(|| {
+                    (kind == FormFieldKind::Checkbox)
+                        .then(|| inherited(b"V"))
+                        .flatten()
+                })
+                .and_then(|state| deref(&self.doc, state).as_name().ok())
+                .filter(|state| *state != b"Off")
+                .map(|state| String::from_utf8_lossy(state).into_owned()),
+            _ => match inherited(b"V") {
+                Some(Object::Array(values)) => {
+                    let values: Vec<String> = values
+                        .iter()
+                        .filter_map(|value| text(deref(&self.doc, value)))
+                        .collect();
+                    (!values.is_empty()).then(|| values.join(", "))
+                }
+                Some(value) => text(value),
+                None => None,
+            },
+        };
+
+        Some(FormField {
+            name: name_parts.join("."),
+            label: inherited(b"TU")
+                .and_then(text)
+                .map(|label| label.trim().to_string())
+                .filter(|label| !label.is_empty()),
+            kind,
+            value,
+            bbox: BBox::new(0.0, 0.0, 0.0, 0.0),
+        })
+    }
+
+    /// An annotation's `/Rect`, normalized so x0 <= x1 and y0 <= y1.
+    fn rect(&self, annot: &Dictionary) -> Option<BBox> {
+        let rect = deref(&self.doc, annot.get(b"Rect").ok()?).as_array().ok()?;
+        let values: Vec<f32> = rect
+            .iter()
+            .filter_map(|value| number(&self.doc, value))
+            .collect();
+        let [x0, y0, x1, y1] = values[..] else {
+            return None;
+        };
+        Some(BBox::new(x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)))
+    }
+
+    pub(crate) fn catalog(&self) -> Option<&Dictionary> {
+        self.doc.catalog().ok()
+    }
+
+    fn page_index(&self) -> PageIndex {
+        self.doc
+            .get_pages()
+            .into_iter()
+            .map(|(number, id)| (id, number as usize - 1))
+            .collect()
+    }
+
+    /// Walk one `/First` ... `/Next` chain of outline items.
+    fn outline_items(
+        &self,
+        first: Option<&Object>,
+        level: usize,
+        pages: &PageIndex,
+        visited: &mut HashSet<ObjectId>,
+    ) -> Vec<OutlineEntry> {
+        let mut entries = Vec::new();
+        let mut next = first;
+        while let Some(obj) = next {
+            // A malformed /Next chain can loop back on itself.
+            if let Object::Reference(id) = obj {
+                if !visited.insert(*id) {
+                    break;
+                }
+            }
+            let Ok(item) = deref(&self.doc, obj).as_dict() else {
+                break;
+            };
+
+            let title = item
+                .get(b"Title")
+                .ok()
+                .and_then(|title| deref(&self.doc, title).as_str().ok())
+                .map(text_string)
+                .unwrap_or_default();
+            let mut entry = OutlineEntry::new(title.trim(), level, self.item_page(item, pages));
+            if level < MAX_TREE_DEPTH {
+                entry.children =
+                    self.outline_items(item.get(b"First").ok(), level + 1, pages, visited);
+            }
+            entries.push(entry);
+            next = item.get(b"Next").ok();
+        }
+        entries
+    }
+
+    /// Target page of an outline item or link annotation, from `/Dest` or a
+    /// `/GoTo` action.
+    fn item_page(&self, item: &Dictionary, pages: &PageIndex) -> Option<usize> {
+        let dest = match item.get(b"Dest") {
+            Ok(dest) => dest,
+            Err(_) => {
+                let action = deref(&self.doc, item.get(b"A").ok()?).as_dict().ok()?;
+                if action.get(b"S").and_then(Object::as_name).ok()? != b"GoTo" {
+                    return None;
+                }
+                action.get(b"D").ok()?
+            }
+        };
+        self.destination_page(dest, pages, 0)
+    }
+
+    /// Resolve an explicit or named destination to a page index.
+    pub(crate) fn destination_page(
+        &self,
+        dest: &Object,
+        pages: &PageIndex,
+        depth: usize,
+    ) -> Option<usize> {
+        if depth > 4 {
+            return None;
+        }
+        match deref(&self.doc, dest) {
+            Object::Array(items) => match items.first()? {
+                Object::Reference(id) => pages.get(id).copied(),
+                // Some writers put a page number where the reference belongs.
+                Object::Integer(number) => usize::try_from(*number).ok(),
+                _ => None,
+            },
+            // Named destinations may map to `<< /D [...] >>`.
+            Object::Dictionary(dict) => {
+                self.destination_page(dict.get(b"D").ok()?, pages, depth + 1)
+            }
+            Object::Name(name) => {
+                let dests = self.catalog()?.get(b"Dests").ok()?;
+                let target = deref(&self.doc, dests).as_dict().ok()?.get(name).ok()?;
+                self.destination_page(target, pages, depth + 1)
+            }
+            Object::String(name, _) => {
+                let names = deref(&self.doc, self.catalog()?.get(b"Names").ok()?);
+                let tree = deref(&self.doc, names.as_dict().ok()?.get(b"Dests").ok()?);
+                let target = name_tree_lookup(&self.doc, tree.as_dict().ok()?, name, 0)?;
+                self.destination_page(target, pages, depth + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn name_tree_lookup<'a>(
+    doc: &'a Document,
+    node: &'a Dictionary,
+    key: &[u8],
+    depth: usize,
+) -> Option<&'a Object> {
+    if let Ok(names) = deref(doc, node.get(b"Names").unwrap_or(&Object::Null)).as_array() {
+        for pair in names.chunks(2) {
+            if let [name, value] = pair {
+                if deref(doc, name).as_str().ok() == Some(key) {
+                    return Some(value);
+                }
+            }
+        }
+    }
+    if depth >= MAX_TREE_DEPTH {
+        return None;
+    }
+    let kids = deref(doc, node.get(b"Kids").ok()?).as_array().ok()?;
+    kids.iter()
+        .filter_map(|kid| deref(doc, kid).as_dict().ok())
+        .find_map(|kid| name_tree_lookup(doc, kid, key, depth + 1))
+}
+
+/// Convert a PDF date (`D:YYYYMMDDHHmmSSOHH'mm'`, everything after the
+/// year optional) to ISO 8601. Unparseable values are returned unchanged.
+pub(crate) fn pdf_date_to_iso(raw: &str) -> String {
+    let date = raw.strip_prefix("D:").unwrap_or(raw);
+    let digits: String = date.chars().take_while(char::is_ascii_digit).collect();
+    if digits.len() < 4 || !digits.len().is_multiple_of(2) {
+        return raw.to_string();
+    }
+    let part =
+        |start: usize, default: &'static str| digits.get(start..start + 2).unwrap_or(default);
+    let mut iso = format!(
+        "{}-{}-{}T{}:{}:{}",
+        &digits[..4],
+        part(4, "01"),
+        part(6, "01"),
+        part(8, "00"),
+        part(10, "00"),
+        part(12, "00"),
+    );
+
+    let zone: String = date[digits.len()..]
+        .chars()
+        .filter(|c| *c != '\'')
+        .collect();
+    match zone.chars().next() {
+        Some('Z') => iso.push('Z'),
+        Some(sign @ ('+' | '-')) => {
+            let offset = &zone[1..];
+            let hours = offset.get(..2).unwrap_or("00");
+            let minutes = offset.get(2..4).unwrap_or("00");
+            iso.push_str(&format!("{sign}{hours}:{minutes}"));
+        }
+        _ => {}
+    }
+    iso
+}
+
+/// Decode a PDF text string: UTF-16BE or UTF-8 with a byte order mark,
+/// otherwise PDFDocEncoding, which matches Latin-1 for printable text.
+pub(crate) fn text_string(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8_lossy(rest).into_owned();
+    }
+    bytes.iter().map(|&byte| byte as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, StringFormat};
+
+    fn utf16(text: &str) -> Object {
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        Object::String(bytes, StringFormat::Hexadecimal)
+    }
+
+    #[test]
+    fn converts_pdf_dates_to_iso() {
+        assert_eq!(
+            pdf_date_to_iso("D:20240115093000+09'00'"),
+            "2024-01-15T09:30:00+09:00"
+        );
+        assert_eq!(pdf_date_to_iso("D:2023Z"), "2023-01-01T00:00:00Z");
+        assert_eq!(pdf_date_to_iso("yesterday"), "yesterday");
+    }
+
+    #[test]
+    fn reads_link_annotations_in_display_space() {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let page_id = doc.new_object_id();
+        let uri_link = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![72.into(), 690.into(), 150.into(), 710.into()],
+            "A" => dictionary! {
+                "S" => "URI",
+                "URI" => Object::string_literal("https://www.korea.kr"),
+            },
+        };
+        let page_link = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![72.into(), 100.into(), 90.into(), 110.into()],
+            "Dest" => vec![page_id.into(), "Fit".into()],
+        };
+        let note = dictionary! {
+            "Subtype" => "Text",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+        };
+        doc.objects.insert(
+            page_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Annots" => vec![uri_link.into(), page_link.into(), note.into()],
+            }),
+        );
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "MediaBox" => vec![0.into(), 0.into(), 600.into(), 800.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let links = NativePdf::from_document(doc).page_links(0).unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            links[0].target,
+            LinkTarget::Uri("https://www.korea.kr".to_string())
+        );
+        assert_eq!(links[0].bbox, BBox::new(72.0, 90.0, 150.0, 110.0));
+        assert_eq!(links[1].target, LinkTarget::Page(0));
+    }
+
+    #[test]
+    fn reads_form_fields_through_parents() {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let page_id = doc.new_object_id();
+        let applicant_id = doc.add_object(dictionary! {
+            "T" => utf16("applicant"),
+        });
+        let name_widget = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "Parent" => applicant_id,
+            "T" => Object::string_literal("name"),
+            "TU" => utf16("성명"),
+            "FT" => "Tx",
+            "V" => utf16("홍길동"),
+            "Rect" => vec![100.into(), 700.into(), 300.into(), 720.into()],
+        });
+        let agree_widget = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "T" => Object::string_literal("agree"),
+            "FT" => "Btn",
+            "V" => "Yes",
+            "AS" => "Yes",
+            "Rect" => vec![100.into(), 650.into(), 112.into(), 662.into()],
+        });
+        let newsletter_widget = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "T" => Object::string_literal("newsletter"),
+            "FT" => "Btn",
+            "V" => "Yes",
+            "Rect" => vec![100.into(), 630.into(), 112.into(), 642.into()],
+        });
+        let radio_id = doc.add_object(dictionary! {
+            "T" => Object::string_literal("gender"),
+            "FT" => "Btn",
+            "Ff" => 1 << 15,
+            "V" => "F",
+        });
+        let radio_off = doc.add_object(dictionary! {
+            "Subtype" => "Widget",
+            "Parent" => radio_id,
+            "AS" => "Off",
+            "Rect" => vec![100.into(), 600.into(), 112.into(), 612.into()],
+        });
+        doc.objects.insert(
+            page_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Annots" => vec![
+                    name_widget.into(),
+                    agree_widget.into(),
+                    newsletter_widget.into(),
+                    radio_off.into(),
+                ],
+            }),
+        );
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "MediaBox" => vec![0.into(), 0.into(), 600.into(), 800.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let fields = NativePdf::from_document(doc).page_form_fields(0).unwrap();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[0].name, "applicant.name");
+        assert_eq!(fields[0].label.as_deref(), Some("성명"));
+        assert_eq!(fields[0].kind, FormFieldKind::Text);
+        assert_eq!(fields[0].value.as_deref(), Some("홍길동"));
+        assert_eq!(fields[0].bbox, BBox::new(100.0, 80.0, 300.0, 100.0));
+        assert_eq!(fields[1].kind, FormFieldKind::Checkbox);
+        assert_eq!(fields[1].value.as_deref(), Some("Yes"));
+        assert_eq!(
+            (fields[2].kind, fields[2].value.as_deref()),
+            (FormFieldKind::Checkbox, Some("Yes"))
+        );
+        assert_eq!(
+            (fields[3].kind, fields[3].value.as_deref()),
+            (FormFieldKind::Radio, None)
+        );
+    }
+
+    #[test]
+    fn reads_info_dictionary_and_language() {
+        let mut doc = Document::with_version("1.5");
+        let info_id = doc.add_object(dictionary! {
+            "Title" => utf16("2024 업무계획"),
+            "Producer" => Object::string_literal("Hancom PDF"),
+            "CreationDate" => Object::string_literal("D:20240115093000+09'00'"),
+        });
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Lang" => Object::string_literal("ko-KR"),
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.trailer.set("Info", info_id);
+
+        let metadata = NativePdf::from_document(doc).metadata();
+        assert_eq!(metadata.title.as_deref(), Some("2024 업무계획"));
+        assert_eq!(metadata.producer.as_deref(), Some("Hancom PDF"));
+        assert_eq!(
+            metadata.created.as_deref(),
+            Some("2024-01-15T09:30:00+09:00")
+        );
+        assert_eq!(metadata.language.as_deref(), Some("ko-KR"));
+        assert_eq!(metadata.author, None);
+    }
+
+    #[test]
+    fn reads_nested_bookmarks_with_targets() {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let page_ids: Vec<ObjectId> = (0..2)
+            .map(|_| {
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                })
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => page_ids.iter().map(|&id| id.into()).collect::<Vec<Object>>(),
+                "Count" => 2,
+            }),
+        );
+
+        let outlines_id = doc.new_object_id();
+        let chapter_id = doc.new_object_id();
+        let section_id = doc.add_object(dictionary! {
+            "Title" => utf16("1.1 추진 배경"),
+            "Parent" => chapter_id,
+            "A" => dictionary! {
+                "S" => "GoTo",
+                "D" => Object::string_literal("sec1"),
+            },
+        });
+        doc.objects.insert(
+            chapter_id,
+            Object::Dictionary(dictionary! {
+                "Title" => Object::string_literal("Overview"),
+                "Parent" => outlines_id,
+                "Dest" => vec![page_ids[0].into(), "Fit".into()],
+                "First" => section_id,
+                "Last" => section_id,
+            }),
+        );
+        doc.objects.insert(
+            outlines_id,
+            Object::Dictionary(dictionary! {
+                "First" => chapter_id,
+                "Last" => chapter_id,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+            "Outlines" => outlines_id,
+            "Names" => dictionary! {
+                "Dests" => dictionary! {
+                    "Names" => vec![
+                        Object::string_literal("sec1"),
+                        vec![page_ids[1].into(), "Fit".into()].into(),
+                    ],
+                },
+            },
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let outline = NativePdf::from_document(doc).outline();
+        assert_eq!(outline.len(), 1);
+        assert_eq!(outline[0].title, "Overview");
+        assert_eq!(outline[0].page_idx, Some(0));
+        let section = &outline[0].children[0];
+        assert_eq!(section.title, "1.1 추진 배경");
+        assert_eq!((section.level, section.page_idx), (2, Some(1)));
+    }
+}
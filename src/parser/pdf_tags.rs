@@ -0,0 +1,779 @@
+//! Logical structure of tagged PDFs.
+//!
+//! The structure tree (`/StructTreeRoot`) lists headings, paragraphs, lists,
+//! tables and figures in reading order and points at the page content each
+//! one owns through marked-content IDs (MCIDs). Custom structure types are
+//! mapped to standard ones through `/RoleMap`. A page gets its share of the
+//! tree as a flat list of block-level elements in that order.
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::HashSet;
+
+use crate::core::model::TextRole;
+use crate::parser::pdf_content::{deref, number, NativePdf};
+
+/// Deeper nesting than this is treated as a malformed tree.
+const MAX_DEPTH: usize = 64;
+/// Role-map chains longer than this are treated as cycles.
+const MAX_ROLE_MAP_STEPS: usize = 8;
+
+const STANDARD_TYPES: &[&str] = &[
+    "Document",
+    "DocumentFragment",
+    "Part",
+    "Art",
+    "Sect",
+    "Div",
+    "BlockQuote",
+    "Caption",
+    "TOC",
+    "TOCI",
+    "Index",
+    "NonStruct",
+    "Private",
+    "Aside",
+    "Title",
+    "FENote",
+    "Sub",
+    "P",
+    "H",
+    "H1",
+    "H2",
+    "H3",
+    "H4",
+    "H5",
+    "H6",
+    "L",
+    "LI",
+    "Lbl",
+    "LBody",
+    "Table",
+    "TR",
+    "TH",
+    "TD",
+    "THead",
+    "TBody",
+    "TFoot",
+    "Span",
+    "Quote",
+    "Note",
+    "Reference",
+    "BibEntry",
+    "Code",
+    "Link",
+    "Annot",
+    "Em",
+    "Strong",
+    "Ruby",
+    "RB",
+    "RT",
+    "RP",
+    "Warichu",
+    "WT",
+    "WP",
+    "Figure",
+    "Formula",
+    "Form",
+    "Artifact",
+];
+
+/// The structure tree of a tagged PDF.
+#[derive(Debug, Clone)]
+pub struct StructTree {
+    roots: Vec<StructKid>,
+}
+
+#[derive(Debug, Clone)]
+struct StructElement {
+    /// Standard structure type after role mapping, e.g. `P`, `H2`, `TD`.
+    kind: String,
+    rowspan: usize,
+    colspan: usize,
+    kids: Vec<StructKid>,
+}
+
+#[derive(Debug, Clone)]
+enum StructKid {
+    Element(StructElement),
+    /// A marked-content sequence on a page.
+    Content {
+        page: ObjectId,
+        mcid: u32,
+    },
+}
+
+/// One block-level element's content on a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedBlock {
+    pub kind: TaggedKind,
+    /// Every MCID the block owns on the page, cells included.
+    pub mcids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaggedKind {
+    Text(TextRole),
+    Figure,
+    Formula,
+    Table(Vec<TaggedCell>),
+}
+
+/// A `TH`/`TD` cell placed on the table grid. Rows are those of the table
+/// that have content on the page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedCell {
+    pub row: usize,
+    pub col: usize,
+    pub rowspan: usize,
+    pub colspan: usize,
+    pub header: bool,
+    pub mcids: Vec<u32>,
+}
+
+/// How an element contributes to the page's blocks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    /// Container whose children are blocks: `Document`, `Sect`, `L`, ...
+    Group,
+    Text(TextRole),
+    /// `H`: a heading whose level is its section depth.
+    Heading,
+    Table,
+    Figure,
+    Formula,
+    /// Part of the surrounding block: `Span`, `Link`, `LBody`, ...
+    Inline,
+    Artifact,
+}
+
+impl Kind {
+    fn of(kind: &str) -> Self {
+        match kind {
+            "Title" | "H1" => Kind::Text(TextRole::Heading(1)),
+            "H2" => Kind::Text(TextRole::Heading(2)),
+            "H3" => Kind::Text(TextRole::Heading(3)),
+            "H4" => Kind::Text(TextRole::Heading(4)),
+            "H5" => Kind::Text(TextRole::Heading(5)),
+            "H6" => Kind::Text(TextRole::Heading(6)),
+            "H" => Kind::Heading,
+            "P" | "BlockQuote" | "Note" | "FENote" | "Code" | "TOCI" | "BibEntry" => {
+                Kind::Text(TextRole::Paragraph)
+            }
+            "LI" => Kind::Text(TextRole::ListItem),
+            "Caption" => Kind::Text(TextRole::Caption),
+            "Table" => Kind::Table,
+            "Figure" => Kind::Figure,
+            "Formula" => Kind::Formula,
+            "Span" | "Quote" | "Reference" | "Link" | "Annot" | "Em" | "Strong" | "Lbl"
+            | "LBody" | "Sub" | "Ruby" | "RB" | "RT" | "RP" | "Warichu" | "WT" | "WP" | "Form" => {
+                Kind::Inline
+            }
+            "Artifact" => Kind::Artifact,
+            _ => Kind::Group,
+        }
+    }
+}
+
+impl NativePdf {
+    /// The document's structure tree, when it is tagged.
+    pub fn structure_tree(&self) -> Option<StructTree> {
+        let root = deref(&self.doc, self.catalog()?.get(b"StructTreeRoot").ok()?)
+            .as_dict()
+            .ok()?;
+        let mut reader = TreeReader {
+            doc: &self.doc,
+            role_map: root
+                .get(b"RoleMap")
+                .ok()
+                .and_then(|obj| deref(&self.doc, obj).as_dict().ok()),
+            visited: HashSet::new(),
+        };
+        let roots = reader.kids(root.get(b"K").ok()?, None, 0);
+        (!roots.is_empty()).then_some(StructTree { roots })
+    }
+}
+
+struct TreeReader<'a> {
+    doc: &'a Document,
+    role_map: Option<&'a Dictionary>,
+    /// Objects already read, so shared or cyclic references are not
+    /// followed twice.
+    visited: HashSet<ObjectId>,
+}
+
+impl TreeReader<'_> {
+    /// Kids of an element: `page` is the nearest `/Pg` above them, which
+    /// bare MCIDs refer to.
+    fn kids(&mut self, obj: &Object, page: Option<ObjectId>, depth: usize) -> Vec<StructKid> {
+        if depth > MAX_DEPTH {
+            return Vec::new();
+        }
+        if let Object::Reference(id) = obj {
+            if !self.visited.insert(*id) {
+                return Vec::new();
+            }
+        }
+        match deref(self.doc, obj) {
+            Object::Array(items) => {
+                let mut kids = Vec::new();
+                for item in items {
+                    kids.extend(self.kids(item, page, depth));
+                }
+                kids
+            }
+            Object::Integer(mcid) => content(page, *mcid).into_iter().collect(),
+            Object::Dictionary(dict) => {
+                let page = dict
+                    .get(b"Pg")
+                    .ok()
+                    .and_then(|obj| obj.as_reference().ok())
+                    .or(page);
+                match dict.get(b"Type").ok().and_then(|obj| obj.as_name().ok()) {
+                    // Content inside a form XObject (`/Stm`) numbers its
+                    // MCIDs separately from the page.
+                    Some(b"MCR") if !dict.has(b"Stm") => dict
+                        .get(b"MCID")
+                        .ok()
+                        .and_then(|obj| deref(self.doc, obj).as_i64().ok())
+                        .and_then(|mcid| content(page, mcid))
+                        .into_iter()
+                        .collect(),
+                    Some(b"MCR" | b"OBJR") => Vec::new(),
+                    _ => self
+                        .element(dict, page, depth)
+                        .map(StructKid::Element)
+                        .into_iter()
+                        .collect(),
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn element(
+        &mut self,
+        dict: &Dictionary,
+        page: Option<ObjectId>,
+        depth: usize,
+    ) -> Option<StructElement> {
+        let kind = self.standard_type(dict.get(b"S").ok()?.as_name().ok()?);
+        let kids = match dict.get(b"K") {
+            Ok(kids) => self.kids(kids, page, depth + 1),
+            Err(_) => Vec::new(),
+        };
+        Some(StructElement {
+            kind,
+            rowspan: self.span_attribute(dict, b"RowSpan"),
+            colspan: self.span_attribute(dict, b"ColSpan"),
+            kids,
+        })
+    }
+
+    fn standard_type(&self, name: &[u8]) -> String {
+        let mut name = name.to_vec();
+        for _ in 0..MAX_ROLE_MAP_STEPS {
+            if STANDARD_TYPES.iter().any(|kind| kind.as_bytes() == name) {
+                break;
+            }
+            let mapped = self
+                .role_map
+                .and_then(|map| map.get(&name).ok())
+                .and_then(|obj| deref(self.doc, obj).as_name().ok());
+            match mapped {
+                Some(mapped) => name = mapped.to_vec(),
+                None => break,
+            }
+        }
+        String::from_utf8_lossy(&name).into_owned()
+    }
+
+    /// `RowSpan`/`ColSpan` from the element's attribute objects (`/A`, a
+    /// dictionary or an array of them with revision numbers), at least 1.
+    fn span_attribute(&self, dict: &Dictionary, key: &[u8]) -> usize {
+        let Ok(attributes) = dict.get(b"A") else {
+            return 1;
+        };
+        let objects = match deref(self.doc, attributes) {
+            Object::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        objects
+            .into_iter()
+            .filter_map(|obj| deref(self.doc, obj).as_dict().ok())
+            .find_map(|attrs| number(self.doc, attrs.get(key).ok()?))
+            .map_or(1, |span| (span as usize).max(1))
+    }
+}
+
+fn content(page: Option<ObjectId>, mcid: i64) -> Option<StructKid> {
+    Some(StructKid::Content {
+        page: page?,
+        mcid: u32::try_from(mcid).ok()?,
+    })
+}
+
+impl StructTree {
+    /// Block-level elements with content on `page`, in reading order.
+    pub fn page_blocks(&self, page: ObjectId) -> Vec<TaggedBlock> {
+        let mut blocks = Vec::new();
+        PageWalker {
+            page,
+            blocks: &mut blocks,
+        }
+        .group(&self.roots, 0);
+        blocks
+    }
+}
+
+struct PageWalker<'a> {
+    page: ObjectId,
+    blocks: &'a mut Vec<TaggedBlock>,
+}
+
+impl PageWalker<'_> {
+    /// Children of a container. Loose content and inline elements between
+    /// blocks form paragraphs of their own.
+    fn group(&mut self, kids: &[StructKid], sections: usize) {
+        let mut loose = Vec::new();
+        for kid in kids {
+            match kid {
+                StructKid::Content { page, mcid } => {
+                    if *page == self.page {
+                        loose.push(*mcid);
+                    }
+                }
+                StructKid::Element(element) if Kind::of(&element.kind) == Kind::Inline => {
+                    let mut nested = Vec::new();
+                    self.text(element, &mut loose, &mut nested);
+                    if !nested.is_empty() {
+                        self.push_text(TextRole::Paragraph, std::mem::take(&mut loose));
+                        for element in nested {
+                            self.element(element, sections);
+                        }
+                    }
+                }
+                StructKid::Element(element) => {
+                    self.push_text(TextRole::Paragraph, std::mem::take(&mut loose));
+                    self.element(element, sections);
+                }
+            }
+        }
+        self.push_text(TextRole::Paragraph, loose);
+    }
+
+    fn element(&mut self, element: &StructElement, sections: usize) {
+        match Kind::of(&element.kind) {
+            Kind::Group => {
+                let sections = sections + usize::from(element.kind == "Sect");
+                self.group(&element.kids, sections);
+            }
+            kind @ (Kind::Text(_) | Kind::Heading | Kind::Inline) => {
+                let role = match kind {
+                    Kind::Text(role) => role,
+                    Kind::Heading => TextRole::Heading(sections.clamp(1, 6) as u8),
+                    _ => TextRole::Paragraph,
+                };
+                let (mut mcids, mut nested) = (Vec::new(), Vec::new());
+                self.text(element, &mut mcids, &mut nested);
+                self.push_text(role, mcids);
+                for element in nested {
+                    self.element(element, sections);
+                }
+            }
+            kind @ (Kind::Figure | Kind::Formula) => {
+                let (mut mcids, mut captions) = (Vec::new(), Vec::new());
+                self.content(element, &mut mcids, &mut captions);
+                if !mcids.is_empty() {
+                    let kind = if kind == Kind::Figure {
+                        TaggedKind::Figure
+                    } else {
+                        TaggedKind::Formula
+                    };
+                    self.blocks.push(TaggedBlock { kind, mcids });
+                }
+                for caption in captions {
+                    self.element(caption, sections);
+                }
+            }
+            Kind::Table => self.table(element, sections),
+            Kind::Artifact => {}
+        }
+    }
+
+    /// Content of a text element. Lists, tables, figures and formulas
+    /// inside it are collected into `nested` to become blocks of their own.
+    fn text<'e>(
+        &self,
+        element: &'e StructElement,
+        mcids: &mut Vec<u32>,
+        nested: &mut Vec<&'e StructElement>,
+    ) {
+        for kid in &element.kids {
+            match kid {
+                StructKid::Content { page, mcid } => {
+                    if *page == self.page {
+                        mcids.push(*mcid);
+                    }
+                }
+                StructKid::Element(child) => match Kind::of(&child.kind) {
+                    Kind::Table | Kind::Figure | Kind::Formula => nested.push(child),
+                    Kind::Group if child.kind == "L" => nested.push(child),
+                    Kind::Artifact => {}
+                    _ => self.text(child, mcids, nested),
+                },
+            }
+        }
+    }
+
+    /// All content under an element, with `Caption` children set aside.
+    fn content<'e>(
+        &self,
+        element: &'e StructElement,
+        mcids: &mut Vec<u32>,
+        captions: &mut Vec<&'e StructElement>,
+    ) {
+        for kid in &element.kids {
+            match kid {
+                StructKid::Content { page, mcid } => {
+                    if *page == self.page {
+                        mcids.push(*mcid);
+                    }
+                }
+                StructKid::Element(child) if child.kind == "Caption" => captions.push(child),
+                StructKid::Element(child) if child.kind != "Artifact" => {
+                    self.content(child, mcids, captions)
+                }
+                StructKid::Element(_) => {}
+            }
+        }
+    }
+
+    fn push_text(&mut self, role: TextRole, mcids: Vec<u32>) {
+        if !mcids.is_empty() {
+            self.blocks.push(TaggedBlock {
+                kind: TaggedKind::Text(role),
+                mcids,
+            });
+        }
+    }
+
+    /// Lay `TH`/`TD` cells out on a grid: each cell takes the next column
+    /// not covered by a row span from above. Only rows with content on this
+    /// page are kept, so a table split across pages starts at row 0 on each.
+    fn table(&mut self, element: &StructElement, sections: usize) {
+        let mut rows: Vec<(Vec<&StructElement>, bool)> = Vec::new();
+        let (mut before, mut after) = (Vec::new(), Vec::new());
+        collect_rows(element, false, &mut rows, &mut before, &mut after);
+
+        // Spans come straight from the file: keep them on the table, no
+        // wider than all its cells side by side.
+        let max_colspan = rows.iter().map(|(cells, _)| cells.len()).sum::<usize>();
+        let mut cells = Vec::new();
+        let mut covered: HashSet<(usize, usize)> = HashSet::new();
+        for (row, (row_cells, in_head)) in rows.iter().enumerate() {
+            let header_row = *in_head || row_cells.iter().all(|cell| cell.kind == "TH");
+            let mut col = 0;
+            for cell in row_cells {
+                while covered.contains(&(row, col)) {
+                    col += 1;
+                }
+                let rowspan = cell.rowspan.min(rows.len() - row);
+                let colspan = cell.colspan.min(max_colspan);
+                for r in row..row + rowspan {
+                    for c in col..col + colspan {
+                        covered.insert((r, c));
+                    }
+                }
+                let mut mcids = Vec::new();
+                self.content(cell, &mut mcids, &mut Vec::new());
+                cells.push(TaggedCell {
+                    row,
+                    col,
+                    rowspan,
+                    colspan,
+                    header: header_row,
+                    mcids,
+                });
+                col += colspan;
+            }
+        }
+
+        let kept: Vec<usize> = (0..rows.len())
+            .filter(|&row| {
+                cells
+                    .iter()
+                    .any(|cell| cell.row == row && !cell.mcids.is_empty())
+            })
+            .collect();
+        let cells: Vec<TaggedCell> = cells
+            .into_iter()
+            .filter_map(|mut cell| {
+                let row = kept.iter().position(|&kept| kept == cell.row)?;
+                cell.rowspan = kept
+                    .iter()
+                    .filter(|&&kept| kept >= cell.row && kept < cell.row + cell.rowspan)
+                    .count();
+                cell.row = row;
+                Some(cell)
+            })
+            .collect();
+
+        for caption in before {
+            self.element(caption, sections);
+        }
+        if !cells.is_empty() {
+            let mcids = cells.iter().flat_map(|cell| cell.mcids.clone()).collect();
+            self.blocks.push(TaggedBlock {
+                kind: TaggedKind::Table(cells),
+                mcids,
+            });
+        }
+        for caption in after {
+            self.element(caption, sections);
+        }
+    }
+}
+
+/// Rows of a table with whether they sit in `THead`, looking through
+/// `THead`/`TBody`/`TFoot` and other wrappers. Captions are split by
+/// whether they come before the first row.
+fn collect_rows<'e>(
+    element: &'e StructElement,
+    in_head: bool,
+    rows: &mut Vec<(Vec<&'e StructElement>, bool)>,
+    before: &mut Vec<&'e StructElement>,
+    after: &mut Vec<&'e StructElement>,
+) {
+    for kid in &element.kids {
+        let StructKid::Element(child) = kid else {
+            continue;
+        };
+        match child.kind.as_str() {
+            "TR" => {
+                let cells = child
+                    .kids
+                    .iter()
+                    .filter_map(|kid| match kid {
+                        StructKid::Element(cell) if matches!(cell.kind.as_str(), "TH" | "TD") => {
+                            Some(cell)
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                rows.push((cells, in_head));
+            }
+            "Caption" if rows.is_empty() => before.push(child),
+            "Caption" => after.push(child),
+            "THead" => collect_rows(child, true, rows, before, after),
+            "TBody" | "TFoot" => collect_rows(child, false, rows, before, after),
+            "Artifact" => {}
+            _ => collect_rows(child, in_head, rows, before, after),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    #[test]
+    fn flattens_page_structure_in_reading_order() {
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "MediaBox" => vec![0.into(), 0.into(), 600.into(), 800.into()],
+            }),
+        );
+        let cell =
+            |kind: &str, mcid: i64| Object::Dictionary(dictionary! { "S" => kind, "K" => mcid });
+        let document = dictionary! {
+            "S" => "Document",
+            "Pg" => page_id,
+            "K" => vec![
+                Object::Dictionary(dictionary! { "S" => "Heading1", "K" => 0 }),
+                Object::Dictionary(dictionary! {
+                    "S" => "Sect",
+                    "K" => vec![
+                        Object::Dictionary(dictionary! { "S" => "H", "K" => 1 }),
+                        Object::Dictionary(dictionary! {
+                            "S" => "P",
+                            "K" => vec![
+                                2.into(),
+                                Object::Dictionary(dictionary! {
+                                    "Type" => "MCR",
+                                    "Pg" => page_id,
+                                    "MCID" => 3,
+                                }),
+                            ],
+                        }),
+                    ],
+                }),
+                Object::Dictionary(dictionary! {
+                    "S" => "Table",
+                    "K" => vec![
+                        Object::Dictionary(dictionary! {
+                            "S" => "TR",
+                            "K" => vec![
+                                Object::Dictionary(dictionary! {
+                                    "S" => "TH",
+                                    "K" => 4,
+                                    "A" => dictionary! { "O" => "Table", "ColSpan" => 2 },
+                                }),
+                            ],
+                        }),
+                        Object::Dictionary(dictionary! {
+                            "S" => "TR",
+                            "K" => vec![cell("TD", 5), cell("TD", 6)],
+                        }),
+                    ],
+                }),
+                Object::Dictionary(dictionary! {
+                    "S" => "L",
+                    "K" => vec![Object::Dictionary(dictionary! {
+                        "S" => "LI",
+                        "K" => vec![cell("Lbl", 7), cell("LBody", 8)],
+                    })],
+                }),
+            ],
+        };
+        let root = doc.add_object(dictionary! {
+            "Type" => "StructTreeRoot",
+            "K" => document,
+            "RoleMap" => dictionary! { "Heading1" => "H1" },
+        });
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+            "StructTreeRoot" => root,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let pdf = NativePdf::from_document(doc);
+        let blocks = pdf.structure_tree().unwrap().page_blocks(page_id);
+        let text = |role, mcids: Vec<u32>| TaggedBlock {
+            kind: TaggedKind::Text(role),
+            mcids,
+        };
+        let td = |row, col, colspan, header, mcid| TaggedCell {
+            row,
+            col,
+            rowspan: 1,
+            colspan,
+            header,
+            mcids: vec![mcid],
+        };
+        assert_eq!(
+            blocks,
+            vec![
+                text(TextRole::Heading(1), vec![0]),
+                text(TextRole::Heading(1), vec![1]),
+                text(TextRole::Paragraph, vec![2, 3]),
+                TaggedBlock {
+                    kind: TaggedKind::Table(vec![
+                        td(0, 0, 2, true, 4),
+                        td(1, 0, 1, false, 5),
+                        td(1, 1, 1, false, 6),
+                    ]),
+                    mcids: vec![4, 5, 6],
+                },
+                text(TextRole::ListItem, vec![7, 8]),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_blocks_nested_in_loose_inline_elements() {
+        let page = (1, 0);
+        let content = |mcid| StructKid::Content { page, mcid };
+        let element = |kind: &str, kids| {
+            StructKid::Element(StructElement {
+                kind: kind.to_string(),
+                rowspan: 1,
+                colspan: 1,
+                kids,
+            })
+        };
+        let tree = StructTree {
+            roots: vec![element(
+                "Document",
+                vec![
+                    content(0),
+                    element(
+                        "Link",
+                        vec![content(1), element("Figure", vec![content(2)])],
+                    ),
+                    content(3),
+                ],
+            )],
+        };
+
+        let text = |mcids| TaggedBlock {
+            kind: TaggedKind::Text(TextRole::Paragraph),
+            mcids,
+        };
+        assert_eq!(
+            tree.page_blocks(page),
+            vec![
+                text(vec![0, 1]),
+                TaggedBlock {
+                    kind: TaggedKind::Figure,
+                    mcids: vec![2],
+                },
+                text(vec![3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn clamps_cell_spans_to_the_table() {
+        let page = (1, 0);
+        let cell = |mcid: u32, rowspan: usize, colspan: usize| {
+            StructKid::Element(StructElement {
+                kind: "TD".to_string(),
+                rowspan,
+                colspan,
+                kids: vec![StructKid::Content { page, mcid }],
+            })
+        };
+        let row = |cells: Vec<StructKid>| {
+            StructKid::Element(StructElement {
+                kind: "TR".to_string(),
+                rowspan: 1,
+                colspan: 1,
+                kids: cells,
+            })
+        };
+        let table = StructElement {
+            kind: "Table".to_string(),
+            rowspan: 1,
+            colspan: 1,
+            kids: vec![
+                row(vec![cell(0, usize::MAX, 1_000_000_000), cell(1, 1, 1)]),
+                row(vec![cell(2, 1, 1)]),
+            ],
+        };
+        let tree = StructTree {
+            roots: vec![StructKid::Element(table)],
+        };
+
+        let blocks = tree.page_blocks(page);
+        let TaggedKind::Table(cells) = &blocks[0].kind else {
+            panic!("expected a table, got {blocks:?}");
+        };
+        let spans: Vec<(usize, usize, usize, usize)> = cells
+            .iter()
+            .map(|cell| (cell.row, cell.col, cell.rowspan, cell.colspan))
+            .collect();
+        assert_eq!(spans, vec![(0, 0, 2, 3), (0, 3, 1, 1), (1, 3, 1, 1)]);
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+use crate::core::error::DocumentError;
+
+/// Pass a document password to a poppler tool. Poppler tries the owner
+/// password first and then the user password, so the same value is given as
+/// both and either kind of password opens the file.
+pub fn password_args<'a>(cmd: &'a mut Command, password: Option<&str>) -> &'a mut Command {
+    if let Some(password) = password {
+        cmd.arg("-opw").arg(password).arg("-upw").arg(password);
+    }
+    cmd
+}
+
+/// Error for a poppler run that exited unsuccessfully. Encrypted inputs are
+/// reported as [`DocumentError`] so they can be told apart from broken ones.
+pub fn failure(
+    program: &str,
+    path: &Path,
+    output: &Output,
+    password: Option<&str>,
+) -> anyhow::Error {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if is_password_error(&stderr) {
+        let path = path.to_path_buf();
+        return match password {
+            Some(_) => DocumentError::IncorrectPassword { path }.into(),
+            None => DocumentError::Encrypted { path }.into(),
+        };
+    }
+    anyhow::anyhow!("{program} failed with status: {}", output.status)
+}
+
+fn is_password_error(stderr: &str) -> bool {
+    stderr.contains("Incorrect password")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_password_for_both_roles() {
+        let mut cmd = Command::new("pdfinfo");
+        password_args(&mut cmd, Some("s3cret")).arg("in.pdf");
+        let args: Vec<_> = cmd.get_args().map(|arg| arg.to_str().unwrap()).collect();
+        assert_eq!(args, ["-opw", "s3cret", "-upw", "s3cret", "in.pdf"]);
+    }
+
+    #[test]
+    fn recognizes_poppler_password_errors() {
+        assert!(is_password_error(
+            "Command Line Error: Incorrect password\n"
+        ));
+        assert!(!is_password_error(
+            "Syntax Error: Couldn't find trailer dictionary\n"
+        ));
+    }
+}
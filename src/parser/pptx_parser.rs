@@ -6,18 +6,48 @@ use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::core::geometry::BBox;
-use crate::core::model::{Block, Line, PageHypothesis, Provenance, Span};
-use crate::parser::ParserTrack;
+use crate::core::model::{
+    Block, DocumentMetadata, Line, LinkTarget, OutlineEntry, PageHypothesis, PageLink, PageSize,
+    Provenance, Span, TextStyle,
+};
+use crate::parser::ooxml;
+use crate::parser::{PageImage, ParserTrack};
 
 #[derive(Debug, Clone, Deserialize)]
 struct SlideRun {
     text: String,
+    #[serde(default)]
+    bold: bool,
+    #[serde(default)]
+    italic: bool,
+    size: Option<f32>,
+    font: Option<String>,
+    link: Option<LinkTarget>,
+}
+
+/// A text shape: its box in points and its paragraphs of formatted runs.
+#[derive(Debug, Clone, Deserialize)]
+struct SlideShape {
     bbox: [f32; 4],
+    paragraphs: Vec<Vec<SlideRun>>,
+    /// Set for the slide's title placeholder.
+    #[serde(default)]
+    title: bool,
+}
+
+/// Script output: slide size in points and the shapes of every slide.
+#[derive(Debug, Clone, Deserialize)]
+struct Presentation {
+    width: f32,
+    height: f32,
+    slides: Vec<Vec<SlideShape>>,
 }
 
 #[derive(Debug)]
 pub struct PptxParser {
-    slides: Vec<Vec<SlideRun>>,
+    path: PathBuf,
+    slides: Vec<Vec<SlideShape>>,
+    page: PageSize,
     render_pdf_path: PathBuf,
     _temp_dir: Option<PathBuf>,
 }
@@ -94,7 +124,25 @@ impl PptxParser {
             );
         }
 
-        let script = r#"
+        let presentation = read_presentation(&path)?;
+
+        Ok(Self {
+            path,
+            slides: presentation.slides,
+            page: PageSize {
+                width: presentation.width,
+                height: presentation.height,
+                rotation: 0,
+            },
+            render_pdf_path,
+            _temp_dir: Some(working_dir),
+        })
+    }
+}
+
+/// Reads slide text, formatting, links and title placeholders from a PPTX
+/// package and prints them as a [`Presentation`].
+const SLIDES_SCRIPT: &str = r#"
 import json, re, sys, zipfile, xml.etree.ElementTree as ET
 path = sys.argv[1]
 ns = {
@@ -106,16 +154,53 @@ with zipfile.ZipFile(path) as z:
     sld = pres.find('.//p:sldSz', ns)
     sw = float(sld.attrib.get('cx', '9144000')) if sld is not None else 9144000.0
     sh = float(sld.attrib.get('cy', '6858000')) if sld is not None else 6858000.0
-    sx = 1000.0 / max(sw, 1.0)
-    sy = 1400.0 / max(sh, 1.0)
+    sx = sy = 1.0 / 12700.0  # EMU -> points
     slides = sorted([n for n in z.namelist() if n.startswith('ppt/slides/slide') and n.endswith('.xml')], key=lambda n:int(re.search(r'slide(\d+)\.xml$', n).group(1)))
     out=[]
+    slide_index = {n.rsplit('/', 1)[-1]: i for i, n in enumerate(slides)}
+    R = '{http://schemas.openxmlformats.org/officeDocument/2006/relationships}'
     for sn in slides:
         root = ET.fromstring(z.read(sn))
-        runs=[]
+        rels = {}
+        rels_name = sn.replace('ppt/slides/', 'ppt/slides/_rels/') + '.rels'
+        if rels_name in z.namelist():
+            for rel in ET.fromstring(z.read(rels_name)):
+                rels[rel.get('Id')] = rel.get('Target')
+        shapes=[]
         for sp in root.findall('.//p:sp', ns):
-            text = ' '.join([t.text for t in sp.findall('.//a:t', ns) if t.text]).strip()
-            if not text:
+            paragraphs = []
+            for para in sp.findall('.//a:p', ns):
+                lines = [[]]
+                for r in [el for el in para if el.tag.split('}')[-1] in ('r', 'fld', 'br')]:
+                    if r.tag.endswith('}br'):
+                        lines.append([])
+                        continue
+                    t = r.find('a:t', ns)
+                    if t is None or not t.text:
+                        continue
+                    run = {'text': t.text}
+                    rpr = r.find('a:rPr', ns)
+                    if rpr is not None:
+                        run['bold'] = rpr.attrib.get('b') in ('1', 'true')
+                        run['italic'] = rpr.attrib.get('i') in ('1', 'true')
+                        if rpr.attrib.get('sz', '').isdigit():
+                            run['size'] = int(rpr.attrib['sz']) / 100.0
+                        latin = rpr.find('a:latin', ns)
+                        typeface = latin.attrib.get('typeface', '') if latin is not None else ''
+                        if typeface and not typeface.startswith('+'):
+                            run['font'] = typeface
+                        click = rpr.find('a:hlinkClick', ns)
+                        target = rels.get(click.get(R + 'id')) if click is not None else None
+                        if target and target.rsplit('/', 1)[-1] in slide_index:
+                            run['link'] = {'page': slide_index[target.rsplit('/', 1)[-1]]}
+                        elif target:
+                            run['link'] = {'uri': target}
+                    lines[-1].append(run)
+                # A soft break (a:br) starts a new line of the paragraph.
+                for line_runs in lines:
+                    if ''.join(run['text'] for run in line_runs).strip():
+                        paragraphs.append(line_runs)
+            if not paragraphs:
                 continue
             off = sp.find('.//a:off', ns)
             ext = sp.find('.//a:ext', ns)
@@ -123,34 +208,29 @@ with zipfile.ZipFile(path) as z:
             y = float(off.attrib.get('y', '0')) if off is not None else 0.0
             w = float(ext.attrib.get('cx', str(sw))) if ext is not None else sw
             h = float(ext.attrib.get('cy', str(sh))) if ext is not None else sh
-            runs.append({'text':text, 'bbox':[x*sx, y*sy, (x+w)*sx, (y+h)*sy]})
-        out.append(runs)
-print(json.dumps(out, ensure_ascii=False))
+            ph = sp.find('p:nvSpPr/p:nvPr/p:ph', ns)
+            is_title = ph is not None and ph.attrib.get('type') in ('title', 'ctrTitle')
+            shapes.append({'paragraphs':paragraphs, 'bbox':[x*sx, y*sy, (x+w)*sx, (y+h)*sy], 'title':is_title})
+        out.append(shapes)
+print(json.dumps({'width': sw * sx, 'height': sh * sy, 'slides': out}, ensure_ascii=False))
 "#;
 
-        let output = Command::new("python3")
-            .arg("-c")
-            .arg(script)
-            .arg(&path)
-            .output()
-            .with_context(|| "failed to invoke python3 for PPTX parsing")?;
+fn read_presentation(path: &Path) -> Result<Presentation> {
+    let output = Command::new("python3")
+        .arg("-c")
+        .arg(SLIDES_SCRIPT)
+        .arg(path)
+        .output()
+        .with_context(|| "failed to invoke python3 for PPTX parsing")?;
 
-        if !output.status.success() {
-            anyhow::bail!(
-                "PPTX parsing failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
-        let slides: Vec<Vec<SlideRun>> =
-            serde_json::from_slice(&output.stdout).context("invalid PPTX parser output")?;
-
-        Ok(Self {
-            slides,
-            render_pdf_path,
-            _temp_dir: Some(working_dir),
-        })
+    if !output.status.success() {
+        anyhow::bail!(
+            "PPTX parsing failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
     }
+
+    serde_json::from_slice(&output.stdout).context("invalid PPTX parser output")
 }
 
 impl ParserTrack for PptxParser {
@@ -158,24 +238,46 @@ impl ParserTrack for PptxParser {
         Ok(self.slides.len())
     }
 
+    fn page_size(&self, _page_idx: usize) -> Result<PageSize> {
+        Ok(self.page)
+    }
+
     fn analyze_page(&self, page_idx: usize) -> Result<PageHypothesis> {
-        let runs = self.slides.get(page_idx).cloned().unwrap_or_default();
+        let shapes = self.slides.get(page_idx).cloned().unwrap_or_default();
         let mut blocks = Vec::new();
 
-        for run in runs {
-            let bbox = BBox::new(run.bbox[0], run.bbox[1], run.bbox[2], run.bbox[3]);
+        for shape in shapes {
+            let bbox = BBox::new(shape.bbox[0], shape.bbox[1], shape.bbox[2], shape.bbox[3]);
+            let lines = shape
+                .paragraphs
+                .into_iter()
+                .map(|runs| Line {
+                    spans: runs
+                        .into_iter()
+                        .map(|run| Span {
+                            text: run.text,
+                            bbox,
+                            source: Provenance::Parser,
+                            style: Some(TextStyle {
+                                font: run.font,
+                                size: run.size,
+                                bold: run.bold,
+                                italic: run.italic,
+                            }),
+                            link: run.link,
+                            revision: None,
+                        })
+                        .collect(),
+                })
+                .collect();
             blocks.push(Block::TextBlock {
                 bbox,
-                lines: vec![Line {
-                    spans: vec![Span {
-                        text: run.text,
-                        bbox,
-                        source: Provenance::Parser,
-                        style: None,
-                    }],
-                }],
+                lines,
                 confidence: 0.6,
                 source: Provenance::Parser,
+                role: None,
+                order: None,
+                tracks: Vec::new(),
                 debug: None,
             });
         }
@@ -183,11 +285,42 @@ impl ParserTrack for PptxParser {
         Ok(PageHypothesis {
             page_idx,
             blocks,
-            width: 1000,
-            height: 1400,
+            width: self.page.width.round() as u32,
+            height: self.page.height.round() as u32,
         })
     }
 
+    fn page_links(&self, _page_idx: usize) -> Result<Vec<PageLink>> {
+        Ok(Vec::new())
+    }
+
+    fn page_images(&self, _page_idx: usize) -> Result<Vec<PageImage>> {
+        Ok(Vec::new())
+    }
+
+    fn outline(&self) -> Result<Vec<OutlineEntry>> {
+        let titles = self
+            .slides
+            .iter()
+            .enumerate()
+            .filter_map(|(slide_idx, shapes)| {
+                let shape = shapes.iter().find(|shape| shape.title)?;
+                let title = shape
+                    .paragraphs
+                    .iter()
+                    .map(|runs| runs.iter().map(|run| run.text.as_str()).collect::<String>())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Some(OutlineEntry::new(title.trim(), 1, Some(slide_idx)))
+            })
+            .collect();
+        Ok(titles)
+    }
+
+    fn metadata(&self) -> Result<DocumentMetadata> {
+        ooxml::read_core_properties(&self.path)
+    }
+
     fn supports_ocr_rendering(&self) -> bool {
         true
     }
@@ -196,3 +329,217 @@ impl ParserTrack for PptxParser {
         Some(&self.render_pdf_path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P_NS: &str = "http://schemas.openxmlformats.org/presentationml/2006/main";
+    const A_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    /// A ZIP archive storing `parts` uncompressed.
+    fn stored_zip(parts: &[(&str, String)]) -> Vec<u8> {
+        let (mut data, mut directory) = (Vec::new(), Vec::new());
+        for (name, content) in parts {
+            let (crc, size) = (crc32(content.as_bytes()), content.len() as u32);
+            let offset = data.len() as u32;
+            let header = |signature: u32, central: bool| {
+                let mut out = signature.to_le_bytes().to_vec();
+                if central {
+                    out.extend(20u16.to_le_bytes());
+                }
+                out.extend([20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+                out.extend(crc.to_le_bytes());
+                out.extend(size.to_le_bytes());
+                out.extend(size.to_le_bytes());
+                out.extend((name.len() as u16).to_le_bytes());
+                out.extend(0u16.to_le_bytes());
+                if central {
+                    out.extend([0; 10]);
+                    out.extend(offset.to_le_bytes());
+                }
+                out.extend(name.as_bytes());
+                out
+            };
+            data.extend(header(0x0403_4b50, false));
+            data.extend(content.as_bytes());
+            directory.extend(header(0x0201_4b50, true));
+        }
+        let (start, count) = (data.len() as u32, parts.len() as u16);
+        let directory_len = directory.len() as u32;
+        data.extend(directory);
+        data.extend(0x0605_4b50u32.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend(count.to_le_bytes());
+        data.extend(count.to_le_bytes());
+        data.extend(directory_len.to_le_bytes());
+        data.extend(start.to_le_bytes());
+        data.extend([0; 2]);
+        data
+    }
+
+    fn shape(placeholder: &str, paragraphs: &str) -> String {
+        format!(
+            r#"<p:sp>
+              <p:nvSpPr><p:nvPr>{placeholder}</p:nvPr></p:nvSpPr>
+              <p:spPr><a:xfrm><a:off x="127000" y="254000"/><a:ext cx="1270000" cy="635000"/></a:xfrm></p:spPr>
+              <p:txBody>{paragraphs}</p:txBody>
+            </p:sp>"#
+        )
+    }
+
+    fn slide(shapes: &[String]) -> String {
+        format!(
+            r#"<p:sld xmlns:p="{P_NS}" xmlns:a="{A_NS}" xmlns:r="{}"><p:cSld><p:spTree>{}</p:spTree></p:cSld></p:sld>"#,
+            ooxml::RELATIONSHIPS_NS,
+            shapes.concat()
+        )
+    }
+
+    /// Parse a presentation with `slides` (shape XML), the first slide's
+    /// relationships being `slide1_rels`.
+    fn parse_slides(slides: &[Vec<String>], slide1_rels: &str) -> Result<PptxParser> {
+        let mut parts = vec![(
+            "ppt/presentation.xml".to_string(),
+            format!(
+                r#"<p:presentation xmlns:p="{P_NS}"><p:sldSz cx="9144000" cy="5143500"/></p:presentation>"#
+            ),
+        )];
+        for (idx, shapes) in slides.iter().enumerate() {
+            parts.push((format!("ppt/slides/slide{}.xml", idx + 1), slide(shapes)));
+        }
+        parts.push((
+            "ppt/slides/_rels/slide1.xml.rels".to_string(),
+            format!(
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{slide1_rels}</Relationships>"#
+            ),
+        ));
+        let parts: Vec<(&str, String)> = parts
+            .iter()
+            .map(|(name, content)| (name.as_str(), content.clone()))
+            .collect();
+
+        let path = std::env::temp_dir().join(format!(
+            "docstruct-pptx-test-{}-{}.pptx",
+            std::process::id(),
+            SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos()
+        ));
+        fs::write(&path, stored_zip(&parts))?;
+        let presentation = read_presentation(&path);
+        fs::remove_file(&path)?;
+        let presentation = presentation?;
+        Ok(PptxParser {
+            path: path.clone(),
+            slides: presentation.slides,
+            page: PageSize {
+                width: presentation.width,
+                height: presentation.height,
+                rotation: 0,
+            },
+            render_pdf_path: path,
+            _temp_dir: None,
+        })
+    }
+
+    #[test]
+    fn reads_slide_lines_styles_and_titles() -> Result<()> {
+        let title = shape(
+            r#"<p:ph type="title"/>"#,
+            "<a:p><a:r><a:t>Overview</a:t></a:r></a:p>",
+        );
+        let body = shape(
+            "",
+            r#"<a:p>
+              <a:r><a:rPr b="1" sz="2400"><a:latin typeface="Arial"/></a:rPr><a:t>Line one</a:t></a:r>
+              <a:br/>
+              <a:r><a:t>Line two</a:t></a:r>
+            </a:p>"#,
+        );
+        let end = shape("", "<a:p><a:r><a:t>End</a:t></a:r></a:p>");
+        let parser = parse_slides(&[vec![title, body], vec![end]], "")?;
+
+        assert_eq!(parser.page_count()?, 2);
+        assert_eq!((parser.page.width, parser.page.height), (720.0, 405.0));
+        let page = parser.analyze_page(0)?;
+        let texts: Vec<Option<String>> = page.blocks.iter().map(Block::text_content).collect();
+        assert_eq!(
+            texts,
+            vec![
+                Some("Overview".to_string()),
+                Some("Line one Line two".to_string()),
+            ]
+        );
+        let Block::TextBlock { bbox, lines, .. } = &page.blocks[1] else {
+            panic!("expected a text block");
+        };
+        assert_eq!(*bbox, BBox::new(10.0, 20.0, 110.0, 70.0));
+        // The soft break splits the paragraph into two lines.
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0].spans[0].style,
+            Some(TextStyle {
+                font: Some("Arial".to_string()),
+                size: Some(24.0),
+                bold: true,
+                italic: false,
+            })
+        );
+        assert_eq!(
+            parser.outline()?,
+            vec![OutlineEntry::new("Overview", 1, Some(0))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn links_runs_to_urls_and_slides() -> Result<()> {
+        let body = shape(
+            "",
+            r#"<a:p>
+              <a:r><a:t>See </a:t></a:r>
+              <a:r><a:rPr><a:hlinkClick r:id="rId1"/></a:rPr><a:t>site</a:t></a:r>
+              <a:r><a:rPr><a:hlinkClick r:id="rId2"/></a:rPr><a:t>next</a:t></a:r>
+            </a:p>"#,
+        );
+        let end = shape("", "<a:p><a:r><a:t>End</a:t></a:r></a:p>");
+        let parser = parse_slides(
+            &[vec![body], vec![end]],
+            r#"<Relationship Id="rId1" Target="https://example.com/" TargetMode="External"/>
+              <Relationship Id="rId2" Target="slide2.xml"/>"#,
+        )?;
+
+        let page = parser.analyze_page(0)?;
+        let Block::TextBlock { lines, .. } = &page.blocks[0] else {
+            panic!("expected a text block");
+        };
+        let links: Vec<Option<&LinkTarget>> = lines[0]
+            .spans
+            .iter()
+            .map(|span| span.link.as_ref())
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                None,
+                Some(&LinkTarget::Uri("https://example.com/".to_string())),
+                Some(&LinkTarget::Page(1)),
+            ]
+        );
+        Ok(())
+    }
+}
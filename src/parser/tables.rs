@@ -0,0 +1,602 @@
+//! Table detection from vector ruling lines and word positions.
+//!
+//! Digital PDFs draw table grids as stroked lines or thin filled rectangles.
+//! Rulings that cross each other form a grid whose distinct line positions
+//! are the row and column boundaries. Tables drawn with horizontal rules
+//! only (top, header and bottom rules) get their columns from the vertical
+//! gaps between the words that sit between the rules.
+//!
+//! Grid cells merge across a boundary wherever no ruling is drawn along
+//! that cell edge, which is how spanning headers and merged cells look.
+
+use crate::core::geometry::BBox;
+
+/// Rulings closer than this (points) are treated as the same line.
+const SNAP: f32 = 2.0;
+/// Shorter segments are glyph parts, underlines of short words or ticks.
+const MIN_RULING_LEN: f32 = 10.0;
+/// Rules-only tables need rules at least this long.
+const MIN_RULE_TABLE_WIDTH: f32 = 72.0;
+/// Consecutive rules of a rules-only table are at most this far apart
+/// (about a dozen body rows); farther rules are page or section dividers.
+const MAX_RULE_GAP: f32 = 180.0;
+/// Minimum blank horizontal run between words that separates two columns.
+const MIN_COLUMN_GAP: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+/// An axis-aligned line segment in displayed page space: `position` is the
+/// y of a horizontal ruling (x of a vertical one), `start..end` its extent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ruling {
+    pub orientation: Orientation,
+    pub position: f32,
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Ruling {
+    /// The ruling for a segment, if it is close enough to horizontal or
+    /// vertical.
+    pub fn from_segment((x0, y0): (f32, f32), (x1, y1): (f32, f32)) -> Option<Self> {
+        if (y1 - y0).abs() <= SNAP / 2.0 {
+            Some(Self {
+                orientation: Orientation::Horizontal,
+                position: (y0 + y1) / 2.0,
+                start: x0.min(x1),
+                end: x0.max(x1),
+            })
+        } else if (x1 - x0).abs() <= SNAP / 2.0 {
+            Some(Self {
+                orientation: Orientation::Vertical,
+                position: (x0 + x1) / 2.0,
+                start: y0.min(y1),
+                end: y0.max(y1),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The ruling a filled rectangle draws when it is thin enough to read as
+    /// a line.
+    pub fn from_filled_rect(bbox: &BBox) -> Option<Self> {
+        let (width, height) = (bbox.width(), bbox.height());
+        if height <= SNAP && width > height {
+            Some(Self {
+                orientation: Orientation::Horizontal,
+                position: (bbox.y0 + bbox.y1) / 2.0,
+                start: bbox.x0,
+                end: bbox.x1,
+            })
+        } else if width <= SNAP && height > width {
+            Some(Self {
+                orientation: Orientation::Vertical,
+                position: (bbox.x0 + bbox.x1) / 2.0,
+                start: bbox.y0,
+                end: bbox.y1,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn len(&self) -> f32 {
+        self.end - self.start
+    }
+
+    fn crosses(&self, other: &Ruling) -> bool {
+        self.orientation != other.orientation
+            && other.position >= self.start - SNAP
+            && other.position <= self.end + SNAP
+            && self.position >= other.start - SNAP
+            && self.position <= other.end + SNAP
+    }
+}
+
+/// A table found on the page: its extent and the row (y) and column (x)
+/// boundaries, both sorted and including the outer edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedTable {
+    pub bbox: BBox,
+    pub rows: Vec<f32>,
+    pub cols: Vec<f32>,
+    /// Cells in row-major order; every grid position is covered by exactly
+    /// one cell.
+    pub cells: Vec<CellSpan>,
+    /// Leading rows set apart as a header by the table's rules.
+    pub header_rows: usize,
+}
+
+/// A cell's top-left grid position and how many rows and columns it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellSpan {
+    pub row: usize,
+    pub col: usize,
+    pub rowspan: usize,
+    pub colspan: usize,
+}
+
+impl DetectedTable {
+    pub fn cell_bbox(&self, cell: &CellSpan) -> BBox {
+        BBox::new(
+            self.cols[cell.col],
+            self.rows[cell.row],
+            self.cols[cell.col + cell.colspan],
+            self.rows[cell.row + cell.rowspan],
+        )
+    }
+}
+
+/// Find ruled tables on a page. `words` are word boxes in the same space;
+/// a candidate grid is kept only when words fill at least two of its cells.
+pub fn detect_tables(rulings: &[Ruling], words: &[BBox]) -> Vec<DetectedTable> {
+    let rulings = merge_rulings(rulings);
+    let mut tables = Vec::new();
+    let mut used = vec![false; rulings.len()];
+
+    for component in connected_components(&rulings) {
+        let horizontals = component
+            .iter()
+            .filter(|&&idx| rulings[idx].orientation == Orientation::Horizontal)
+            .count();
+        if horizontals < 2 || component.len() - horizontals < 2 {
+            continue;
+        }
+        let members: Vec<Ruling> = component.iter().map(|&idx| rulings[idx]).collect();
+        if let Some(table) = grid_table(members, words) {
+            for &idx in &component {
+                used[idx] = true;
+            }
+            tables.push(table);
+        }
+    }
+
+    let free: Vec<Ruling> = rulings
+        .iter()
+        .zip(&used)
+        .filter(|(ruling, used)| !**used && ruling.orientation == Orientation::Horizontal)
+        .map(|(ruling, _)| *ruling)
+        .collect();
+    tables.extend(rule_tables(&free, words));
+    tables.sort_by(|a, b| a.bbox.y0.total_cmp(&b.bbox.y0));
+    tables
+}
+
+/// Join collinear rulings that touch or overlap, and drop short ones.
+fn merge_rulings(rulings: &[Ruling]) -> Vec<Ruling> {
+    let mut sorted = rulings.to_vec();
+    sorted.sort_by(|a, b| {
+        (a.orientation == Orientation::Vertical)
+            .cmp(&(b.orientation == Orientation::Vertical))
+            .then(a.position.total_cmp(&b.position))
+            .then(a.start.total_cmp(&b.start))
+    });
+
+    let mut merged: Vec<Ruling> = Vec::new();
+    for ruling in sorted {
+        let joined = merged.iter_mut().rev().find(|prev| {
+            prev.orientation == ruling.orientation
+                && (prev.position - ruling.position).abs() <= SNAP
+                && ruling.start <= prev.end + SNAP
+                && ruling.end >= prev.start - SNAP
+        });
+        match joined {
+            Some(prev) => {
+                prev.start = prev.start.min(ruling.start);
+                prev.end = prev.end.max(ruling.end);
+            }
+            None => merged.push(ruling),
+        }
+    }
+    merged.retain(|ruling| ruling.len() >= MIN_RULING_LEN);
+    merged
+}
+
+/// Groups of rulings linked by crossings.
+fn connected_components(rulings: &[Ruling]) -> Vec<Vec<usize>> {
+    let mut component = vec![usize::MAX; rulings.len()];
+    let mut components = Vec::new();
+    for seed in 0..rulings.len() {
+        if component[seed] != usize::MAX {
+            continue;
+        }
+        let id = components.len();
+        component[seed] = id;
+        let mut members = vec![seed];
+        let mut next = 0;
+        while next < members.len() {
+            let current = rulings[members[next]];
+            next += 1;
+            for (idx, other) in rulings.iter().enumerate() {
+                if component[idx] == usize::MAX && current.crosses(other) {
+                    component[idx] = id;
+                    members.push(idx);
+                }
+            }
+        }
+        components.push(members);
+    }
+    components
+}
+
+/// Sorted positions with values within [`SNAP`] of each other collapsed.
+fn cluster_positions(mut values: Vec<f32>) -> Vec<f32> {
+    values.sort_by(f32::total_cmp);
+    let mut clusters: Vec<f32> = Vec::new();
+    for value in values {
+        match clusters.last() {
+            Some(last) if value - last <= SNAP => {}
+            _ => clusters.push(value),
+        }
+    }
+    clusters
+}
+
+fn occupied_cells(rows: &[f32], cols: &[f32], words: &[BBox]) -> usize {
+    let mut cells = std::collections::HashSet::new();
+    for word in words {
+        let (cx, cy) = word.center();
+        let row = rows.windows(2).position(|w| cy >= w[0] && cy < w[1]);
+        let col = cols.windows(2).position(|w| cx >= w[0] && cx < w[1]);
+        if let (Some(row), Some(col)) = (row, col) {
+            cells.insert((row, col));
+        }
+    }
+    cells.len()
+}
+
+fn grid_table(rulings: Vec<Ruling>, words: &[BBox]) -> Option<DetectedTable> {
+    let positions = |orientation| {
+        cluster_positions(
+            rulings
+                .iter()
+                .filter(|ruling| ruling.orientation == orientation)
+                .map(|ruling| ruling.position)
+                .collect(),
+        )
+    };
+    let rows = positions(Orientation::Horizontal);
+    let cols = positions(Orientation::Vertical);
+    // A single framed box is a text box or callout, not a table.
+    if rows.len() < 2 || cols.len() < 2 || (rows.len() - 1) * (cols.len() - 1) < 2 {
+        return None;
+    }
+    if occupied_cells(&rows, &cols, words) < 2 {
+        return None;
+    }
+    let bbox = BBox::new(cols[0], rows[0], cols[cols.len() - 1], rows[rows.len() - 1]);
+    let cells = grid_cells(&rows, &cols, &rulings);
+    Some(DetectedTable {
+        bbox,
+        rows,
+        cols,
+        cells,
+        header_rows: 0,
+    })
+}
+
+/// Cells of a ruled grid. A cell grows right while no vertical ruling
+/// crosses the middle of its row at the next column boundary, then down
+/// while no horizontal ruling separates it from the row below.
+fn grid_cells(rows: &[f32], cols: &[f32], rulings: &[Ruling]) -> Vec<CellSpan> {
+    let ruled = |orientation: Orientation, position: f32, along: f32| {
+        rulings.iter().any(|ruling| {
+            ruling.orientation == orientation
+                && (ruling.position - position).abs() <= SNAP
+                && along >= ruling.start - SNAP
+                && along <= ruling.end + SNAP
+        })
+    };
+    let (row_count, col_count) = (rows.len() - 1, cols.len() - 1);
+    let mut covered = vec![false; row_count * col_count];
+    let mut cells = Vec::new();
+    for row in 0..row_count {
+        let mid_y = (rows[row] + rows[row + 1]) / 2.0;
+        for col in 0..col_count {
+            if covered[row * col_count + col] {
+                continue;
+            }
+            let mut colspan = 1;
+            while col + colspan < col_count
+                && !covered[row * col_count + col + colspan]
+                && !ruled(Orientation::Vertical, cols[col + colspan], mid_y)
+            {
+                colspan += 1;
+            }
+            let mut rowspan = 1;
+            while row + rowspan < row_count
+                && (col..col + colspan).all(|c| {
+                    let mid_x = (cols[c] + cols[c + 1]) / 2.0;
+                    !covered[(row + rowspan) * col_count + c]
+                        && !ruled(Orientation::Horizontal, rows[row + rowspan], mid_x)
+                })
+            {
+                rowspan += 1;
+            }
+            for r in row..row + rowspan {
+                for c in col..col + colspan {
+                    covered[r * col_count + c] = true;
+                }
+            }
+            cells.push(CellSpan {
+                row,
+                col,
+                rowspan,
+                colspan,
+            });
+        }
+    }
+    cells
+}
+
+/// Tables bounded by three or more horizontal rules of the same extent, no
+/// more than [`MAX_RULE_GAP`] apart, and no verticals. Rows are the text
+/// lines between the rules; columns come from blank runs shared by every
+/// row.
+fn rule_tables(horizontals: &[Ruling], words: &[BBox]) -> Vec<DetectedTable> {
+    let mut rules: Vec<Ruling> = horizontals
+        .iter()
+        .filter(|ruling| ruling.len() >= MIN_RULE_TABLE_WIDTH)
+        .copied()
+        .collect();
+    rules.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+    let mut tables = Vec::new();
+    let mut idx = 0;
+    while idx < rules.len() {
+        let first = rules[idx];
+        let mut group = vec![first];
+        for rule in &rules[idx + 1..] {
+            let aligned = (rule.start - first.start).abs() <= SNAP * 2.0
+                && (rule.end - first.end).abs() <= SNAP * 2.0;
+            if !aligned || rule.position - group[group.len() - 1].position > MAX_RULE_GAP {
+                break;
+            }
+            group.push(*rule);
+        }
+        idx += group.len();
+        if group.len() < 3 {
+            continue;
+        }
+        let bbox = BBox::new(
+            first.start,
+            first.position,
+            first.end,
+            group[group.len() - 1].position,
+        );
+        if let Some(table) = rule_table(bbox, &group, words) {
+            tables.push(table);
+        }
+    }
+    tables
+}
+
+fn rule_table(bbox: BBox, rules: &[Ruling], words: &[BBox]) -> Option<DetectedTable> {
+    let inside: Vec<BBox> = words
+        .iter()
+        .filter(|word| {
+            let (cx, cy) = word.center();
+            cx >= bbox.x0 && cx <= bbox.x1 && cy >= bbox.y0 && cy <= bbox.y1
+        })
+        .copied()
+        .collect();
+    if inside.is_empty() {
+        return None;
+    }
+
+    // Columns: blank runs in the projection of every word onto the x axis.
+    let mut spans: Vec<(f32, f32)> = inside.iter().map(|word| (word.x0, word.x1)).collect();
+    spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut cols = vec![bbox.x0];
+    let mut covered = spans[0].1;
+    for &(x0, x1) in &spans[1..] {
+        if x0 - covered >= MIN_COLUMN_GAP {
+            cols.push((covered + x0) / 2.0);
+        }
+        covered = covered.max(x1);
+    }
+    cols.push(bbox.x1);
+    if cols.len() < 3 {
+        return None;
+    }
+
+    // Rows: the rules plus the gaps between text lines within each band.
+    let mut rows: Vec<f32> = rules.iter().map(|rule| rule.position).collect();
+    for band in rules.windows(2) {
+        let mut lines: Vec<(f32, f32)> = Vec::new();
+        let mut band_words: Vec<&BBox> = inside
+            .iter()
+            .filter(|word| {
+                let (_, cy) = word.center();
+                cy > band[0].position && cy < band[1].position
+            })
+            .collect();
+        band_words.sort_by(|a, b| a.y0.total_cmp(&b.y0));
+        for word in band_words {
+            match lines.last_mut() {
+                Some(line) if word.y0 < line.1 - SNAP => line.1 = line.1.max(word.y1),
+                _ => lines.push((word.y0, word.y1)),
+            }
+        }
+        rows.extend(lines.windows(2).map(|pair| (pair[0].1 + pair[1].0) / 2.0));
+    }
+    let rows = cluster_positions(rows);
+    // The rows above the second rule are the header.
+    let header_rows = rows
+        .iter()
+        .filter(|&&row| row < rules[1].position - SNAP)
+        .count();
+    let cells = (0..rows.len() - 1)
+        .flat_map(|row| {
+            (0..cols.len() - 1).map(move |col| CellSpan {
+                row,
+                col,
+                rowspan: 1,
+                colspan: 1,
+            })
+        })
+        .collect();
+
+    Some(DetectedTable {
+        bbox,
+        rows,
+        cols,
+        cells,
+        header_rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn h(y: f32, x0: f32, x1: f32) -> Ruling {
+        Ruling::from_segment((x0, y), (x1, y)).unwrap()
+    }
+
+    fn v(x: f32, y0: f32, y1: f32) -> Ruling {
+        Ruling::from_segment((x, y0), (x, y1)).unwrap()
+    }
+
+    fn word(x0: f32, y0: f32) -> BBox {
+        BBox::new(x0, y0, x0 + 30.0, y0 + 10.0)
+    }
+
+    #[test]
+    fn finds_grid_from_crossing_rulings() {
+        // 2x3 grid; the top rule is drawn in two pieces.
+        let rulings = vec![
+            h(100.0, 50.0, 200.0),
+            h(100.5, 199.0, 350.0),
+            h(130.0, 50.0, 350.0),
+            h(160.0, 50.0, 350.0),
+            v(50.0, 100.0, 160.0),
+            v(150.0, 100.0, 160.0),
+            v(250.0, 100.0, 160.0),
+            v(350.0, 100.0, 160.0),
+            // An unrelated underline elsewhere on the page.
+            h(400.0, 60.0, 120.0),
+        ];
+        let words = vec![word(60.0, 110.0), word(160.0, 110.0), word(60.0, 140.0)];
+
+        let tables = detect_tables(&rulings, &words);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].bbox, BBox::new(50.0, 100.0, 350.0, 160.0));
+        assert_eq!(tables[0].rows, vec![100.0, 130.0, 160.0]);
+        assert_eq!(tables[0].cols, vec![50.0, 150.0, 250.0, 350.0]);
+        assert_eq!(tables[0].cells.len(), 6);
+        assert!(tables[0]
+            .cells
+            .iter()
+            .all(|cell| cell.rowspan == 1 && cell.colspan == 1));
+    }
+
+    #[test]
+    fn merges_cells_across_missing_rulings() {
+        // 3x3 grid: the first row spans all columns, and the first column's
+        // lower two cells are one.
+        let rulings = vec![
+            h(100.0, 50.0, 350.0),
+            h(130.0, 50.0, 350.0),
+            h(160.0, 150.0, 350.0),
+            h(190.0, 50.0, 350.0),
+            v(50.0, 100.0, 190.0),
+            v(150.0, 130.0, 190.0),
+            v(250.0, 130.0, 190.0),
+            v(350.0, 100.0, 190.0),
+        ];
+        let words = vec![word(60.0, 110.0), word(60.0, 140.0), word(160.0, 140.0)];
+
+        let tables = detect_tables(&rulings, &words);
+        assert_eq!(tables.len(), 1);
+        let spans: Vec<(usize, usize, usize, usize)> = tables[0]
+            .cells
+            .iter()
+            .map(|cell| (cell.row, cell.col, cell.rowspan, cell.colspan))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 0, 1, 3),
+                (1, 0, 2, 1),
+                (1, 1, 1, 1),
+                (1, 2, 1, 1),
+                (2, 1, 1, 1),
+                (2, 2, 1, 1),
+            ]
+        );
+        assert_eq!(
+            tables[0].cell_bbox(&tables[0].cells[1]),
+            BBox::new(50.0, 130.0, 150.0, 190.0)
+        );
+    }
+
+    #[test]
+    fn ignores_framed_text_box() {
+        let rulings = vec![
+            h(100.0, 50.0, 350.0),
+            h(200.0, 50.0, 350.0),
+            v(50.0, 100.0, 200.0),
+            v(350.0, 100.0, 200.0),
+        ];
+        let words = vec![word(60.0, 110.0), word(160.0, 110.0)];
+        assert!(detect_tables(&rulings, &words).is_empty());
+    }
+
+    #[test]
+    fn infers_columns_between_horizontal_rules() {
+        let rulings = vec![
+            h(100.0, 50.0, 350.0),
+            h(120.0, 50.0, 350.0),
+            h(170.0, 50.0, 350.0),
+        ];
+        let words = vec![
+            word(60.0, 105.0),
+            word(200.0, 105.0),
+            word(60.0, 125.0),
+            word(200.0, 125.0),
+            word(60.0, 150.0),
+            word(200.0, 150.0),
+        ];
+
+        let tables = detect_tables(&rulings, &words);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].cols, vec![50.0, 145.0, 350.0]);
+        assert_eq!(tables[0].rows, vec![100.0, 120.0, 142.5, 170.0]);
+        assert_eq!(tables[0].header_rows, 1);
+        assert_eq!(tables[0].cells.len(), 6);
+    }
+
+    #[test]
+    fn ignores_page_dividers_around_two_columns() {
+        // Header, section and footer rules spanning a two-column page.
+        let rulings = vec![
+            h(60.0, 50.0, 550.0),
+            h(300.0, 50.0, 550.0),
+            h(740.0, 50.0, 550.0),
+        ];
+        let words: Vec<BBox> = (0..20)
+            .flat_map(|line| {
+                let y = 80.0 + 30.0 * line as f32;
+                [word(60.0, y), word(320.0, y)]
+            })
+            .collect();
+        assert!(detect_tables(&rulings, &words).is_empty());
+    }
+
+    #[test]
+    fn thin_filled_rectangles_read_as_rulings() {
+        let ruling = Ruling::from_filled_rect(&BBox::new(50.0, 99.5, 350.0, 100.5)).unwrap();
+        assert_eq!(ruling.orientation, Orientation::Horizontal);
+        assert_eq!(
+            (ruling.position, ruling.start, ruling.end),
+            (100.0, 50.0, 350.0)
+        );
+        assert!(Ruling::from_filled_rect(&BBox::new(0.0, 0.0, 20.0, 20.0)).is_none());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::core::geometry::BBox;
+use crate::core::model::TextStyle;
 use crate::parser::hangul::combine_hangul;
+use crate::parser::poppler;
 use std::path::Path;
 use std::process::Command;
 
@@ -7,6 +9,30 @@ use std::process::Command;
 pub struct GlyphRun {
     pub text: String,
     pub bbox: BBox,
+    /// Font of the run, when the extractor knows it (poppler does not).
+    pub style: Option<TextStyle>,
+}
+
+/// A line of words as laid out by `pdftotext -bbox-layout`.
+#[derive(Debug, Clone)]
+pub struct TextLineLayout {
+    pub bbox: BBox,
+    pub words: Vec<GlyphRun>,
+}
+
+/// A paragraph-like block of lines as laid out by `pdftotext -bbox-layout`.
+#[derive(Debug, Clone)]
+pub struct TextBlockLayout {
+    pub bbox: BBox,
+    pub lines: Vec<TextLineLayout>,
+}
+
+/// Word/line/block geometry of one page, in PDF points with a top-left origin.
+#[derive(Debug, Clone)]
+pub struct PageTextLayout {
+    pub width: f32,
+    pub height: f32,
+    pub blocks: Vec<TextBlockLayout>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -16,9 +42,15 @@ enum PdfToTextMode {
     Layout,
 }
 
-fn run_pdftotext(pdf_path: &Path, page_number: usize, mode: PdfToTextMode) -> Option<String> {
+fn run_pdftotext(
+    pdf_path: &Path,
+    password: Option<&str>,
+    page_number: usize,
+    mode: PdfToTextMode,
+) -> Option<String> {
     let mut cmd = Command::new("pdftotext");
-    cmd.arg("-f")
+    poppler::password_args(&mut cmd, password)
+        .arg("-f")
         .arg(page_number.to_string())
         .arg("-l")
         .arg(page_number.to_string())
@@ -107,6 +139,18 @@ fn is_degraded_korean_text(text: &str) -> bool {
     total >= 8 && jamos > syllables * 2
 }
 
+fn is_jamo_noise_line(line: &str) -> bool {
+    let (syllables, jamos) = korean_counts(line);
+    let has_korean = syllables + jamos > 0;
+    let mostly_jamo = jamos >= 2 && syllables == 0;
+    let heavy_jamo_noise = has_korean && jamos >= syllables * 3 && jamos >= 4;
+    mostly_jamo || heavy_jamo_noise
+}
+
+fn is_low_quality_korean_page(text: &str) -> bool {
+    has_korean_chars(text) && (hangul_quality_score(text) < -10 || is_degraded_korean_text(text))
+}
+
 fn clean_decomposed_korean_lines(text: &str) -> String {
     let mut kept = Vec::new();
 
@@ -117,12 +161,7 @@ fn clean_decomposed_korean_lines(text: &str) -> String {
             continue;
         }
 
-        let (syllables, jamos) = korean_counts(trimmed);
-        let has_korean = syllables + jamos > 0;
-        let mostly_jamo = jamos >= 2 && syllables == 0;
-        let heavy_jamo_noise = has_korean && jamos >= syllables * 3 && jamos >= 4;
-
-        if mostly_jamo || heavy_jamo_noise {
+        if is_jamo_noise_line(trimmed) {
             continue;
         }
 
@@ -146,7 +185,13 @@ fn clean_decomposed_korean_lines(text: &str) -> String {
     out
 }
 
-pub fn extract_glyph_runs(pdf_path: &Path, page_idx: usize) -> Vec<GlyphRun> {
+pub fn extract_glyph_runs(
+    pdf_path: &Path,
+    password: Option<&str>,
+    page_idx: usize,
+    page_width: f32,
+    page_height: f32,
+) -> Vec<GlyphRun> {
     // Use pdftotext (from poppler-utils) to extract plain text for a single page.
     // This is a coarse approximation: we treat all text on the page as one run
     // and assign it a page-wide bounding box.
@@ -159,7 +204,7 @@ pub fn extract_glyph_runs(pdf_path: &Path, page_idx: usize) -> Vec<GlyphRun> {
     ];
     let best_text = modes
         .iter()
-        .filter_map(|mode| run_pdftotext(pdf_path, page_number, *mode))
+        .filter_map(|mode| run_pdftotext(pdf_path, password, page_number, *mode))
         .map(|raw| {
             // Combine separated Hangul jamos into complete syllables while preserving normal spacing.
             let combined = combine_hangul(&raw);
@@ -191,9 +236,7 @@ pub fn extract_glyph_runs(pdf_path: &Path, page_idx: usize) -> Vec<GlyphRun> {
 
     // If Korean text remains heavily decomposed after normalization/composition,
     // parser output is likely unreliable for this page. Let OCR dominate instead.
-    if has_korean_chars(&text)
-        && (hangul_quality_score(&text) < -10 || is_degraded_korean_text(&text))
-    {
+    if is_low_quality_korean_page(&text) {
         eprintln!(
             "parser text quality is too low for Korean on page {}. falling back to OCR track",
             page_number
@@ -201,10 +244,254 @@ pub fn extract_glyph_runs(pdf_path: &Path, page_idx: usize) -> Vec<GlyphRun> {
         return Vec::new();
     }
 
-    // For now, approximate the page as a fixed-size box; this matches the
-    // default dimensions used elsewhere in the parser pipeline.
+    // Plain-text modes carry no positions: the run covers the whole page.
     // Multi-column layout is handled by OCR's column detection.
-    let bbox = BBox::new(0.0, 0.0, 1000.0, 1400.0);
+    let bbox = BBox::new(0.0, 0.0, page_width, page_height);
+
+    vec![GlyphRun {
+        text,
+        bbox,
+        style: None,
+    }]
+}
+
+/// Extract word, line and block geometry for a single page using
+/// `pdftotext -bbox-layout`.
+///
+/// Returns `None` when poppler is unavailable, the page has no text, or the
+/// Korean text is too degraded to trust; callers should then fall back to
+/// [`extract_glyph_runs`], which tries several plain-text modes.
+pub fn extract_page_layout(
+    pdf_path: &Path,
+    password: Option<&str>,
+    page_idx: usize,
+) -> Option<PageTextLayout> {
+    let page_number = page_idx + 1;
+    let output = poppler::password_args(&mut Command::new("pdftotext"), password)
+        .arg("-f")
+        .arg(page_number.to_string())
+        .arg("-l")
+        .arg(page_number.to_string())
+        .arg("-bbox-layout")
+        .arg(pdf_path)
+        .arg("-")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let xhtml = String::from_utf8_lossy(&output.stdout);
+    let layout = parse_bbox_layout(&xhtml)?;
+    if layout.blocks.is_empty() {
+        return None;
+    }
+
+    let layout = normalize_page_layout(layout);
+    if layout.is_none() {
+        eprintln!(
+            "parser word layout quality is too low for Korean on page {}",
+            page_number
+        );
+    }
+    layout
+}
+
+/// Apply the Korean cleanup shared by every word-layout source: compose
+/// Hangul per word, drop lines that are mostly decomposed jamo, and reject
+/// the page entirely when what is left is still degraded.
+///
+/// Returns `None` for pages without text as well.
+pub fn normalize_page_layout(mut layout: PageTextLayout) -> Option<PageTextLayout> {
+    for block in &mut layout.blocks {
+        for line in &mut block.lines {
+            for word in &mut line.words {
+                word.text = combine_hangul(&word.text);
+            }
+        }
+        block
+            .lines
+            .retain(|line| !line.words.is_empty() && !is_jamo_noise_line(&line_text(line)));
+    }
+    layout.blocks.retain(|block| !block.lines.is_empty());
+    if layout.blocks.is_empty() {
+        return None;
+    }
+
+    let page_text = layout
+        .blocks
+        .iter()
+        .flat_map(|block| block.lines.iter())
+        .map(line_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if is_low_quality_korean_page(&page_text) {
+        return None;
+    }
+
+    Some(layout)
+}
+
+/// Parse the XHTML produced by `pdftotext -bbox-layout` for its first page.
+///
+/// Words are Hangul-composed individually and lines that are mostly
+/// decomposed jamo are dropped, mirroring the plain-text cleanup.
+pub fn parse_bbox_layout(xhtml: &str) -> Option<PageTextLayout> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = roxmltree::Document::parse_with_options(xhtml, options).ok()?;
+    let page = doc
+        .descendants()
+        .find(|node| node.tag_name().name() == "page")?;
+
+    let width = attr_f32(&page, "width")?;
+    let height = attr_f32(&page, "height")?;
+
+    let mut blocks = Vec::new();
+    for block_node in page
+        .descendants()
+        .filter(|n| n.tag_name().name() == "block")
+    {
+        let mut lines = Vec::new();
+        for line_node in block_node
+            .children()
+            .filter(|n| n.tag_name().name() == "line")
+        {
+            let words: Vec<GlyphRun> = line_node
+                .children()
+                .filter(|n| n.tag_name().name() == "word")
+                .filter_map(|word_node| {
+                    let text = combine_hangul(word_node.text().unwrap_or_default().trim());
+                    if text.is_empty() {
+                        return None;
+                    }
+                    Some(GlyphRun {
+                        text,
+                        bbox: node_bbox(&word_node)?,
+                        style: None,
+                    })
+                })
+                .collect();
+            if words.is_empty() {
+                continue;
+            }
+
+            let line = TextLineLayout {
+                bbox: node_bbox(&line_node).unwrap_or_else(|| union_bbox(&words)),
+                words,
+            };
+            if is_jamo_noise_line(&line_text(&line)) {
+                continue;
+            }
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            continue;
+        }
+
+        let bbox = node_bbox(&block_node).unwrap_or_else(|| {
+            lines
+                .iter()
+                .skip(1)
+                .fold(lines[0].bbox, |acc, line| acc.union(&line.bbox))
+        });
+        blocks.push(TextBlockLayout { bbox, lines });
+    }
+
+    Some(PageTextLayout {
+        width,
+        height,
+        blocks,
+    })
+}
+
+/// Plain text of a laid-out line, with words separated by single spaces.
+pub fn line_text(line: &TextLineLayout) -> String {
+    line.words
+        .iter()
+        .map(|word| word.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    vec![GlyphRun { text, bbox }]
+fn attr_f32(node: &roxmltree::Node, name: &str) -> Option<f32> {
+    node.attribute(name)?.trim().parse().ok()
+}
+
+fn node_bbox(node: &roxmltree::Node) -> Option<BBox> {
+    Some(BBox::new(
+        attr_f32(node, "xMin")?,
+        attr_f32(node, "yMin")?,
+        attr_f32(node, "xMax")?,
+        attr_f32(node, "yMax")?,
+    ))
+}
+
+fn union_bbox(runs: &[GlyphRun]) -> BBox {
+    runs.iter()
+        .skip(1)
+        .fold(runs[0].bbox, |acc, run| acc.union(&run.bbox))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN"
+"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head>
+<title></title>
+</head>
+<body>
+<doc>
+  <page width="612.000000" height="792.000000">
+    <flow>
+      <block xMin="72.000000" yMin="70.000000" xMax="300.000000" yMax="98.000000">
+        <line xMin="72.000000" yMin="70.000000" xMax="300.000000" yMax="82.000000">
+          <word xMin="72.000000" yMin="70.000000" xMax="110.000000" yMax="82.000000">Hello</word>
+          <word xMin="114.000000" yMin="70.000000" xMax="150.000000" yMax="82.000000">world</word>
+        </line>
+        <line xMin="72.000000" yMin="86.000000" xMax="200.000000" yMax="98.000000">
+          <word xMin="72.000000" yMin="86.000000" xMax="200.000000" yMax="98.000000">&amp;more</word>
+        </line>
+      </block>
+    </flow>
+    <flow>
+      <block xMin="320.000000" yMin="70.000000" xMax="540.000000" yMax="82.000000">
+        <line xMin="320.000000" yMin="70.000000" xMax="540.000000" yMax="82.000000">
+          <word xMin="320.000000" yMin="70.000000" xMax="360.000000" yMax="82.000000">ㄱ</word>
+          <word xMin="362.000000" yMin="70.000000" xMax="400.000000" yMax="82.000000">ㅏ</word>
+        </line>
+      </block>
+    </flow>
+  </page>
+</doc>
+</body>
+</html>
+"#;
+
+    #[test]
+    fn parses_word_line_and_block_geometry() {
+        let layout = parse_bbox_layout(SAMPLE).expect("layout should parse");
+        assert_eq!(layout.width, 612.0);
+        assert_eq!(layout.height, 792.0);
+
+        // The second block is pure jamo noise and is dropped.
+        assert_eq!(layout.blocks.len(), 1);
+        let block = &layout.blocks[0];
+        assert_eq!(block.bbox, BBox::new(72.0, 70.0, 300.0, 98.0));
+        assert_eq!(block.lines.len(), 2);
+        assert_eq!(line_text(&block.lines[0]), "Hello world");
+        assert_eq!(block.lines[0].words[1].bbox.x0, 114.0);
+        assert_eq!(block.lines[1].words[0].text, "&more");
+    }
+
+    #[test]
+    fn rejects_non_layout_input() {
+        assert!(parse_bbox_layout("not xml").is_none());
+    }
 }
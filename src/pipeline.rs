@@ -2,69 +2,185 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
-use crate::core::model::{DocumentFinal, PageDebug, PageFinal, PageHypothesis};
+use crate::core::geometry::BBox;
+use crate::core::model::{
+    Block, DocumentFinal, OutlineEntry, PageDebug, PageFinal, PageGeometry, PageHypothesis,
+    PageLink, PageSize, Provenance, RevisionMode,
+};
+use crate::core::policy::FusionPolicy;
 use crate::export::html_debug_export::HtmlDebugExporter;
 use crate::export::json_export::JsonExporter;
 use crate::export::markdown_export::MarkdownExporter;
 use crate::export::text_export::TextExporter;
-use crate::export::Exporter;
-use crate::fusion::{FusionEngine, SimpleFusionEngine};
+use crate::export::{ExportOptions, Exporter};
+use crate::fusion::{FusionEngine, SimpleFusionEngine, Track};
 use crate::ocr::{
-    bridge::OcrBridge, layout_builder::OcrLayoutBuilder, renderer::PageRenderer, OcrTrack,
+    bridge::OcrBridge, layout_builder::OcrLayoutBuilder, pixels, renderer::PageRenderer, OcrTrack,
 };
-use crate::parser::{layout_builder::ParserLayoutBuilder, ParserTrack};
+use crate::parser::{layout_builder::ParserLayoutBuilder, PageImage, ParserTrack};
 
 #[derive(Debug, Clone)]
 pub struct PipelineConfig {
     pub input: PathBuf,
     pub output: PathBuf,
     pub dpi: u32,
+    /// Owner or user password for encrypted PDFs.
+    pub password: Option<String>,
+    /// Fusion thresholds; see [`FusionPolicy::load`] for policy files.
+    pub policy: FusionPolicy,
+    /// How DOCX tracked changes are read.
+    pub revisions: RevisionMode,
 }
 
 impl PipelineConfig {
     pub fn new(input: PathBuf, output: PathBuf, dpi: u32) -> Self {
-        Self { input, output, dpi }
+        Self {
+            input,
+            output,
+            dpi,
+            password: None,
+            policy: FusionPolicy::default(),
+            revisions: RevisionMode::default(),
+        }
+    }
+
+    pub fn with_password(mut self, password: Option<String>) -> Self {
+        self.password = password;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: FusionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_revisions(mut self, revisions: RevisionMode) -> Self {
+        self.revisions = revisions;
+        self
     }
 }
 
 pub fn build_document(config: &PipelineConfig) -> Result<DocumentFinal> {
-    let parser_track = ParserLayoutBuilder::new(config.input.clone())?;
+    let parser_track = ParserLayoutBuilder::with_options(
+        config.input.clone(),
+        config.password.clone(),
+        config.revisions,
+    )?;
     let page_count = parser_track.page_count()?;
 
-    let renderer = PageRenderer::new(config.output.join("debug"), config.dpi);
+    let renderer = PageRenderer::new(config.output.join("debug"), config.dpi)
+        .with_password(config.password.clone());
     let bridge = OcrBridge::new(config.output.join("ocr"));
     let ocr_track = OcrLayoutBuilder::new(bridge);
-    let fusion = SimpleFusionEngine::new();
+    let fusion = SimpleFusionEngine::new()
+        .with_policy(config.policy.clone())
+        .with_dpi(config.dpi);
 
     let mut pages: Vec<PageFinal> = Vec::with_capacity(page_count);
 
     for page_idx in 0..page_count {
-        let parser_hypo = parser_track.analyze_page(page_idx)?;
-        let ocr_hypo = if parser_track.supports_ocr_rendering() {
+        let page_size = parser_track.page_size(page_idx)?;
+        let mut parser_hypo = parser_track.analyze_page(page_idx)?;
+        let images = parser_track.page_images(page_idx).unwrap_or_else(|err| {
+            eprintln!("Warning: {err:#}");
+            Vec::new()
+        });
+        let image_boxes: Vec<BBox> = images.iter().map(|image| image.bbox).collect();
+        let image_coverage =
+            BBox::new(0.0, 0.0, page_size.width, page_size.height).coverage_by(&image_boxes);
+        if let Err(err) = add_parser_figures(
+            &mut parser_hypo,
+            images,
+            page_idx,
+            &page_size,
+            &config.output,
+        ) {
+            eprintln!("Warning: {err:#}");
+        }
+        let (geometry, ocr_hypo, rendered_path) = if parser_track.supports_ocr_rendering() {
             let rendered = renderer.render_page(
                 parser_track
                     .rendering_source_path()
                     .ok_or_else(|| anyhow::anyhow!("missing rendering source path"))?,
                 page_idx,
             )?;
-            ocr_track.analyze_page(&rendered.path, page_idx)?
+            let geometry =
+                PageGeometry::new(page_size, rendered.width, rendered.height, config.dpi);
+            let ocr_hypo = ocr_track.analyze_page(&rendered.path, page_idx)?;
+            (geometry, ocr_hypo, Some(rendered.path))
         } else {
-            PageHypothesis {
+            let geometry = PageGeometry::unrendered(page_size, config.dpi);
+            let ocr_hypo = PageHypothesis {
                 page_idx,
                 blocks: vec![],
-                width: 1000,
-                height: 1400,
-            }
+                width: geometry.image_width,
+                height: geometry.image_height,
+            };
+            (geometry, ocr_hypo, None)
         };
-        let mut fused = fusion.fuse(&parser_hypo, &ocr_hypo)?;
-        attach_debug_info(&mut fused, &parser_hypo, &ocr_hypo);
+        // Fusion works in rendered-image pixels, where OCR boxes live.
+        let mut parser_hypo = parser_hypo.transformed(
+            &geometry.page_to_image,
+            geometry.image_width,
+            geometry.image_height,
+        );
+        // An invisible OCR text layer is not the page's own text: it is a
+        // track of its own, trusted below our OCR.
+        let text_layer = parser_hypo.take_source(Provenance::TextLayer);
+        let mut tracks = vec![Track::parser(&parser_hypo), Track::ocr(&ocr_hypo)];
+        if !text_layer.blocks.is_empty() {
+            tracks.push(Track::text_layer(&text_layer));
+        }
+        let pixels = rendered_path.and_then(|path| {
+            let parser_text: Vec<BBox> = parser_hypo
+                .blocks
+                .iter()
+                .filter_map(|block| match block {
+                    Block::TextBlock { bbox, .. } => Some(*bbox),
+                    _ => None,
+                })
+                .collect();
+            pixels::measure_page(&path, &parser_text, image_coverage)
+                .map_err(|err| eprintln!("Warning: {err:#}"))
+                .ok()
+        });
+        let mut fused = fusion.fuse_page(&tracks, pixels.as_ref())?;
+        attach_page_links(&mut fused, &parser_track, page_idx, &geometry);
+        fused.geometry = Some(geometry);
+        attach_debug_info(&mut fused, &parser_hypo, &ocr_hypo, &text_layer);
         pages.push(fused);
     }
 
-    Ok(DocumentFinal { pages })
+    // Outline and metadata are optional: a document without readable
+    // bookmarks or properties still converts.
+    let mut outline = parser_track.outline().unwrap_or_else(|err| {
+        eprintln!("Warning: {err:#}");
+        Vec::new()
+    });
+    resolve_outline_targets(&mut outline, &pages);
+    let metadata = parser_track.metadata().unwrap_or_else(|err| {
+        eprintln!("Warning: {err:#}");
+        Default::default()
+    });
+
+    Ok(DocumentFinal {
+        pages,
+        outline,
+        metadata,
+    })
 }
 
 pub fn export_document(document: &DocumentFinal, output: &Path) -> Result<()> {
+    export_document_with(document, output, &ExportOptions::default())
+}
+
+/// Export with notes or headers and footers left out as `options` say.
+pub fn export_document_with(
+    document: &DocumentFinal,
+    output: &Path,
+    options: &ExportOptions,
+) -> Result<()> {
+    let document = &options.apply(document);
     let json_exporter = JsonExporter::new(output.to_path_buf());
     json_exporter.export(document)?;
 
@@ -80,11 +196,137 @@ pub fn export_document(document: &DocumentFinal, output: &Path) -> Result<()> {
     Ok(())
 }
 
-fn attach_debug_info(fused: &mut PageFinal, parser: &PageHypothesis, ocr: &PageHypothesis) {
-    fused.debug = Some(PageDebug {
-        parser_blocks: parser.blocks.clone(),
-        ocr_blocks: ocr.blocks.clone(),
-    });
+/// Save the parser track's embedded images under `figures/` and add them to
+/// its hypothesis as figure blocks. Near page-sized images are scans, left to
+/// OCR; tiny ones are rules, bullets and other decoration.
+fn add_parser_figures(
+    hypo: &mut PageHypothesis,
+    images: Vec<PageImage>,
+    page_idx: usize,
+    page_size: &PageSize,
+    output: &Path,
+) -> Result<()> {
+    const MIN_SIDE_PT: f32 = 8.0;
+    const MAX_PAGE_COVERAGE: f32 = 0.9;
+
+    let page_area = (page_size.width * page_size.height).max(1.0);
+    let figure_dir = output.join("figures");
+    let mut saved = 0;
+    for image in images {
+        let bbox = image.bbox;
+        if bbox.width() < MIN_SIDE_PT
+            || bbox.height() < MIN_SIDE_PT
+            || bbox.area() / page_area > MAX_PAGE_COVERAGE
+        {
+            continue;
+        }
+        saved += 1;
+        let name = format!(
+            "page_{:03}_image_{:02}.{}",
+            page_idx + 1,
+            saved,
+            image.extension
+        );
+        std::fs::create_dir_all(&figure_dir)?;
+        std::fs::write(figure_dir.join(&name), &image.data)?;
+        let path = format!("figures/{name}");
+
+        // A tagged figure around the image keeps its place in the reading
+        // order and takes the image.
+        let (cx, cy) = bbox.center();
+        let tagged = hypo.blocks.iter_mut().find_map(|block| match block {
+            Block::FigureBlock {
+                bbox: figure,
+                image: slot @ None,
+                ..
+            } if cx >= figure.x0 && cx <= figure.x1 && cy >= figure.y0 && cy <= figure.y1 => {
+                Some(slot)
+            }
+            _ => None,
+        });
+        match tagged {
+            Some(slot) => *slot = Some(path),
+            None => hypo.blocks.push(Block::FigureBlock {
+                bbox,
+                confidence: 0.6,
+                source: Provenance::Parser,
+                image: Some(path),
+                order: None,
+                tracks: Vec::new(),
+                debug: None,
+            }),
+        }
+    }
+    Ok(())
+}
+
+/// Attach the page's link areas to the final spans they cover. Links are
+/// matched after fusion so they survive whichever track's text was kept.
+fn attach_page_links(
+    fused: &mut PageFinal,
+    parser_track: &impl ParserTrack,
+    page_idx: usize,
+    geometry: &PageGeometry,
+) {
+    let links: Vec<PageLink> = match parser_track.page_links(page_idx) {
+        Ok(links) => links
+            .into_iter()
+            .map(|link| PageLink {
+                bbox: geometry.page_to_image.apply_bbox(&link.bbox),
+                target: link.target,
+            })
+            .collect(),
+        Err(err) => {
+            eprintln!("Warning: {err:#}");
+            return;
+        }
+    };
+    if links.is_empty() {
+        return;
+    }
+    for block in &mut fused.blocks {
+        block.attach_links(&links);
+    }
+}
+
+/// Point each outline entry at the first block on its target page whose
+/// text contains the entry title, ignoring whitespace and case.
+fn resolve_outline_targets(outline: &mut [OutlineEntry], pages: &[PageFinal]) {
+    fn normalize(text: &str) -> String {
+        text.chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+
+    for entry in outline {
+        let title = normalize(&entry.title);
+        let page = entry
+            .page_idx
+            .and_then(|page_idx| pages.iter().find(|page| page.page_idx == page_idx));
+        if let (false, Some(page)) = (title.is_empty(), page) {
+            entry.block_idx = page.blocks.iter().position(|block| {
+                matches!(block, Block::TextBlock { .. })
+                    && block
+                        .text_content()
+                        .is_some_and(|text| normalize(&text).contains(&title))
+            });
+        }
+        resolve_outline_targets(&mut entry.children, pages);
+    }
+}
+
+fn attach_debug_info(
+    fused: &mut PageFinal,
+    parser: &PageHypothesis,
+    ocr: &PageHypothesis,
+    text_layer: &PageHypothesis,
+) {
+    // Fusion has already recorded its decisions here.
+    let debug = fused.debug.get_or_insert_with(PageDebug::default);
+    debug.parser_blocks = parser.blocks.clone();
+    debug.ocr_blocks = ocr.blocks.clone();
+    debug.text_layer_blocks = text_layer.blocks.clone();
 }
 
 #[cfg(test)]
@@ -94,7 +336,7 @@ mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
 
     use crate::core::geometry::BBox;
-    use crate::core::model::{Block, Line, PageClass, PageHypothesis, Provenance, Span};
+    use crate::core::model::{Line, PageClass, Provenance, Span};
 
     fn temp_output_dir(prefix: &str) -> PathBuf {
         let mut out = std::env::temp_dir();
@@ -117,10 +359,15 @@ mod tests {
                     bbox,
                     source,
                     style: None,
+                    link: None,
+                    revision: None,
                 }],
             }],
             confidence: 0.5,
             source,
+            role: None,
+            order: None,
+            tracks: Vec::new(),
             debug: None,
         }
     }
@@ -145,14 +392,48 @@ mod tests {
             blocks: vec![],
             width: 100,
             height: 100,
+            geometry: None,
             debug: None,
         };
 
-        attach_debug_info(&mut fused, &parser, &ocr);
+        let text_layer = PageHypothesis {
+            page_idx: 0,
+            blocks: vec![text_block("layer", Provenance::TextLayer)],
+            width: 100,
+            height: 100,
+        };
+
+        attach_debug_info(&mut fused, &parser, &ocr, &text_layer);
 
         let debug = fused.debug.expect("debug info should be set");
         assert_eq!(debug.parser_blocks.len(), 1);
         assert_eq!(debug.ocr_blocks.len(), 1);
+        assert_eq!(debug.text_layer_blocks.len(), 1);
+    }
+
+    #[test]
+    fn outline_entries_point_at_matching_blocks() {
+        let page = PageFinal {
+            page_idx: 1,
+            class: PageClass::Digital,
+            blocks: vec![
+                text_block("Body text", Provenance::Parser),
+                text_block("1. 추진  배경", Provenance::Parser),
+            ],
+            width: 100,
+            height: 100,
+            geometry: None,
+            debug: None,
+        };
+        let mut outline = vec![OutlineEntry::new("Overview", 1, Some(1))];
+        outline[0]
+            .children
+            .push(OutlineEntry::new("1. 추진 배경", 2, Some(1)));
+
+        resolve_outline_targets(&mut outline, &[page]);
+
+        assert_eq!(outline[0].block_idx, None);
+        assert_eq!(outline[0].children[0].block_idx, Some(1));
     }
 
     #[test]
@@ -167,8 +448,11 @@ mod tests {
                 blocks: vec![],
                 width: 100,
                 height: 100,
+                geometry: None,
                 debug: None,
             }],
+            outline: vec![],
+            metadata: Default::default(),
         };
 
         export_document(&document, &output)?;
@@ -6,7 +6,8 @@ use anyhow::Result;
 
 use docstruct::core::geometry::BBox;
 use docstruct::core::model::{
-    Block, DocumentFinal, Line, PageDebug, PageFinal, PageHypothesis, Provenance, Span,
+    Block, DocumentFinal, Line, PageDebug, PageFinal, PageGeometry, PageHypothesis, Provenance,
+    Span,
 };
 use docstruct::export::{Exporter, HtmlDebugExporter, JsonExporter};
 use docstruct::fusion::{FusionEngine, SimpleFusionEngine};
@@ -36,10 +37,15 @@ fn test_fusion_with_synthetic_data() -> Result<()> {
                 bbox: p_bbox,
                 source: Provenance::Parser,
                 style: None,
+                link: None,
+                revision: None,
             }],
         }],
         confidence: 0.6,
         source: Provenance::Parser,
+        role: None,
+        order: None,
+        tracks: Vec::new(),
         debug: None,
     };
 
@@ -60,10 +66,15 @@ fn test_fusion_with_synthetic_data() -> Result<()> {
                 bbox: o_bbox,
                 source: Provenance::Ocr,
                 style: None,
+                link: None,
+                revision: None,
             }],
         }],
         confidence: 0.5,
         source: Provenance::Ocr,
+        role: None,
+        order: None,
+        tracks: Vec::new(),
         debug: None,
     };
 
@@ -98,7 +109,11 @@ fn test_fusion_with_synthetic_data() -> Result<()> {
     let pid = std::process::id();
     out.push(format!("docstruct-test-{}-{}", pid, now));
     let exporter = JsonExporter::new(out.clone());
-    exporter.export(&DocumentFinal { pages: vec![fused] })?;
+    exporter.export(&DocumentFinal {
+        pages: vec![fused],
+        outline: vec![],
+        metadata: Default::default(),
+    })?;
 
     // Verify exported JSON contains the text
     let file = out.join("document.json");
@@ -197,17 +212,31 @@ fn test_full_pipeline_with_test_document() -> Result<()> {
     // Process first page only for fast testing
     let page_idx = 0;
     let rendered = renderer.render_page(&test_pdf, page_idx)?;
-    let parser_hypo = parser_track.analyze_page(page_idx)?;
+    let geometry = PageGeometry::new(
+        parser_track.page_size(page_idx)?,
+        rendered.width,
+        rendered.height,
+        200,
+    );
+    let parser_hypo = parser_track.analyze_page(page_idx)?.transformed(
+        &geometry.page_to_image,
+        rendered.width,
+        rendered.height,
+    );
     let ocr_hypo = ocr_track.analyze_page(&rendered.path, page_idx)?;
 
     let mut fused = fusion.fuse(&parser_hypo, &ocr_hypo)?;
-    fused.debug = Some(PageDebug {
-        parser_blocks: parser_hypo.blocks.clone(),
-        ocr_blocks: ocr_hypo.blocks.clone(),
-    });
+    fused.geometry = Some(geometry);
+    let debug = fused.debug.get_or_insert_with(PageDebug::default);
+    debug.parser_blocks = parser_hypo.blocks.clone();
+    debug.ocr_blocks = ocr_hypo.blocks.clone();
     pages.push(fused);
 
-    let document = DocumentFinal { pages };
+    let document = DocumentFinal {
+        pages,
+        outline: vec![],
+        metadata: Default::default(),
+    };
 
     // Export JSON
     let json_exporter = JsonExporter::new(out.clone());